@@ -0,0 +1,216 @@
+//! # Address Module
+//!
+//! This module parses and validates the email addresses accepted by the Resend API,
+//! either bare (`ops@acme.com`) or with a display name
+//! (`Ops Team <ops@acme.com>`, `"Ops, Team" <ops@acme.com>`), so that mistyped
+//! recipients are reported before a request goes out.
+
+use anyhow::{bail, Result};
+use std::fmt;
+
+/// Maximum length of an email address, per RFC 5321
+const MAX_ADDRESS_LENGTH: usize = 254;
+
+/// Characters not allowed in the local part of an unquoted address
+const FORBIDDEN_LOCAL_CHARS: &[char] = &['<', '>', '(', ')', '[', ']', '\\', ',', ';', ':', '"'];
+
+/// An email address with an optional display name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    /// Display name, e.g. `Ops Team`
+    pub name: Option<String>,
+    /// Email address, e.g. `ops@acme.com`
+    pub email: String,
+}
+
+impl Address {
+    /// Parses an address, with or without a display name
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Address such as `ops@acme.com` or `"Ops Team" <ops@acme.com>`
+    ///
+    /// # Returns
+    ///
+    /// The parsed address, or an error explaining what is wrong with it
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            bail!("the address is empty");
+        }
+
+        let (name, email) = match input.strip_suffix('>') {
+            Some(rest) => {
+                let Some((name, email)) = rest.rsplit_once('<') else {
+                    bail!("missing '<' before the address");
+                };
+                (parse_name(name.trim())?, email.trim())
+            }
+            None if input.contains('<') => bail!("missing '>' after the address"),
+            None => (None, input),
+        };
+        validate_email(email)?;
+        Ok(Self {
+            name,
+            email: email.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} <{}>", name, self.email),
+            None => write!(f, "{}", self.email),
+        }
+    }
+}
+
+/// Validates a list of addresses
+///
+/// # Arguments
+///
+/// * `field` - Name of the field holding the addresses, used in errors, e.g. `cc`
+/// * `addresses` - Addresses to validate
+///
+/// # Returns
+///
+/// Ok(()) if every address is valid, or an error naming the first invalid one
+pub fn validate_addresses<'a>(
+    field: &str,
+    addresses: impl IntoIterator<Item = &'a String>,
+) -> Result<()> {
+    for address in addresses {
+        if let Err(err) = Address::parse(address) {
+            bail!("Invalid {} address '{}': {}", field, address, err);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a display name, removing the quotes around it
+fn parse_name(name: &str) -> Result<Option<String>> {
+    let name = match name.strip_prefix('"') {
+        Some(quoted) => {
+            let Some(unquoted) = quoted.strip_suffix('"') else {
+                bail!("unbalanced quote in the display name");
+            };
+            unquoted.replace("\\\"", "\"")
+        }
+        None => {
+            if name.contains('"') {
+                bail!("unbalanced quote in the display name");
+            }
+            if name.contains(',') || name.contains(';') {
+                bail!("quote display names containing ',' or ';'");
+            }
+            name.to_string()
+        }
+    };
+    if name.contains('<') || name.contains('>') {
+        bail!("the display name cannot contain '<' or '>'");
+    }
+    Ok(Some(name).filter(|name| !name.is_empty()))
+}
+
+/// Validates a bare email address such as `ops@acme.com`
+fn validate_email(email: &str) -> Result<()> {
+    if email.len() > MAX_ADDRESS_LENGTH {
+        bail!(
+            "the address is longer than {} characters",
+            MAX_ADDRESS_LENGTH
+        );
+    }
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        bail!("missing '@'");
+    };
+    if local.is_empty() {
+        bail!("missing the part before '@'");
+    }
+    if local.contains('@')
+        || local
+            .chars()
+            .any(|c| c.is_whitespace() || FORBIDDEN_LOCAL_CHARS.contains(&c))
+    {
+        bail!("invalid characters before '@'");
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        bail!("misplaced '.' before '@'");
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        bail!("the domain '{}' has no top-level domain", domain);
+    }
+    for label in labels {
+        if label.is_empty()
+            || label.starts_with('-')
+            || label.ends_with('-')
+            || !label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        {
+            bail!("invalid domain '{}'", domain);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_addresses() {
+        assert_eq!(
+            Address::parse("ops@acme.com").unwrap(),
+            Address {
+                name: None,
+                email: "ops@acme.com".to_string()
+            }
+        );
+        assert_eq!(
+            Address::parse(" Ops Team <ops@x.com> ").unwrap(),
+            Address {
+                name: Some("Ops Team".to_string()),
+                email: "ops@x.com".to_string()
+            }
+        );
+        let quoted = Address::parse(r#""Ops, \"Night\" Team" <ops+alerts@mail.x.co.uk>"#).unwrap();
+        assert_eq!(quoted.name.as_deref(), Some(r#"Ops, "Night" Team"#));
+        assert_eq!(quoted.email, "ops+alerts@mail.x.co.uk");
+        assert_eq!(Address::parse("<ops@x.com>").unwrap().name, None);
+        assert_eq!(
+            Address::parse("Ops Team <ops@x.com>").unwrap().to_string(),
+            "Ops Team <ops@x.com>"
+        );
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        for (address, reason) in [
+            ("", "empty"),
+            ("ops.acme.com", "missing '@'"),
+            ("@acme.com", "before '@'"),
+            ("ops@acme", "no top-level domain"),
+            ("ops@acme..com", "invalid domain"),
+            ("ops@-acme.com", "invalid domain"),
+            ("o ps@acme.com", "invalid characters"),
+            ("ops@@acme.com", "invalid characters"),
+            ("ops.@acme.com", "misplaced '.'"),
+            ("Ops <ops@acme.com", "missing '>'"),
+            ("Ops ops@acme.com>", "missing '<'"),
+            ("Ops, Team <ops@acme.com>", "quote display names"),
+            ("\"Ops <ops@acme.com>", "unbalanced quote"),
+        ] {
+            let err = Address::parse(address).unwrap_err().to_string();
+            assert!(err.contains(reason), "{}: {}", address, err);
+        }
+    }
+
+    #[test]
+    fn test_validate_addresses_names_the_field() {
+        let addresses = vec!["ok@acme.com".to_string(), "oops".to_string()];
+        assert!(validate_addresses("cc", &addresses[..1]).is_ok());
+        let err = validate_addresses("cc", &addresses).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cc address 'oops': missing '@'");
+    }
+}
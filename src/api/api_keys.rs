@@ -46,4 +46,7 @@ pub struct ApiKey {
 pub struct ListApiKeysResponse {
     /// Array of API key objects
     pub data: Vec<ApiKey>,
+    /// Whether more items are available beyond this page
+    #[serde(default)]
+    pub has_more: bool,
 }
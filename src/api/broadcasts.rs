@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateBroadcastRequest {
@@ -22,16 +23,20 @@ pub struct UpdateBroadcastRequest {
     pub reply_to: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Broadcast {
     pub id: String,
+    #[tabled(display_with = "crate::output::display::option")]
     pub name: Option<String>,
     pub status: String,
     pub created_at: String,
+    #[tabled(display_with = "crate::output::display::option")]
     pub segment_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListBroadcastsResponse {
     pub data: Vec<Broadcast>,
+    #[serde(default)]
+    pub has_more: bool,
 }
@@ -0,0 +1,323 @@
+//! # Client Builder Module
+//!
+//! This module provides `ResendClientBuilder`, the stable way to construct a
+//! `ResendClient` when embedding it in another application. Every setting has a
+//! sensible default, so only the API key is required:
+//!
+//! ```no_run
+//! use resend_cli::api::{ResendApi, ResendClient};
+//! use std::time::Duration;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = ResendClient::builder("re_123")
+//!     .timeout(Duration::from_secs(10))
+//!     .max_attempts(5)
+//!     .build()?;
+//! let domains = client.list_domains(Default::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    rate_limit, Cassette, RateLimiter, ResendClient, RetryPolicy, Tracer, DEFAULT_BASE_URL,
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT,
+};
+use crate::config::Config;
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder for [`ResendClient`]
+///
+/// Created with [`ResendClient::builder`]. Durations of zero disable the
+/// corresponding timeout, and a rate of zero disables client-side rate limiting.
+#[must_use]
+pub struct ResendClientBuilder {
+    api_key: String,
+    base_url: String,
+    retry: RetryPolicy,
+    requests_per_second: f64,
+    timeout: Duration,
+    connect_timeout: Duration,
+    proxy: Option<String>,
+    ca_cert: Option<PathBuf>,
+    http_client: Option<Client>,
+    tracer: Option<Tracer>,
+    cassette: Option<Cassette>,
+}
+
+impl ResendClientBuilder {
+    /// Creates a builder with default settings
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - API key for authenticating with the Resend API
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
+            requests_per_second: rate_limit::DEFAULT_REQUESTS_PER_SECOND,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            proxy: None,
+            ca_cert: None,
+            http_client: None,
+            tracer: None,
+            cassette: None,
+        }
+    }
+
+    /// Applies every setting present in a configuration
+    ///
+    /// Settings left unset in `config` keep their current value. The API key
+    /// is not changed.
+    ///
+    /// # Returns
+    ///
+    /// The updated builder, or an error if a timeout is negative, not finite or
+    /// too large, or if the request rate is out of range
+    pub fn config(mut self, config: &Config) -> Result<Self> {
+        if let Some(base_url) = &config.base_url {
+            self = self.base_url(base_url);
+        }
+        if let Some(max_attempts) = config.max_attempts {
+            self = self.max_attempts(max_attempts);
+        }
+        if let Some(requests_per_second) = config.requests_per_second {
+            crate::config::check_requests_per_second(requests_per_second)
+                .context("Invalid request rate")?;
+            self = self.requests_per_second(requests_per_second);
+        }
+        if let Some(timeout) = config.timeout {
+            self = self.timeout(duration("timeout", timeout)?);
+        }
+        if let Some(connect_timeout) = config.connect_timeout {
+            self = self.connect_timeout(duration("connect timeout", connect_timeout)?);
+        }
+        if let Some(proxy) = &config.proxy {
+            self = self.proxy(proxy);
+        }
+        if let Some(ca_cert) = &config.ca_cert {
+            self = self.ca_cert(ca_cert);
+        }
+        Ok(self)
+    }
+
+    /// Sets the base URL of the API (defaults to [`DEFAULT_BASE_URL`])
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the maximum number of attempts per request, including the first one
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.retry.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Replaces the whole retry policy
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the client-side request quota, in requests per second (0 disables it)
+    pub fn requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = requests_per_second;
+        self
+    }
+
+    /// Sets the timeout for a whole request (zero disables it)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection (zero disables it)
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sends every request through a proxy
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Trusts the CA certificates of a PEM file in addition to the system ones
+    pub fn ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_cert = Some(path.into());
+        self
+    }
+
+    /// Uses an existing HTTP client, ignoring timeout, proxy and TLS settings
+    ///
+    /// Useful to share a connection pool with the rest of an application.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Traces every HTTP exchange
+    pub fn tracer(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Records every HTTP exchange to a cassette, or replays them from it
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Builds the client
+    ///
+    /// # Returns
+    ///
+    /// The configured client, or an error if the proxy URL or CA certificate
+    /// file is invalid
+    pub fn build(self) -> Result<ResendClient> {
+        let client = match self.http_client {
+            Some(client) => client,
+            None => http_client(
+                self.timeout,
+                self.connect_timeout,
+                self.proxy.as_deref(),
+                self.ca_cert.as_deref(),
+            )?,
+        };
+        let rate_limiter = (self.requests_per_second > 0.0)
+            .then(|| Arc::new(RateLimiter::new(self.requests_per_second)));
+
+        Ok(ResendClient {
+            client,
+            api_key: self.api_key,
+            base_url: self.base_url,
+            retry: self.retry,
+            rate_limiter,
+            tracer: self.tracer.filter(Tracer::is_enabled).map(Arc::new),
+            cassette: self.cassette.map(Arc::new),
+        })
+    }
+}
+
+/// Converts a configured number of seconds into a duration
+fn duration(setting: &str, seconds: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| anyhow!("Invalid {} of {} seconds", setting, seconds))
+}
+
+/// Builds the underlying HTTP client from the timeout, proxy and TLS settings
+fn http_client(
+    timeout: Duration,
+    connect_timeout: Duration,
+    proxy: Option<&str>,
+    ca_cert: Option<&std::path::Path>,
+) -> Result<Client> {
+    let mut builder = Client::builder();
+    if !timeout.is_zero() {
+        builder = builder.timeout(timeout);
+    }
+    if !connect_timeout.is_zero() {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+
+    if let Some(proxy) = proxy {
+        let proxy =
+            reqwest::Proxy::all(proxy).with_context(|| format!("Invalid proxy URL {}", proxy))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = ca_cert {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Could not read CA certificate {}", path.display()))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid CA certificate {}", path.display()))?;
+        if certificates.is_empty() {
+            return Err(anyhow!("No certificate found in {}", path.display()));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder.build().context("Could not build the HTTP client")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let client = ResendClient::builder("re_123").build().unwrap();
+        assert_eq!(client.api_key, "re_123");
+        assert_eq!(client.base_url, DEFAULT_BASE_URL);
+        assert_eq!(client.retry, RetryPolicy::default());
+        assert!(client.rate_limiter.is_some());
+        assert!(client.tracer.is_none());
+    }
+
+    #[test]
+    fn test_builder_settings() {
+        let client = ResendClient::builder("re_123")
+            .base_url("http://127.0.0.1:4010/")
+            .max_attempts(0)
+            .requests_per_second(0.0)
+            .tracer(Tracer::default())
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "http://127.0.0.1:4010");
+        assert_eq!(client.retry.max_attempts, 1);
+        assert!(client.rate_limiter.is_none());
+        // A tracer without any destination is dropped
+        assert!(client.tracer.is_none());
+    }
+
+    #[test]
+    fn test_config_overrides_only_set_values() {
+        let config = Config {
+            api_key: "ignored".to_string(),
+            max_attempts: Some(7),
+            ..Default::default()
+        };
+        let client = ResendClient::builder("re_123")
+            .base_url("http://localhost")
+            .config(&config)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.api_key, "re_123");
+        assert_eq!(client.base_url, "http://localhost");
+        assert_eq!(client.retry.max_attempts, 7);
+    }
+
+    #[test]
+    fn test_config_rejects_invalid_numbers() {
+        for timeout in [-1.0, f64::INFINITY, f64::NAN, 1e30] {
+            let config = Config {
+                timeout: Some(timeout),
+                ..Default::default()
+            };
+            let err = ResendClient::builder("re_123")
+                .config(&config)
+                .err()
+                .unwrap();
+            assert!(err.to_string().starts_with("Invalid timeout"), "{}", err);
+        }
+
+        let config = Config {
+            requests_per_second: Some(1e-300),
+            ..Default::default()
+        };
+        let err = ResendClient::builder("re_123")
+            .config(&config)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Invalid request rate");
+    }
+}
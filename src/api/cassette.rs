@@ -0,0 +1,414 @@
+//! # Cassette Module
+//!
+//! This module records and replays the HTTP exchanges of `ResendClient`, so that
+//! scripts shelling out to the CLI can be tested deterministically and offline.
+//!
+//! In record mode, every request/response pair is appended to a JSON cassette
+//! file as it happens, one interaction per line. In replay mode, responses are
+//! served from that file without touching the network: each request is matched
+//! against the first unused interaction with the same method, path, query string
+//! and JSON body, and a request without a match fails.
+//!
+//! Request headers are never recorded, and the `token` fields of response bodies,
+//! such as the key returned by `api-keys create`, are redacted, so cassettes do
+//! not contain API keys.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Whether a cassette is being recorded or replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CassetteMode {
+    /// Send requests to the API and save every exchange to the cassette
+    Record,
+    /// Serve responses from the cassette without touching the network
+    Replay,
+}
+
+/// Recorded request, identified by method, path, query string and body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: String,
+    /// URL path, e.g. /emails
+    pub path: String,
+    /// Query string, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// JSON body, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// Recorded response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Response body, as JSON when it parses, `null` when empty
+    #[serde(default)]
+    pub body: Value,
+}
+
+/// One request/response pair of a cassette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request sent to the API
+    pub request: RecordedRequest,
+    /// The response received for it
+    pub response: RecordedResponse,
+}
+
+/// Content of a cassette file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Start of a recorded cassette file, before its interactions
+const CASSETTE_START: &str = "{\"interactions\": [\n";
+
+/// End of a recorded cassette file, overwritten by each appended interaction
+const CASSETTE_END: &str = "\n]}\n";
+
+/// Value replacing the redacted fields of recorded responses
+const REDACTED: &str = "[REDACTED]";
+
+/// Fields of response bodies that hold secrets
+const SECRET_FIELDS: &[&str] = &["token"];
+
+/// A cassette file being recorded or replayed
+#[derive(Debug)]
+pub struct Cassette {
+    /// Path of the cassette file
+    path: PathBuf,
+    /// Whether the cassette is recorded or replayed
+    mode: CassetteMode,
+    /// Replayed interactions, with whether each has been served
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+    /// In record mode, the cassette file and the number of interactions in it
+    recording: Option<Mutex<(File, usize)>>,
+}
+
+impl Cassette {
+    /// Opens a cassette
+    ///
+    /// In record mode the file is created or truncated. In replay mode it must
+    /// exist and contain a valid cassette.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the cassette file
+    /// * `mode` - Whether to record or replay the cassette
+    pub fn open(path: &Path, mode: CassetteMode) -> Result<Self> {
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Could not read cassette {}", path.display()))?;
+                let file: CassetteFile = serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid cassette {}", path.display()))?;
+                file.interactions
+            }
+        };
+        let recording = match mode {
+            CassetteMode::Record => {
+                let mut file = File::create(path)
+                    .with_context(|| format!("Could not write cassette {}", path.display()))?;
+                write!(file, "{}{}", CASSETTE_START, CASSETTE_END)
+                    .with_context(|| format!("Could not write cassette {}", path.display()))?;
+                Some(Mutex::new((file, 0)))
+            }
+            CassetteMode::Replay => None,
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            mode,
+            interactions: Mutex::new(interactions.into_iter().map(|i| (i, false)).collect()),
+            recording,
+        })
+    }
+
+    /// Returns whether responses are served from the cassette
+    pub fn is_replay(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    /// Serves the response recorded for a request
+    ///
+    /// # Returns
+    ///
+    /// The status, headers and body of the first unused matching interaction,
+    /// or an error message describing the unmatched request
+    pub fn replay(
+        &self,
+        request: &Request,
+    ) -> std::result::Result<(StatusCode, HeaderMap, String), String> {
+        let recorded = RecordedRequest::from_request(request);
+        let mut interactions = self.interactions.lock().unwrap_or_else(|e| e.into_inner());
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(interaction, used)| !*used && interaction.request == recorded)
+            .ok_or_else(|| {
+                let mut message = format!(
+                    "no recorded interaction matches {} {}",
+                    recorded.method, recorded.path
+                );
+                if let Some(query) = &recorded.query {
+                    message.push_str(&format!("?{}", query));
+                }
+                format!("{} in {}", message, self.path.display())
+            })?;
+        *used = true;
+        interaction.response.to_parts()
+    }
+
+    /// Appends an exchange to the cassette file
+    ///
+    /// Only the new interaction is written, over the end of the file, which stays
+    /// a valid cassette after each exchange.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The recorded request
+    /// * `status` - Status of the response
+    /// * `headers` - Headers of the response
+    /// * `body` - Body of the response
+    pub fn record(
+        &self,
+        request: RecordedRequest,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Result<()> {
+        let response = RecordedResponse {
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.to_string(), value.to_string()))
+                })
+                .collect(),
+            body: redact_secrets(parse_body(body)),
+        };
+        let Some(recording) = &self.recording else {
+            anyhow::bail!("Cassette {} is not being recorded", self.path.display());
+        };
+        let line = serde_json::to_string(&Interaction { request, response })?;
+        let mut recording = recording.lock().unwrap_or_else(|e| e.into_inner());
+        let (file, count) = &mut *recording;
+        let separator = if *count == 0 { "" } else { ",\n" };
+        file.seek(SeekFrom::End(-(CASSETTE_END.len() as i64)))
+            .and_then(|_| write!(file, "{}{}{}", separator, line, CASSETTE_END))
+            .with_context(|| format!("Could not write cassette {}", self.path.display()))?;
+        *count += 1;
+        Ok(())
+    }
+}
+
+impl RecordedRequest {
+    /// Captures the identifying parts of a request
+    pub fn from_request(request: &Request) -> Self {
+        let url = request.url();
+        Self {
+            method: request.method().to_string(),
+            path: url.path().to_string(),
+            query: url.query().map(str::to_string),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map(|b| parse_body(&String::from_utf8_lossy(b)))
+                .filter(|body| !body.is_null()),
+        }
+    }
+}
+
+impl RecordedResponse {
+    /// Converts the recorded response back into a status, headers and body
+    fn to_parts(&self) -> std::result::Result<(StatusCode, HeaderMap, String), String> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|_| format!("invalid recorded status {}", self.status))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        let body = match &self.body {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        Ok((status, headers, body))
+    }
+}
+
+/// Replaces the values of secret fields, at any depth, with a placeholder
+fn redact_secrets(mut body: Value) -> Value {
+    match &mut body {
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                *value = if SECRET_FIELDS.contains(&name.as_str()) && !value.is_null() {
+                    Value::String(REDACTED.to_string())
+                } else {
+                    redact_secrets(value.take())
+                };
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                *item = redact_secrets(item.take());
+            }
+        }
+        _ => {}
+    }
+    body
+}
+
+/// Parses a body as JSON, keeping it as a string when it is not valid JSON
+fn parse_body(body: &str) -> Value {
+    if body.trim().is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(method: reqwest::Method, url: &str, body: Option<&str>) -> Request {
+        let builder = reqwest::Client::new().request(method, url);
+        match body {
+            Some(body) => builder.body(body.to_string()),
+            None => builder,
+        }
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let recorder = Cassette::open(&path, CassetteMode::Record).unwrap();
+        let send = request(
+            reqwest::Method::POST,
+            "http://localhost/emails",
+            Some(r#"{"to":["a@example.com"],"subject":"Hi"}"#),
+        );
+        recorder
+            .record(
+                RecordedRequest::from_request(&send),
+                StatusCode::OK,
+                &HeaderMap::new(),
+                r#"{"id":"email_1"}"#,
+            )
+            .unwrap();
+        let delete = request(reqwest::Method::DELETE, "http://localhost/domains/d1", None);
+        recorder
+            .record(
+                RecordedRequest::from_request(&delete),
+                StatusCode::NO_CONTENT,
+                &HeaderMap::new(),
+                "",
+            )
+            .unwrap();
+
+        let player = Cassette::open(&path, CassetteMode::Replay).unwrap();
+        assert!(player.is_replay());
+
+        // Bodies match regardless of key order and base URL
+        let send = request(
+            reqwest::Method::POST,
+            "http://127.0.0.1:4010/emails",
+            Some(r#"{"subject":"Hi","to":["a@example.com"]}"#),
+        );
+        let (status, _, body) = player.replay(&send).unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"{"id":"email_1"}"#);
+
+        let (status, _, body) = player.replay(&delete).unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(body, "");
+    }
+
+    #[test]
+    fn test_unmatched_and_reused_requests_fail() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cassette.json");
+        std::fs::write(
+            &path,
+            r#"{"interactions":[{"request":{"method":"GET","path":"/domains","query":"limit=1"},"response":{"status":200,"body":{"data":[]}}}]}"#,
+        )
+        .unwrap();
+        let player = Cassette::open(&path, CassetteMode::Replay).unwrap();
+
+        let other = request(reqwest::Method::GET, "http://localhost/domains", None);
+        let err = player.replay(&other).unwrap_err();
+        assert!(err.contains("GET /domains"));
+
+        let list = request(
+            reqwest::Method::GET,
+            "http://localhost/domains?limit=1",
+            None,
+        );
+        assert!(player.replay(&list).is_ok());
+        assert!(player.replay(&list).is_err());
+    }
+
+    #[test]
+    fn test_recorded_tokens_are_redacted() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cassette.json");
+        let recorder = Cassette::open(&path, CassetteMode::Record).unwrap();
+        let create = request(
+            reqwest::Method::POST,
+            "http://localhost/api-keys",
+            Some(r#"{"name":"ci"}"#),
+        );
+        recorder
+            .record(
+                RecordedRequest::from_request(&create),
+                StatusCode::CREATED,
+                &HeaderMap::new(),
+                r#"{"id":"key_1","token":"re_secret","data":[{"token":"re_other"}]}"#,
+            )
+            .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("re_secret") && !content.contains("re_other"));
+
+        let player = Cassette::open(&path, CassetteMode::Replay).unwrap();
+        let (_, _, body) = player.replay(&create).unwrap();
+        assert_eq!(
+            body,
+            r#"{"id":"key_1","token":"[REDACTED]","data":[{"token":"[REDACTED]"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_missing_cassette_cannot_be_replayed() {
+        let dir = TempDir::new().unwrap();
+        assert!(Cassette::open(&dir.path().join("missing.json"), CassetteMode::Replay).is_err());
+    }
+}
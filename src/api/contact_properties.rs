@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateContactPropertyRequest {
@@ -13,12 +14,14 @@ pub struct UpdateContactPropertyRequest {
     pub fallback_value: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct ContactProperty {
     pub id: String,
     pub key: String,
     #[serde(rename = "type")]
+    #[tabled(rename = "type")]
     pub property_type: String,
+    #[tabled(display_with = "crate::output::display::option_json")]
     pub fallback_value: Option<serde_json::Value>,
     pub created_at: String,
 }
@@ -26,4 +29,6 @@ pub struct ContactProperty {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListContactPropertiesResponse {
     pub data: Vec<ContactProperty>,
+    #[serde(default)]
+    pub has_more: bool,
 }
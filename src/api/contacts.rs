@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateContactRequest {
@@ -24,11 +25,13 @@ pub struct UpdateContactRequest {
     pub unsubscribed: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Contact {
     pub id: String,
     pub email: String,
+    #[tabled(display_with = "crate::output::display::option")]
     pub first_name: Option<String>,
+    #[tabled(display_with = "crate::output::display::option")]
     pub last_name: Option<String>,
     pub created_at: String,
     pub unsubscribed: bool,
@@ -37,4 +40,6 @@ pub struct Contact {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListContactsResponse {
     pub data: Vec<Contact>,
+    #[serde(default)]
+    pub has_more: bool,
 }
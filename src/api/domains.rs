@@ -43,4 +43,7 @@ pub struct Domain {
 pub struct ListDomainsResponse {
     /// Array of domain objects
     pub data: Vec<Domain>,
+    /// Whether more items are available beyond this page
+    #[serde(default)]
+    pub has_more: bool,
 }
@@ -0,0 +1,724 @@
+//! # Dry Run Module
+//!
+//! This module provides `DryRunClient`, a `ResendApi` implementation that never
+//! touches the network. Every call prints the HTTP method, path and JSON body that
+//! `ResendClient` would send, then returns a synthetic response, so bulk and
+//! destructive commands can be reviewed before they reach production.
+
+use super::*;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Mutex;
+
+/// ID given to every resource returned by a dry run
+pub const DRY_RUN_ID: &str = "dry-run";
+
+/// Request that a dry run would have sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunRequest {
+    /// HTTP method
+    pub method: Method,
+    /// URL path, including the query string if any
+    pub path: String,
+    /// Value of the `Idempotency-Key` header, if any
+    pub idempotency_key: Option<String>,
+    /// JSON body, if any
+    pub body: Option<Value>,
+}
+
+impl fmt::Display for DryRunRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[dry-run] {} {}", self.method, self.path)?;
+        if let Some(key) = &self.idempotency_key {
+            write!(f, "\nIdempotency-Key: {}", key)?;
+        }
+        if let Some(body) = &self.body {
+            let body = serde_json::to_string_pretty(body).map_err(|_| fmt::Error)?;
+            write!(f, "\n{}", body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error for a resource that a dry run cannot read without calling the API
+///
+/// Commands that only need the resource to fill in defaults, such as sending from
+/// a template, can match it with `err.downcast_ref::<NotFetched>()` and go on
+/// without the resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFetched {
+    /// URL path of the resource
+    pub path: String,
+}
+
+impl fmt::Display for NotFetched {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not fetched in a dry run", self.path)
+    }
+}
+
+impl std::error::Error for NotFetched {}
+
+/// `ResendApi` implementation that prints requests instead of sending them
+#[derive(Debug, Default)]
+pub struct DryRunClient {
+    /// Whether to print requests to stderr
+    quiet: bool,
+    /// Requests made so far
+    requests: Mutex<Vec<DryRunRequest>>,
+}
+
+impl DryRunClient {
+    /// Creates a dry-run client printing every request to stderr
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the requests made so far
+    pub fn requests(&self) -> Vec<DryRunRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Records a request and prints it
+    fn record<B: Serialize>(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<&B>,
+        idempotency_key: Option<String>,
+    ) -> Result<()> {
+        let request = DryRunRequest {
+            method,
+            path,
+            idempotency_key,
+            body: body.map(serde_json::to_value).transpose()?,
+        };
+        if !self.quiet {
+            eprintln!("{}", request);
+        }
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(request);
+        Ok(())
+    }
+
+    /// Records a request without a body whose response is not needed
+    fn record_empty(&self, method: Method, path: String) -> Result<()> {
+        self.record(method, path, None::<&()>, None)
+    }
+
+    /// Records a request and returns a synthetic resource
+    ///
+    /// The resource is built from an ID and creation date, the given defaults,
+    /// and the fields of the request body, in increasing order of precedence.
+    fn respond<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<&B>,
+        defaults: Value,
+    ) -> Result<T> {
+        self.record(method, path, body, None)?;
+        let mut resource = json!({
+            "id": DRY_RUN_ID,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+        });
+        merge(&mut resource, defaults);
+        if let Some(body) = body {
+            merge(&mut resource, serde_json::to_value(body)?);
+        }
+        Ok(serde_json::from_value(resource)?)
+    }
+
+    /// Records a list request and returns an empty page
+    fn respond_list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        pagination: &PaginationOptions,
+    ) -> Result<T> {
+        let mut query = Vec::new();
+        if let Some(limit) = pagination.limit {
+            query.push(format!("limit={}", limit));
+        }
+        if let Some(after) = &pagination.after {
+            query.push(format!("after={}", after));
+        }
+        if let Some(before) = &pagination.before {
+            query.push(format!("before={}", before));
+        }
+        let path = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, query.join("&"))
+        };
+        self.record_empty(Method::GET, path)?;
+        Ok(serde_json::from_value(
+            json!({ "data": [], "has_more": false }),
+        )?)
+    }
+}
+
+/// Copies the non-null fields of `fields` into `target`
+fn merge(target: &mut Value, fields: Value) {
+    if let (Value::Object(target), Value::Object(fields)) = (target, fields) {
+        for (key, value) in fields {
+            if !value.is_null() {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Request body used for endpoints without one
+const NO_BODY: Option<&()> = None;
+
+#[async_trait]
+impl ResendApi for DryRunClient {
+    // Emails
+    async fn send_email(
+        &self,
+        request: emails::SendEmailRequest,
+        idempotency_key: Option<String>,
+    ) -> Result<emails::SendEmailResponse> {
+        self.record(
+            Method::POST,
+            "/emails".to_string(),
+            Some(&request),
+            idempotency_key,
+        )?;
+        Ok(emails::SendEmailResponse {
+            id: DRY_RUN_ID.to_string(),
+        })
+    }
+    async fn send_email_batch(
+        &self,
+        requests: Vec<emails::SendEmailRequest>,
+        idempotency_key: Option<String>,
+    ) -> Result<Vec<emails::SendEmailResponse>> {
+        self.record(
+            Method::POST,
+            "/emails/batch".to_string(),
+            Some(&requests),
+            idempotency_key,
+        )?;
+        Ok((1..=requests.len())
+            .map(|i| emails::SendEmailResponse {
+                id: format!("{}-{}", DRY_RUN_ID, i),
+            })
+            .collect())
+    }
+    async fn get_email(&self, id: &str) -> Result<emails::Email> {
+        self.respond(
+            Method::GET,
+            format!("/emails/{}", id),
+            NO_BODY,
+            json!({ "id": id, "from": "", "to": [], "subject": "", "last_event": "" }),
+        )
+    }
+    async fn list_emails(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<emails::ListEmailsResponse> {
+        self.respond_list("/emails", &pagination)
+    }
+    async fn cancel_email(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::POST, format!("/emails/{}/cancel", id))
+    }
+    async fn update_email(
+        &self,
+        id: &str,
+        request: emails::UpdateEmailRequest,
+    ) -> Result<emails::SendEmailResponse> {
+        self.respond(
+            Method::PATCH,
+            format!("/emails/{}", id),
+            Some(&request),
+            json!({ "id": id }),
+        )
+    }
+    async fn list_email_attachments(&self, id: &str) -> Result<emails::ListAttachmentsResponse> {
+        self.respond_list(
+            &format!("/emails/{}/attachments", id),
+            &PaginationOptions::default(),
+        )
+    }
+
+    // API Keys
+    async fn create_api_key(
+        &self,
+        request: api_keys::CreateApiKeyRequest,
+    ) -> Result<api_keys::ApiKey> {
+        self.respond(
+            Method::POST,
+            "/api-keys".to_string(),
+            Some(&request),
+            json!({ "token": format!("re_{}", DRY_RUN_ID) }),
+        )
+    }
+    async fn list_api_keys(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<api_keys::ListApiKeysResponse> {
+        self.respond_list("/api-keys", &pagination)
+    }
+    async fn delete_api_key(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/api-keys/{}", id))
+    }
+
+    // Domains
+    async fn create_domain(
+        &self,
+        request: domains::CreateDomainRequest,
+    ) -> Result<domains::Domain> {
+        self.respond(
+            Method::POST,
+            "/domains".to_string(),
+            Some(&request),
+            json!({ "status": "not_started", "region": "us-east-1" }),
+        )
+    }
+    async fn list_domains(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<domains::ListDomainsResponse> {
+        self.respond_list("/domains", &pagination)
+    }
+    async fn get_domain(&self, id: &str) -> Result<domains::Domain> {
+        self.respond(
+            Method::GET,
+            format!("/domains/{}", id),
+            NO_BODY,
+            json!({ "id": id, "name": "", "status": "", "region": "" }),
+        )
+    }
+    async fn delete_domain(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/domains/{}", id))
+    }
+    async fn verify_domain(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::POST, format!("/domains/{}/verify", id))
+    }
+
+    // Segments
+    async fn create_segment(&self, name: &str) -> Result<segments::Segment> {
+        let request = segments::CreateSegmentRequest {
+            name: name.to_string(),
+        };
+        self.respond(
+            Method::POST,
+            "/segments".to_string(),
+            Some(&request),
+            json!({}),
+        )
+    }
+    async fn list_segments(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<segments::ListSegmentsResponse> {
+        self.respond_list("/segments", &pagination)
+    }
+    async fn get_segment(&self, id: &str) -> Result<segments::Segment> {
+        self.respond(
+            Method::GET,
+            format!("/segments/{}", id),
+            NO_BODY,
+            json!({ "id": id, "name": "" }),
+        )
+    }
+    async fn delete_segment(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/segments/{}", id))
+    }
+
+    // Contacts
+    async fn create_contact(
+        &self,
+        request: contacts::CreateContactRequest,
+    ) -> Result<contacts::Contact> {
+        self.respond(
+            Method::POST,
+            "/contacts".to_string(),
+            Some(&request),
+            json!({ "unsubscribed": false }),
+        )
+    }
+    async fn list_contacts(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<contacts::ListContactsResponse> {
+        self.respond_list("/contacts", &pagination)
+    }
+    async fn get_contact(&self, id: &str) -> Result<contacts::Contact> {
+        self.respond(
+            Method::GET,
+            format!("/contacts/{}", id),
+            NO_BODY,
+            json!({ "id": id, "email": "", "unsubscribed": false }),
+        )
+    }
+    async fn update_contact(
+        &self,
+        id: &str,
+        request: contacts::UpdateContactRequest,
+    ) -> Result<contacts::Contact> {
+        self.respond(
+            Method::PATCH,
+            format!("/contacts/{}", id),
+            Some(&request),
+            json!({ "id": id, "email": "", "unsubscribed": false }),
+        )
+    }
+    async fn delete_contact(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/contacts/{}", id))
+    }
+    async fn add_contact_to_segment(&self, contact_id: &str, segment_id: &str) -> Result<()> {
+        self.record_empty(
+            Method::POST,
+            format!("/contacts/{}/segments/{}", contact_id, segment_id),
+        )
+    }
+    async fn delete_contact_from_segment(&self, contact_id: &str, segment_id: &str) -> Result<()> {
+        self.record_empty(
+            Method::DELETE,
+            format!("/contacts/{}/segments/{}", contact_id, segment_id),
+        )
+    }
+
+    // Templates
+    async fn create_template(
+        &self,
+        request: templates::CreateTemplateRequest,
+    ) -> Result<templates::Template> {
+        self.respond(
+            Method::POST,
+            "/templates".to_string(),
+            Some(&request),
+            json!({}),
+        )
+    }
+    async fn list_templates(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<templates::ListTemplatesResponse> {
+        self.respond_list("/templates", &pagination)
+    }
+    async fn get_template(&self, id: &str) -> Result<templates::Template> {
+        // A made-up template would hide a wrong ID, sender, subject or variables
+        let path = format!("/templates/{}", id);
+        self.record_empty(Method::GET, path.clone())?;
+        if !self.quiet {
+            eprintln!(
+                "[dry-run] Template {} is not fetched: its sender, subject and required variables are not checked",
+                id
+            );
+        }
+        Err(NotFetched { path }.into())
+    }
+    async fn update_template(
+        &self,
+        id: &str,
+        request: templates::UpdateTemplateRequest,
+    ) -> Result<templates::Template> {
+        self.respond(
+            Method::PATCH,
+            format!("/templates/{}", id),
+            Some(&request),
+            json!({ "id": id, "name": "" }),
+        )
+    }
+    async fn delete_template(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/templates/{}", id))
+    }
+
+    // Topics
+    async fn create_topic(&self, request: topics::CreateTopicRequest) -> Result<topics::Topic> {
+        self.respond(
+            Method::POST,
+            "/topics".to_string(),
+            Some(&request),
+            json!({}),
+        )
+    }
+    async fn list_topics(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<topics::ListTopicsResponse> {
+        self.respond_list("/topics", &pagination)
+    }
+    async fn get_topic(&self, id: &str) -> Result<topics::Topic> {
+        self.respond(
+            Method::GET,
+            format!("/topics/{}", id),
+            NO_BODY,
+            json!({ "id": id, "name": "" }),
+        )
+    }
+    async fn update_topic(
+        &self,
+        id: &str,
+        request: topics::UpdateTopicRequest,
+    ) -> Result<topics::Topic> {
+        self.respond(
+            Method::PATCH,
+            format!("/topics/{}", id),
+            Some(&request),
+            json!({ "id": id, "name": "" }),
+        )
+    }
+    async fn delete_topic(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/topics/{}", id))
+    }
+
+    // Webhooks
+    async fn create_webhook(
+        &self,
+        request: webhooks::CreateWebhookRequest,
+    ) -> Result<webhooks::Webhook> {
+        self.respond(
+            Method::POST,
+            "/webhooks".to_string(),
+            Some(&request),
+            json!({}),
+        )
+    }
+    async fn list_webhooks(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<webhooks::ListWebhooksResponse> {
+        self.respond_list("/webhooks", &pagination)
+    }
+    async fn get_webhook(&self, id: &str) -> Result<webhooks::Webhook> {
+        self.respond(
+            Method::GET,
+            format!("/webhooks/{}", id),
+            NO_BODY,
+            json!({ "id": id }),
+        )
+    }
+    async fn delete_webhook(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/webhooks/{}", id))
+    }
+
+    // Broadcasts
+    async fn create_broadcast(
+        &self,
+        request: broadcasts::CreateBroadcastRequest,
+    ) -> Result<broadcasts::Broadcast> {
+        self.respond(
+            Method::POST,
+            "/broadcasts".to_string(),
+            Some(&request),
+            json!({ "status": "draft" }),
+        )
+    }
+    async fn list_broadcasts(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<broadcasts::ListBroadcastsResponse> {
+        self.respond_list("/broadcasts", &pagination)
+    }
+    async fn get_broadcast(&self, id: &str) -> Result<broadcasts::Broadcast> {
+        self.respond(
+            Method::GET,
+            format!("/broadcasts/{}", id),
+            NO_BODY,
+            json!({ "id": id, "status": "" }),
+        )
+    }
+    async fn update_broadcast(
+        &self,
+        id: &str,
+        request: broadcasts::UpdateBroadcastRequest,
+    ) -> Result<broadcasts::Broadcast> {
+        self.respond(
+            Method::PATCH,
+            format!("/broadcasts/{}", id),
+            Some(&request),
+            json!({ "id": id, "status": "draft" }),
+        )
+    }
+    async fn delete_broadcast(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/broadcasts/{}", id))
+    }
+    async fn send_broadcast(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::POST, format!("/broadcasts/{}/send", id))
+    }
+
+    // Contact Properties
+    async fn create_contact_property(
+        &self,
+        request: contact_properties::CreateContactPropertyRequest,
+    ) -> Result<contact_properties::ContactProperty> {
+        self.respond(
+            Method::POST,
+            "/contact-properties".to_string(),
+            Some(&request),
+            json!({}),
+        )
+    }
+    async fn list_contact_properties(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<contact_properties::ListContactPropertiesResponse> {
+        self.respond_list("/contact-properties", &pagination)
+    }
+    async fn get_contact_property(&self, id: &str) -> Result<contact_properties::ContactProperty> {
+        self.respond(
+            Method::GET,
+            format!("/contact-properties/{}", id),
+            NO_BODY,
+            json!({ "id": id, "key": "", "type": "string" }),
+        )
+    }
+    async fn update_contact_property(
+        &self,
+        id: &str,
+        request: contact_properties::UpdateContactPropertyRequest,
+    ) -> Result<contact_properties::ContactProperty> {
+        self.respond(
+            Method::PATCH,
+            format!("/contact-properties/{}", id),
+            Some(&request),
+            json!({ "id": id, "key": "", "type": "string" }),
+        )
+    }
+    async fn delete_contact_property(&self, id: &str) -> Result<()> {
+        self.record_empty(Method::DELETE, format!("/contact-properties/{}", id))
+    }
+
+    // Receiving
+    async fn list_received_emails(
+        &self,
+        pagination: PaginationOptions,
+    ) -> Result<receiving::ListReceivedEmailsResponse> {
+        self.respond_list("/emails/receiving", &pagination)
+    }
+    async fn get_received_email(&self, id: &str) -> Result<serde_json::Value> {
+        self.respond(
+            Method::GET,
+            format!("/emails/receiving/{}", id),
+            NO_BODY,
+            json!({ "id": id, "object": "email" }),
+        )
+    }
+    async fn list_received_attachments(
+        &self,
+        id: &str,
+    ) -> Result<receiving::ListReceivedAttachmentsResponse> {
+        self.respond_list(
+            &format!("/emails/receiving/{}/attachments", id),
+            &PaginationOptions::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet() -> DryRunClient {
+        DryRunClient {
+            quiet: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_email_is_recorded_not_sent() {
+        let client = quiet();
+        let request = emails::SendEmailRequest {
+            text: Some("Hi".to_string()),
+            ..emails::SendEmailRequest::new(
+                "hi@acme.com",
+                vec!["user@example.com".to_string()],
+                "Hello",
+            )
+        };
+        let response = client
+            .send_email(request, Some("order-1".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.id, DRY_RUN_ID);
+
+        let requests = client.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::POST);
+        assert_eq!(requests[0].path, "/emails");
+        assert_eq!(requests[0].idempotency_key.as_deref(), Some("order-1"));
+        assert_eq!(requests[0].body.as_ref().unwrap()["subject"], "Hello");
+        assert!(requests[0]
+            .to_string()
+            .starts_with("[dry-run] POST /emails\nIdempotency-Key: order-1\n{"));
+    }
+
+    #[tokio::test]
+    async fn test_synthetic_responses_echo_the_request() {
+        let client = quiet();
+        let domain = client
+            .create_domain(domains::CreateDomainRequest {
+                name: "acme.com".to_string(),
+                region: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(domain.id, DRY_RUN_ID);
+        assert_eq!(domain.name, "acme.com");
+        assert_eq!(domain.region, "us-east-1");
+
+        let broadcast = client.get_broadcast("b_1").await.unwrap();
+        assert_eq!(broadcast.id, "b_1");
+
+        client.send_broadcast("b_1").await.unwrap();
+        client.delete_contact("c_1").await.unwrap();
+        let paths: Vec<String> = client.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "/domains",
+                "/broadcasts/b_1",
+                "/broadcasts/b_1/send",
+                "/contacts/c_1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_are_empty_and_keep_pagination() {
+        let client = quiet();
+        let page = client
+            .list_contacts(PaginationOptions {
+                limit: Some(10),
+                after: Some("c_9".to_string()),
+                before: None,
+            })
+            .await
+            .unwrap();
+        assert!(page.data.is_empty());
+        assert!(!page.has_more);
+        assert_eq!(client.requests()[0].path, "/contacts?limit=10&after=c_9");
+    }
+
+    #[tokio::test]
+    async fn test_templates_are_not_made_up() {
+        let client = quiet();
+        let err = client.get_template("welcome").await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<NotFetched>(),
+            Some(&NotFetched {
+                path: "/templates/welcome".to_string()
+            })
+        );
+        assert_eq!(client.requests()[0].path, "/templates/welcome");
+    }
+
+    #[tokio::test]
+    async fn test_batch_returns_one_id_per_email() {
+        let client = quiet();
+        let responses = client.send_email_batch(Vec::new(), None).await.unwrap();
+        assert!(responses.is_empty());
+        assert_eq!(client.requests()[0].body, Some(json!([])));
+    }
+}
@@ -3,19 +3,37 @@
 //! This module defines the data structures used for email operations in the Resend API.
 //! It includes request and response types for sending, retrieving, and managing emails.
 
+use crate::api::address::validate_addresses;
+use crate::api::inline_images::embed_local_images;
+use anyhow::{bail, Context};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 use tabled::Tabled;
 
 /// Request structure for sending an email
 ///
 /// This struct contains all the parameters needed to send an email through the Resend API.
+/// Create it with [`SendEmailRequest::new`] and set the optional fields, since new
+/// fields may be added in any release.
 #[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SendEmailRequest {
-    /// Sender's email address
+    /// Sender's email address, which may be left out of batch files to use the
+    /// sender of the template or the default sender of the profile
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub from: String,
     /// Recipient email addresses
     pub to: Vec<String>,
-    /// Email subject line
+    /// Email subject line, which may be left out of batch files for emails
+    /// sent from a template with a subject
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub subject: String,
     /// HTML content of the email (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -35,6 +53,471 @@ pub struct SendEmailRequest {
     /// Scheduled delivery time for the email (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scheduled_at: Option<String>,
+    /// Files attached to the email (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<EmailAttachment>>,
+    /// Custom headers of the email, e.g. `List-Unsubscribe` (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, String>>,
+    /// Tags for filtering and analytics (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Tag>>,
+    /// Template the email is rendered from, instead of `html` and `text` (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<EmailTemplate>,
+}
+
+/// Template an email is rendered from, with the values of its variables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailTemplate {
+    /// ID or alias of the template
+    pub id: String,
+    /// Values of the template variables, by key
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub variables: Map<String, Value>,
+}
+
+/// Maximum length of a tag name or value
+pub const MAX_TAG_LENGTH: usize = 256;
+
+/// Name/value pair attached to an email, e.g. `campaign=spring-sale`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+    /// Name of the tag
+    pub name: String,
+    /// Value of the tag
+    pub value: String,
+}
+
+impl Tag {
+    /// Validates the tag against Resend's rules
+    ///
+    /// Names and values may only contain ASCII letters, digits, underscores and
+    /// dashes, and are at most 256 characters long.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (part, text) in [("name", &self.name), ("value", &self.value)] {
+            if text.is_empty() || text.len() > MAX_TAG_LENGTH {
+                bail!(
+                    "Invalid tag {} '{}': must be 1 to {} characters long",
+                    part,
+                    text,
+                    MAX_TAG_LENGTH
+                );
+            }
+            if !text
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                bail!(
+                    "Invalid tag {} '{}': only ASCII letters, digits, '_' and '-' are allowed",
+                    part,
+                    text
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Tag {
+    type Err = anyhow::Error;
+
+    /// Parses and validates a tag given as `name=value`
+    fn from_str(tag: &str) -> anyhow::Result<Self> {
+        let Some((name, value)) = tag.split_once('=') else {
+            bail!("Tag '{}' must be given as name=value", tag);
+        };
+        let tag = Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        tag.validate()?;
+        Ok(tag)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// Parses a custom header given as `Name:Value`
+///
+/// # Arguments
+///
+/// * `header` - Header such as `X-Entity-Ref-ID: 1234`
+///
+/// # Returns
+///
+/// The header name and value, with surrounding whitespace trimmed
+pub fn parse_header(header: &str) -> anyhow::Result<(String, String)> {
+    let Some((name, value)) = header.split_once(':') else {
+        bail!("Header '{}' must be given as Name:Value", header);
+    };
+    let (name, value) = (name.trim(), value.trim());
+    validate_header(name, value)?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Validates a header name (an HTTP token) and value (a single line)
+fn validate_header(name: &str, value: &str) -> anyhow::Result<()> {
+    let token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() || !name.chars().all(token) {
+        bail!("Invalid header name '{}'", name);
+    }
+    if value.contains(['\r', '\n']) {
+        bail!(
+            "Invalid value of header '{}': line breaks are not allowed",
+            name
+        );
+    }
+    Ok(())
+}
+
+impl SendEmailRequest {
+    /// Creates a request with the required fields, leaving every optional one unset
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Sender's email address
+    /// * `to` - Recipient email addresses
+    /// * `subject` - Email subject line
+    pub fn new(from: impl Into<String>, to: Vec<String>, subject: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to,
+            subject: subject.into(),
+            html: None,
+            text: None,
+            cc: None,
+            bcc: None,
+            reply_to: None,
+            scheduled_at: None,
+            attachments: None,
+            headers: None,
+            tags: None,
+            template: None,
+        }
+    }
+}
+
+/// Maximum size of the attachments of an email once base64-encoded, in bytes
+pub const MAX_ATTACHMENTS_SIZE: usize = 40 * 1024 * 1024;
+
+/// File attached to an outgoing email
+///
+/// The file is either embedded as base64 `content`, or fetched by Resend from a
+/// remote `path` URL. Create it with [`EmailAttachment::from_bytes`],
+/// [`EmailAttachment::from_file`] or [`EmailAttachment::from_url`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EmailAttachment {
+    /// Name of the file as shown to recipients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Base64-encoded content of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// URL Resend fetches the file from, instead of `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// MIME type of the file (optional, derived from the filename if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Content ID referenced as `cid:<content_id>` by an inline image (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_id: Option<String>,
+}
+
+impl EmailAttachment {
+    /// Attaches in-memory content, encoding it and guessing its MIME type
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Name of the file as shown to recipients
+    /// * `bytes` - Content of the file
+    pub fn from_bytes(filename: impl Into<String>, bytes: &[u8]) -> Self {
+        let filename = filename.into();
+        Self {
+            content: Some(BASE64.encode(bytes)),
+            content_type: Some(guess_content_type(Path::new(&filename)).to_string()),
+            filename: Some(filename),
+            ..Default::default()
+        }
+    }
+
+    /// Attaches a local file, encoding its content and guessing its MIME type
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to attach
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Self::from_file_within(path, MAX_ATTACHMENTS_SIZE)
+    }
+
+    /// Attaches a local file like [`EmailAttachment::from_file`], if it fits in
+    /// the room left for the attachments of the email
+    ///
+    /// The size of the file is checked before it is read, so that a file too
+    /// large to send is never loaded into memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to attach
+    /// * `budget` - Bytes of encoded content the email can still carry
+    pub fn from_file_within(path: &Path, budget: usize) -> anyhow::Result<Self> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Could not read attachment {}", path.display()))?
+            .len();
+        let encoded = size.div_ceil(3).saturating_mul(4);
+        if encoded > budget as u64 {
+            bail!(
+                "Attachment {} is {} once encoded, over the {} left of Resend's limit of {} MB per email",
+                path.display(),
+                megabytes(encoded),
+                megabytes(budget as u64),
+                MAX_ATTACHMENTS_SIZE / (1024 * 1024)
+            );
+        }
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Could not read attachment {}", path.display()))?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        Ok(Self {
+            filename,
+            content_type: Some(guess_content_type(path).to_string()),
+            ..Self::from_bytes("", &bytes)
+        })
+    }
+
+    /// Attaches a remote file, which Resend downloads when sending the email
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - HTTP(S) URL of the file
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        if !is_url(url) {
+            bail!(
+                "Attachment URL '{}' must start with http:// or https://",
+                url
+            );
+        }
+        // The last segment of the URL path, ignoring the host and query string
+        let filename = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|url| url.split_once("://")?.1.split_once('/'))
+            .and_then(|(_, path)| path.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        Ok(Self {
+            filename,
+            path: Some(url.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Returns the size of the encoded content of the attachment, in bytes
+    pub fn content_size(&self) -> usize {
+        self.content.as_ref().map_or(0, String::len)
+    }
+
+    /// Returns whether the attachment is a local file that is not read yet
+    pub fn is_local(&self) -> bool {
+        self.content.is_none() && self.path.as_deref().is_some_and(|path| !is_url(path))
+    }
+
+    /// Reads the file of an attachment whose `path` is local rather than a URL
+    ///
+    /// Lets batch files attach local files the way `--attach` does. Attachments
+    /// with content or a remote URL are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_dir` - Directory relative paths are resolved from
+    /// * `budget` - Bytes of encoded content the email can still carry
+    pub fn load_local(self, base_dir: &Path, budget: usize) -> anyhow::Result<Self> {
+        match &self.path {
+            Some(path) if self.is_local() => {
+                let loaded = Self::from_file_within(&base_dir.join(path), budget)?;
+                Ok(Self {
+                    filename: self.filename.or(loaded.filename),
+                    content_type: self.content_type.or(loaded.content_type),
+                    content_id: self.content_id,
+                    ..loaded
+                })
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Formats a number of bytes as megabytes, e.g. `40.0 MB`
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Returns whether an attachment path is a remote URL
+fn is_url(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// Guesses the MIME type of a file from its extension
+///
+/// # Arguments
+///
+/// * `path` - Path or name of the file
+///
+/// # Returns
+///
+/// The MIME type, or `application/octet-stream` for unknown extensions
+pub fn guess_content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("bmp") => "image/bmp",
+        Some("ico") => "image/x-icon",
+        Some("tif" | "tiff") => "image/tiff",
+        Some("txt") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("csv") => "text/csv",
+        Some("html" | "htm") => "text/html",
+        Some("css") => "text/css",
+        Some("ics") => "text/calendar",
+        Some("xml") => "application/xml",
+        Some("json") => "application/json",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        Some("eml") => "message/rfc822",
+        Some("doc") => "application/msword",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("xls") => "application/vnd.ms-excel",
+        Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        Some("ppt") => "application/vnd.ms-powerpoint",
+        Some("pptx") => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        Some("odt") => "application/vnd.oasis.opendocument.text",
+        Some("ods") => "application/vnd.oasis.opendocument.spreadsheet",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("mp4") => "video/mp4",
+        Some("mov") => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+impl SendEmailRequest {
+    /// Embeds the local images of the HTML body as inline attachments
+    ///
+    /// Each `<img src>` pointing to a local file is rewritten to `cid:<content ID>`,
+    /// and the file is attached with that content ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_dir` - Directory relative image paths are resolved from
+    pub fn embed_local_images(&mut self, base_dir: &Path) -> anyhow::Result<()> {
+        let Some(html) = &self.html else {
+            return Ok(());
+        };
+        let budget = MAX_ATTACHMENTS_SIZE.saturating_sub(self.attachments_size());
+        let (html, images) = embed_local_images(html, base_dir, budget)?;
+        self.html = Some(html);
+        if !images.is_empty() {
+            self.attachments.get_or_insert_with(Vec::new).extend(images);
+        }
+        Ok(())
+    }
+
+    /// Validates the sender, subject, addresses, headers, tags and attachments of
+    /// the email
+    ///
+    /// An email sent from a template may leave out the sender and subject, which
+    /// the API then takes from the template.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) if there is a sender and a subject or a template, all addresses,
+    /// headers and tags are valid and the attachments fit Resend's size limit, or
+    /// an error describing the first problem
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.template.is_none() {
+            if self.from.trim().is_empty() {
+                bail!("The email has no sender");
+            }
+            if self.subject.trim().is_empty() {
+                bail!("The email has no subject");
+            }
+        }
+        validate_addresses("from", Some(&self.from).filter(|from| !from.is_empty()))?;
+        validate_addresses("to", &self.to)?;
+        validate_addresses("cc", self.cc.iter().flatten())?;
+        validate_addresses("bcc", self.bcc.iter().flatten())?;
+        validate_addresses("reply_to", self.reply_to.iter().flatten())?;
+        for (name, value) in self.headers.iter().flatten() {
+            validate_header(name, value)?;
+        }
+        for tag in self.tags.iter().flatten() {
+            tag.validate()?;
+        }
+
+        for attachment in self.attachments.iter().flatten() {
+            if attachment.content.is_some() == attachment.path.is_some() {
+                bail!(
+                    "Attachment {} must have either content or a path",
+                    attachment
+                        .filename
+                        .as_deref()
+                        .unwrap_or("without a filename")
+                );
+            }
+        }
+        let size = self.attachments_size();
+        if size > MAX_ATTACHMENTS_SIZE {
+            bail!(
+                "Attachments add up to {} once encoded, over Resend's limit of {} MB per email",
+                megabytes(size as u64),
+                MAX_ATTACHMENTS_SIZE / (1024 * 1024)
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the total size of the encoded content of the attachments, in bytes
+    pub fn attachments_size(&self) -> usize {
+        self.attachments
+            .iter()
+            .flatten()
+            .map(EmailAttachment::content_size)
+            .sum()
+    }
+}
+
+/// Derives an idempotency key from the content of a send request
+///
+/// The key is a SHA-256 hash of the serialized request, so sending the exact same
+/// email (or batch) again yields the same key and Resend drops the duplicate.
+///
+/// # Arguments
+///
+/// * `request` - The request (or batch of requests) to hash
+///
+/// # Returns
+///
+/// A key of the form `resend-cli-<sha256 hex digest>`
+pub fn derive_idempotency_key<T: Serialize>(request: &T) -> anyhow::Result<String> {
+    let body = serde_json::to_vec(request)?;
+    let digest = Sha256::digest(&body);
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("resend-cli-{}", hex))
 }
 
 /// Response structure for sending an email
@@ -46,17 +529,27 @@ pub struct SendEmailResponse {
     pub id: String,
 }
 
+/// Response structure for sending a batch of emails
+///
+/// The Resend API wraps the IDs of the sent emails in a `data` field.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendBatchResponse {
+    /// Responses for each email of the batch, in request order
+    pub data: Vec<SendEmailResponse>,
+}
+
 /// Data structure representing an email
 ///
 /// This struct contains information about an email in the Resend API.
 #[derive(Debug, Serialize, Deserialize, Tabled)]
+#[non_exhaustive]
 pub struct Email {
     /// Unique identifier for the email
     pub id: String,
     /// Sender's email address
     pub from: String,
     /// Recipient email addresses
-    #[tabled(display_with = "display_vec")]
+    #[tabled(display_with = "crate::output::display::list")]
     pub to: Vec<String>,
     /// Email subject line
     pub subject: String,
@@ -64,11 +557,41 @@ pub struct Email {
     pub created_at: String,
     /// Status of the last event for the email
     pub last_event: String,
+    /// Tags of the email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tabled(display_with = "crate::output::display::option_items")]
+    pub tags: Option<Vec<Tag>>,
 }
 
-/// Helper function to display vector values in tables
-fn display_vec(v: &[String]) -> String {
-    v.join(", ")
+impl Email {
+    /// Creates an email with the required fields, leaving every optional one unset
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Unique identifier for the email
+    /// * `from` - Sender's email address
+    /// * `to` - Recipient email addresses
+    /// * `subject` - Email subject line
+    /// * `created_at` - Creation timestamp of the email
+    /// * `last_event` - Status of the last event for the email
+    pub fn new(
+        id: impl Into<String>,
+        from: impl Into<String>,
+        to: Vec<String>,
+        subject: impl Into<String>,
+        created_at: impl Into<String>,
+        last_event: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            from: from.into(),
+            to,
+            subject: subject.into(),
+            created_at: created_at.into(),
+            last_event: last_event.into(),
+            tags: None,
+        }
+    }
 }
 
 /// Response structure for listing emails
@@ -78,6 +601,9 @@ fn display_vec(v: &[String]) -> String {
 pub struct ListEmailsResponse {
     /// Array of email objects
     pub data: Vec<Email>,
+    /// Whether more items are available beyond this page
+    #[serde(default)]
+    pub has_more: bool,
 }
 
 /// Request structure for updating an email
@@ -111,4 +637,219 @@ pub struct Attachment {
 pub struct ListAttachmentsResponse {
     /// Array of attachment objects
     pub data: Vec<Attachment>,
+    /// Whether more items are available beyond this page
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(subject: &str) -> SendEmailRequest {
+        SendEmailRequest {
+            text: Some("Hello".to_string()),
+            ..SendEmailRequest::new(
+                "sender@example.com",
+                vec!["recipient@example.com".to_string()],
+                subject,
+            )
+        }
+    }
+
+    #[test]
+    fn test_derive_idempotency_key_is_stable() {
+        let first = derive_idempotency_key(&request("Hello")).unwrap();
+        let second = derive_idempotency_key(&request("Hello")).unwrap();
+        let other = derive_idempotency_key(&request("Goodbye")).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert!(first.starts_with("resend-cli-"));
+        assert_eq!(first.len(), "resend-cli-".len() + 64);
+    }
+
+    #[test]
+    fn test_validate_checks_every_address() {
+        let mut email = request("Hello");
+        email.cc = Some(vec!["Ops Team <ops@x.com>".to_string()]);
+        assert!(email.validate().is_ok());
+
+        email.bcc = Some(vec!["audit@x".to_string()]);
+        let err = email.validate().unwrap_err().to_string();
+        assert!(err.starts_with("Invalid bcc address 'audit@x'"));
+
+        email.bcc = None;
+        email.from = "Acme".to_string();
+        assert!(email.validate().unwrap_err().to_string().contains("from"));
+
+        email.from = String::new();
+        let err = email.validate().unwrap_err().to_string();
+        assert_eq!(err, "The email has no sender");
+    }
+
+    #[test]
+    fn test_parse_headers_and_tags() {
+        assert_eq!(
+            parse_header("X-Entity-Ref-ID: 1234").unwrap(),
+            ("X-Entity-Ref-ID".to_string(), "1234".to_string())
+        );
+        assert_eq!(
+            parse_header("List-Unsubscribe:<https://acme.com/u?id=1>")
+                .unwrap()
+                .1,
+            "<https://acme.com/u?id=1>"
+        );
+        assert!(parse_header("X-Ref 1234").is_err());
+        assert!(parse_header("X Ref: 1234").is_err());
+        assert!(parse_header(": 1234").is_err());
+
+        let tag: Tag = "campaign=spring-sale_2".parse().unwrap();
+        assert_eq!(tag.name, "campaign");
+        assert_eq!(tag.value, "spring-sale_2");
+        assert_eq!(tag.to_string(), "campaign=spring-sale_2");
+        assert!("campaign".parse::<Tag>().is_err());
+        assert!("campaign=".parse::<Tag>().is_err());
+        let err = "campaign=spring sale"
+            .parse::<Tag>()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("only ASCII letters, digits, '_' and '-'"));
+        assert!(format!("t={}", "a".repeat(257)).parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn test_validate_checks_headers_and_tags() {
+        let mut email = request("Hello");
+        email.headers = Some(BTreeMap::from([(
+            "X-Entity-Ref-ID".to_string(),
+            "1234".to_string(),
+        )]));
+        email.tags = Some(vec![Tag {
+            name: "campaign".to_string(),
+            value: "launch".to_string(),
+        }]);
+        assert!(email.validate().is_ok());
+
+        email.headers = Some(BTreeMap::from([(
+            "X-Ref".to_string(),
+            "1\r\nBcc: x@acme.com".to_string(),
+        )]));
+        assert!(email.validate().is_err());
+
+        email.headers = None;
+        email.tags = Some(vec![Tag {
+            name: "campaign".to_string(),
+            value: "launch!".to_string(),
+        }]);
+        assert!(email.validate().is_err());
+    }
+
+    #[test]
+    fn test_email_deserializes_tags() {
+        let email: Email = serde_json::from_str(
+            r#"{"id":"e_1","from":"a@acme.com","to":["b@acme.com"],"subject":"S",
+                "created_at":"2023-01-01","last_event":"delivered",
+                "tags":[{"name":"campaign","value":"launch"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            email.tags,
+            Some(vec![Tag {
+                name: "campaign".to_string(),
+                value: "launch".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_attachments_from_files_and_urls() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("Report.PDF");
+        std::fs::write(&file, b"%PDF-1.4").unwrap();
+
+        let attachment = EmailAttachment::from_file(&file).unwrap();
+        assert_eq!(attachment.filename.as_deref(), Some("Report.PDF"));
+        assert_eq!(attachment.content.as_deref(), Some("JVBERi0xLjQ="));
+        assert_eq!(attachment.content_type.as_deref(), Some("application/pdf"));
+        assert!(EmailAttachment::from_file(&dir.path().join("missing.pdf")).is_err());
+
+        let remote =
+            EmailAttachment::from_url("https://cdn.acme.com/files/invoice.pdf?v=2").unwrap();
+        assert_eq!(remote.filename.as_deref(), Some("invoice.pdf"));
+        assert_eq!(
+            remote.path.as_deref(),
+            Some("https://cdn.acme.com/files/invoice.pdf?v=2")
+        );
+        assert!(EmailAttachment::from_url("https://acme.com")
+            .unwrap()
+            .filename
+            .is_none());
+        assert!(EmailAttachment::from_url("ftp://acme.com/a.pdf").is_err());
+
+        let local = EmailAttachment {
+            filename: Some("q3.pdf".to_string()),
+            path: Some("Report.PDF".to_string()),
+            ..Default::default()
+        };
+        let loaded = local.load_local(dir.path(), MAX_ATTACHMENTS_SIZE).unwrap();
+        assert_eq!(loaded.filename.as_deref(), Some("q3.pdf"));
+        assert_eq!(loaded.content, attachment.content);
+        assert!(loaded.path.is_none());
+        assert_eq!(
+            remote
+                .clone()
+                .load_local(dir.path(), MAX_ATTACHMENTS_SIZE)
+                .unwrap(),
+            remote
+        );
+    }
+
+    #[test]
+    fn test_oversized_files_are_rejected_before_reading() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("huge.bin");
+        // A sparse file: its size is checked, its content is never read
+        std::fs::File::create(&file)
+            .unwrap()
+            .set_len(50 * 1024 * 1024)
+            .unwrap();
+        let err = EmailAttachment::from_file(&file).unwrap_err().to_string();
+        assert!(err.contains("is 66.7 MB once encoded, over the 40.0 MB left"));
+
+        let small = dir.path().join("small.txt");
+        std::fs::write(&small, b"hello").unwrap();
+        assert!(EmailAttachment::from_file_within(&small, 8).is_ok());
+        let err = EmailAttachment::from_file_within(&small, 4)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("small.txt"));
+    }
+
+    #[test]
+    fn test_validate_limits_attachment_size() {
+        let attachment = |content: Option<String>| EmailAttachment {
+            filename: Some("big.bin".to_string()),
+            content,
+            ..Default::default()
+        };
+        let mut email = request("Hello");
+        email.attachments = Some(vec![
+            attachment(Some("A".repeat(MAX_ATTACHMENTS_SIZE / 2))),
+            attachment(Some("A".repeat(MAX_ATTACHMENTS_SIZE / 2))),
+        ]);
+        assert!(email.validate().is_ok());
+
+        email
+            .attachments
+            .as_mut()
+            .unwrap()
+            .push(attachment(Some("AAAA".to_string())));
+        let err = email.validate().unwrap_err().to_string();
+        assert!(err.contains("over Resend's limit of 40 MB per email"));
+
+        email.attachments = Some(vec![attachment(None)]);
+        let err = email.validate().unwrap_err().to_string();
+        assert!(err.contains("either content or a path"));
+    }
 }
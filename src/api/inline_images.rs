@@ -0,0 +1,186 @@
+//! # Inline Images Module
+//!
+//! This module embeds the local images referenced by an HTML body, such as
+//! `<img src="./logo.png">`, as inline attachments. Each image is attached once with
+//! a content ID, and its `src` is rewritten to `cid:<content ID>`, so that HTML
+//! previewed locally renders the same in inboxes.
+//!
+//! Only relative paths are local images: absolute ones such as `/images/logo.png`
+//! are site-root URLs of a web page, and are left as they are. Paths are
+//! percent-decoded, so `my%20logo.png` reads `my logo.png`.
+
+use crate::api::emails::EmailAttachment;
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Prefixes of image sources that are not local files
+const REMOTE_PREFIXES: &[&str] = &["cid:", "data:", "//", "#"];
+
+/// Rewrites the local images of an HTML body into inline attachments
+///
+/// # Arguments
+///
+/// * `html` - HTML body of the email
+/// * `base_dir` - Directory relative image paths are resolved from
+/// * `budget` - Bytes of encoded content the email can still carry
+///
+/// # Returns
+///
+/// The rewritten HTML and an attachment per distinct image, or an error if an
+/// image cannot be read or the images do not fit in `budget`
+pub fn embed_local_images(
+    html: &str,
+    base_dir: &Path,
+    mut budget: usize,
+) -> Result<(String, Vec<EmailAttachment>)> {
+    let pattern = Regex::new(r#"(?is)<img\b[^>]*?\ssrc\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#)
+        .expect("valid image pattern");
+
+    let mut rewritten = String::with_capacity(html.len());
+    let mut attachments: Vec<EmailAttachment> = Vec::new();
+    let mut content_ids: HashMap<PathBuf, String> = HashMap::new();
+    let mut last = 0;
+    for captures in pattern.captures_iter(html) {
+        let src = (1..=3)
+            .find_map(|group| captures.get(group))
+            .expect("one source group matches");
+        let Some(path) = local_path(src.as_str()) else {
+            continue;
+        };
+
+        // Components drop `.` segments, so `./logo.png` and `logo.png` are one image
+        let file: PathBuf = base_dir.join(&path).components().collect();
+        let content_id = match content_ids.get(&file) {
+            Some(content_id) => content_id.clone(),
+            None => {
+                let mut attachment = EmailAttachment::from_file_within(&file, budget)?;
+                budget -= attachment.content_size();
+                let content_id = unique_content_id(&attachment, &attachments);
+                attachment.content_id = Some(content_id.clone());
+                attachments.push(attachment);
+                content_ids.insert(file, content_id.clone());
+                content_id
+            }
+        };
+        rewritten.push_str(&html[last..src.start()]);
+        rewritten.push_str("cid:");
+        rewritten.push_str(&content_id);
+        last = src.end();
+    }
+    rewritten.push_str(&html[last..]);
+    Ok((rewritten, attachments))
+}
+
+/// Returns the decoded relative file path of a local image source, without
+/// query or fragment
+fn local_path(src: &str) -> Option<PathBuf> {
+    let src = src.trim();
+    let remote = src.contains("://")
+        || REMOTE_PREFIXES
+            .iter()
+            .any(|prefix| src.to_ascii_lowercase().starts_with(prefix));
+    let path = src.split(['?', '#']).next()?;
+    if remote || path.is_empty() || path.starts_with(['/', '\\']) {
+        return None;
+    }
+    let path = PathBuf::from(percent_decode_str(path).decode_utf8_lossy().as_ref());
+    Some(path).filter(|path| path.is_relative())
+}
+
+/// Derives a content ID from the filename, unique among the attachments
+fn unique_content_id(attachment: &EmailAttachment, attachments: &[EmailAttachment]) -> String {
+    let base: String = attachment
+        .filename
+        .as_deref()
+        .unwrap_or("image")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let taken = |id: &str| {
+        attachments
+            .iter()
+            .any(|a| a.content_id.as_deref() == Some(id))
+    };
+    let mut content_id = base.clone();
+    let mut n = 2;
+    while taken(&content_id) {
+        content_id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    content_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::emails::MAX_ATTACHMENTS_SIZE;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_embed_local_images() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("img")).unwrap();
+        std::fs::write(dir.path().join("logo.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("img").join("logo.png"), b"other").unwrap();
+
+        let html = r#"<p><img src="./logo.png" alt="Logo"></p>
+<IMG width=10 SRC='img/logo.png?v=2'><img src=logo.png>
+<img src="https://cdn.acme.com/a.png"><img data-src="x.png" src="cid:kept">"#;
+        let (rewritten, attachments) =
+            embed_local_images(html, dir.path(), MAX_ATTACHMENTS_SIZE).unwrap();
+        assert_eq!(
+            rewritten,
+            r#"<p><img src="cid:logo.png" alt="Logo"></p>
+<IMG width=10 SRC='cid:logo.png-2'><img src=cid:logo.png>
+<img src="https://cdn.acme.com/a.png"><img data-src="x.png" src="cid:kept">"#
+        );
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].content_id.as_deref(), Some("logo.png"));
+        assert_eq!(attachments[0].content.as_deref(), Some("cG5n"));
+        assert_eq!(attachments[0].content_type.as_deref(), Some("image/png"));
+        assert_eq!(attachments[1].content_id.as_deref(), Some("logo.png-2"));
+    }
+
+    #[test]
+    fn test_only_relative_paths_are_embedded() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("my logo.png"), b"png").unwrap();
+
+        let html = r#"<img src="/images/logo.png"><img src="my%20logo.png">"#;
+        let (rewritten, attachments) =
+            embed_local_images(html, dir.path(), MAX_ATTACHMENTS_SIZE).unwrap();
+        assert_eq!(
+            rewritten,
+            r#"<img src="/images/logo.png"><img src="cid:my_logo.png">"#
+        );
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].filename.as_deref(), Some("my logo.png"));
+        assert_eq!(local_path("file:///tmp/logo.png"), None);
+        assert_eq!(local_path(r"\\server\logo.png"), None);
+    }
+
+    #[test]
+    fn test_missing_images_are_reported() {
+        let dir = TempDir::new().unwrap();
+        let err = embed_local_images(
+            r#"<img src="missing.png">"#,
+            dir.path(),
+            MAX_ATTACHMENTS_SIZE,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("missing.png"));
+        assert_eq!(
+            embed_local_images("<p>No images</p>", dir.path(), MAX_ATTACHMENTS_SIZE).unwrap(),
+            ("<p>No images</p>".to_string(), vec![])
+        );
+    }
+}
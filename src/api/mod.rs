@@ -7,36 +7,71 @@
 //! ## Key Components
 //!
 //! - `ResendClient`: The main HTTP client implementation
+//! - `ResendClientBuilder`: Builder for configuring a `ResendClient`
 //! - `ResendApi`: Trait defining all API operations
+//! - `ResendError`: Typed errors parsed from Resend's JSON error envelope
+//! - `RetryPolicy`: Retries with backoff for rate-limited and failed requests
+//! - `RateLimiter`: Client-side token bucket keeping requests under the quota
+//! - `Tracer`: Verbose logging of HTTP exchanges with redacted credentials
+//! - `Cassette`: Record/replay of HTTP exchanges for offline tests
 //! - `PaginationOptions`: Struct for handling pagination parameters
+//! - `paginate`: Stream of every item of a list endpoint, following cursors
 //! - Module-specific request/response types in submodules
 
 use crate::config::Config;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use reqwest::{Client, Method, RequestBuilder};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+pub use builder::ResendClientBuilder;
+pub use cassette::{Cassette, CassetteMode};
+pub use dry_run::DryRunClient;
+pub use rate_limit::RateLimiter;
+pub use retry::RetryPolicy;
+pub use trace::Tracer;
+
+/// Parsing and validation of email addresses
+pub mod address;
 /// API key management operations
 pub mod api_keys;
 /// Broadcast management operations
 pub mod broadcasts;
+/// Builder for configuring a `ResendClient`
+pub mod builder;
+/// Recording and replaying of HTTP exchanges
+pub mod cassette;
 /// Contact property management operations
 pub mod contact_properties;
 /// Contact management operations
 pub mod contacts;
 /// Domain management operations
 pub mod domains;
+/// Printing requests instead of sending them
+pub mod dry_run;
 /// Email management operations
 pub mod emails;
+/// Embedding of local images as inline attachments
+pub mod inline_images;
+/// Client-side rate limiting of requests
+pub mod rate_limit;
 /// Received email management operations
 pub mod receiving;
+/// Retry policy for failed requests
+pub mod retry;
 /// Segment management operations
 pub mod segments;
 /// Template management operations
 pub mod templates;
 /// Topic management operations
 pub mod topics;
+/// Tracing of HTTP requests and responses
+pub mod trace;
 /// Webhook management operations
 pub mod webhooks;
 
@@ -57,6 +92,341 @@ pub struct PaginationOptions {
     pub before: Option<String>,
 }
 
+/// Maximum page size accepted by Resend list endpoints
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Options for list commands, adding auto-pagination to `PaginationOptions`
+#[derive(Debug, Default, clap::Args, Clone)]
+pub struct ListOptions {
+    #[command(flatten)]
+    pub pagination: PaginationOptions,
+    /// Follow pagination cursors and fetch every page (ndjson and csv print each
+    /// page as it arrives, with the csv columns of the first page)
+    #[arg(long)]
+    pub all: bool,
+    /// Stop after this many items, following cursors as needed
+    #[arg(long)]
+    pub max_items: Option<usize>,
+}
+
+impl ListOptions {
+    /// Streams the items selected by these options
+    ///
+    /// Without `--all` or `--max-items` a single page is fetched. Otherwise pages
+    /// are fetched lazily with [`paginate`] until the last page or `max_items` is
+    /// reached, so items can be printed as they arrive.
+    ///
+    /// # Arguments
+    ///
+    /// * `fetch` - Function fetching one page, e.g. `|p| client.list_emails(p)`
+    pub fn stream<R, F, Fut>(self, mut fetch: F) -> impl Stream<Item = Result<R::Item>> + Unpin
+    where
+        R: ListResponse,
+        F: FnMut(PaginationOptions) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        if !self.all && self.max_items.is_none() {
+            let page = fetch(self.pagination);
+            return Box::pin(
+                stream::once(page)
+                    .map_ok(|page| stream::iter(page.into_items().into_iter().map(Ok)))
+                    .try_flatten(),
+            )
+            .left_stream();
+        }
+
+        let mut pagination = self.pagination;
+        if pagination.limit.is_none() {
+            pagination.limit = Some(MAX_PAGE_SIZE);
+        }
+        paginate(pagination, fetch)
+            .take(self.max_items.unwrap_or(usize::MAX))
+            .right_stream()
+    }
+
+    /// Fetches the items selected by these options, see [`ListOptions::stream`]
+    ///
+    /// # Arguments
+    ///
+    /// * `fetch` - Function fetching one page, e.g. `|p| client.list_emails(p)`
+    pub async fn collect<R, F, Fut>(self, fetch: F) -> Result<Vec<R::Item>>
+    where
+        R: ListResponse,
+        F: FnMut(PaginationOptions) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        self.stream(fetch).try_collect().await
+    }
+}
+
+/// A resource identified by a string ID, usable as a pagination cursor
+pub trait Resource {
+    /// Returns the unique identifier of the resource
+    fn id(&self) -> &str;
+}
+
+/// A page of results returned by a list endpoint
+pub trait ListResponse {
+    /// Type of the listed resources
+    type Item: Resource;
+
+    /// Returns whether more items are available beyond this page
+    fn has_more(&self) -> bool;
+
+    /// Consumes the page and returns its items
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// Implements `Resource` and `ListResponse` for a list response and its item type
+macro_rules! impl_list_response {
+    ($response:ty, $item:ty) => {
+        impl Resource for $item {
+            fn id(&self) -> &str {
+                &self.id
+            }
+        }
+
+        impl ListResponse for $response {
+            type Item = $item;
+
+            fn has_more(&self) -> bool {
+                self.has_more
+            }
+
+            fn into_items(self) -> Vec<Self::Item> {
+                self.data
+            }
+        }
+    };
+}
+
+impl_list_response!(emails::ListEmailsResponse, emails::Email);
+impl_list_response!(emails::ListAttachmentsResponse, emails::Attachment);
+impl_list_response!(api_keys::ListApiKeysResponse, api_keys::ApiKey);
+impl_list_response!(domains::ListDomainsResponse, domains::Domain);
+impl_list_response!(segments::ListSegmentsResponse, segments::Segment);
+impl_list_response!(contacts::ListContactsResponse, contacts::Contact);
+impl_list_response!(templates::ListTemplatesResponse, templates::Template);
+impl_list_response!(topics::ListTopicsResponse, topics::Topic);
+impl_list_response!(webhooks::ListWebhooksResponse, webhooks::Webhook);
+impl_list_response!(broadcasts::ListBroadcastsResponse, broadcasts::Broadcast);
+impl_list_response!(
+    contact_properties::ListContactPropertiesResponse,
+    contact_properties::ContactProperty
+);
+impl_list_response!(
+    receiving::ListReceivedEmailsResponse,
+    receiving::ReceivedEmail
+);
+impl_list_response!(
+    receiving::ListReceivedAttachmentsResponse,
+    receiving::ReceivedAttachment
+);
+
+/// Streams every item of a list endpoint, following pagination cursors
+///
+/// Pages are fetched lazily as the stream is consumed. When paging forward the
+/// last item of each page becomes the `after` cursor of the next request; when
+/// `pagination.before` is set, the first item becomes the next `before` cursor.
+/// The stream ends when a page reports `has_more: false` or comes back empty.
+///
+/// # Arguments
+///
+/// * `pagination` - Options for the first page (limit and starting cursor)
+/// * `fetch` - Function fetching one page, e.g. `|p| client.list_emails(p)`
+///
+/// # Example
+///
+/// ```ignore
+/// let mut emails = paginate(PaginationOptions::default(), |p| client.list_emails(p));
+/// while let Some(email) = emails.try_next().await? {
+///     println!("{}", email.id);
+/// }
+/// ```
+pub fn paginate<R, F, Fut>(
+    pagination: PaginationOptions,
+    fetch: F,
+) -> impl Stream<Item = Result<R::Item>> + Unpin
+where
+    R: ListResponse,
+    F: FnMut(PaginationOptions) -> Fut,
+    Fut: Future<Output = Result<R>>,
+{
+    let pages = stream::try_unfold((Some(pagination), fetch), |(next, mut fetch)| async move {
+        let Some(options) = next else {
+            return Ok::<_, anyhow::Error>(None);
+        };
+        let page = fetch(options.clone()).await?;
+        let has_more = page.has_more();
+        let items = page.into_items();
+
+        let next = if !has_more || items.is_empty() {
+            None
+        } else if options.before.is_some() {
+            Some(PaginationOptions {
+                before: items.first().map(|item| item.id().to_string()),
+                after: None,
+                ..options
+            })
+        } else {
+            Some(PaginationOptions {
+                after: items.last().map(|item| item.id().to_string()),
+                before: None,
+                ..options
+            })
+        };
+        Ok(Some((items, (next, fetch))))
+    });
+
+    Box::pin(
+        pages
+            .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+            .try_flatten(),
+    )
+}
+
+/// Details of an error response returned by the Resend API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiErrorDetails {
+    /// HTTP status code of the response
+    pub status: u16,
+    /// Machine-readable error name (e.g. "validation_error", "missing_api_key")
+    pub name: String,
+    /// Human-readable error message
+    pub message: String,
+}
+
+/// Error envelope returned by the Resend API on failure
+///
+/// Resend responds to failed requests with a body such as
+/// `{"statusCode": 422, "name": "validation_error", "message": "..."}`.
+/// The status code is taken from the HTTP response itself.
+#[derive(Debug, Deserialize)]
+struct ErrorEnvelope {
+    name: Option<String>,
+    message: Option<String>,
+}
+
+/// Typed error for failed Resend API calls
+///
+/// Every `ResendApi` method returns an `anyhow::Error` that wraps a `ResendError`
+/// when the request failed at the HTTP level, so callers can match on the failure
+/// with `err.downcast_ref::<ResendError>()`.
+#[derive(Debug)]
+pub enum ResendError {
+    /// The request was rejected as invalid (400, 405, 409, 422 and other 4xx)
+    Validation(ApiErrorDetails),
+    /// The API key is missing, invalid or lacks the required permission (401, 403)
+    Auth(ApiErrorDetails),
+    /// The requested resource does not exist (404)
+    NotFound(ApiErrorDetails),
+    /// Too many requests were sent in a given amount of time (429)
+    RateLimit(ApiErrorDetails),
+    /// The Resend API failed to process the request (5xx)
+    Server(ApiErrorDetails),
+    /// The request could not be sent or the response could not be read
+    Transport(reqwest::Error),
+    /// A replayed request has no recorded match, or the cassette could not be saved
+    Cassette(String),
+}
+
+impl ResendError {
+    /// Builds a typed error from a failed response's status code and body
+    ///
+    /// The body is parsed as Resend's JSON error envelope. When it is not valid
+    /// JSON, the raw body is used as the message and the name is derived from the
+    /// status code.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let envelope = serde_json::from_str::<ErrorEnvelope>(body).ok();
+        let (name, message) = match envelope {
+            Some(envelope) => (envelope.name, envelope.message),
+            None => (None, None),
+        };
+        let name = name.unwrap_or_else(|| default_error_name(status).to_string());
+        let message = message.unwrap_or_else(|| {
+            if body.trim().is_empty() {
+                reqwest::StatusCode::from_u16(status)
+                    .ok()
+                    .and_then(|s| s.canonical_reason())
+                    .unwrap_or("Unknown error")
+                    .to_string()
+            } else {
+                body.trim().to_string()
+            }
+        });
+        let details = ApiErrorDetails {
+            status,
+            name,
+            message,
+        };
+
+        match status {
+            401 | 403 => ResendError::Auth(details),
+            404 => ResendError::NotFound(details),
+            429 => ResendError::RateLimit(details),
+            500..=599 => ResendError::Server(details),
+            _ => ResendError::Validation(details),
+        }
+    }
+
+    /// Returns the API error details, or `None` for transport errors
+    pub fn details(&self) -> Option<&ApiErrorDetails> {
+        match self {
+            ResendError::Validation(d)
+            | ResendError::Auth(d)
+            | ResendError::NotFound(d)
+            | ResendError::RateLimit(d)
+            | ResendError::Server(d) => Some(d),
+            ResendError::Transport(_) | ResendError::Cassette(_) => None,
+        }
+    }
+}
+
+/// Returns the error name used when a failed response has no JSON envelope
+fn default_error_name(status: u16) -> &'static str {
+    match status {
+        401 | 403 => "unauthorized",
+        404 => "not_found",
+        429 => "rate_limit_exceeded",
+        500..=599 => "internal_server_error",
+        _ => "validation_error",
+    }
+}
+
+impl fmt::Display for ResendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ResendError::Validation(_) => "Validation error",
+            ResendError::Auth(_) => "Authentication error",
+            ResendError::NotFound(_) => "Not found",
+            ResendError::RateLimit(_) => "Rate limit exceeded",
+            ResendError::Server(_) => "Server error",
+            ResendError::Transport(e) => return write!(f, "Request failed: {}", e),
+            ResendError::Cassette(message) => return write!(f, "Cassette error: {}", message),
+        };
+        match self.details() {
+            Some(d) => write!(f, "{}: {} ({}, HTTP {})", kind, d.message, d.name, d.status),
+            None => write!(f, "{}", kind),
+        }
+    }
+}
+
+impl std::error::Error for ResendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // The transport error is already part of the message, so skip to its cause
+            ResendError::Transport(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ResendError {
+    fn from(err: reqwest::Error) -> Self {
+        ResendError::Transport(err)
+    }
+}
+
 /// Trait defining all API operations for the Resend service
 ///
 /// This trait provides a unified interface for all operations available in the Resend API.
@@ -66,13 +436,18 @@ pub struct PaginationOptions {
 #[async_trait]
 pub trait ResendApi {
     // Emails
+    /// Sends an email, optionally with an `Idempotency-Key` so that retries of
+    /// the same request never send it twice
     async fn send_email(
         &self,
         request: emails::SendEmailRequest,
+        idempotency_key: Option<String>,
     ) -> Result<emails::SendEmailResponse>;
+    /// Sends a batch of emails, optionally with an `Idempotency-Key`
     async fn send_email_batch(
         &self,
         requests: Vec<emails::SendEmailRequest>,
+        idempotency_key: Option<String>,
     ) -> Result<Vec<emails::SendEmailResponse>>;
     async fn get_email(&self, id: &str) -> Result<emails::Email>;
     async fn list_emails(
@@ -227,12 +602,25 @@ pub trait ResendApi {
     ) -> Result<receiving::ListReceivedAttachmentsResponse>;
 }
 
+/// Default base URL of the Resend API
+pub const DEFAULT_BASE_URL: &str = "https://api.resend.com";
+
+/// Default timeout for a whole request
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout for establishing a connection
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// HTTP client implementation for the Resend API
 ///
 /// This struct provides the concrete implementation of the ResendApi trait,
 /// handling HTTP requests, authentication, and response processing.
 /// It manages the connection to the Resend API and provides methods
 /// for all supported operations.
+///
+/// Clones share the same connection pool and rate limiter, so a client can be
+/// cloned into concurrent tasks without exceeding the request quota.
+#[derive(Clone)]
 pub struct ResendClient {
     /// HTTP client for making requests
     client: Client,
@@ -240,6 +628,14 @@ pub struct ResendClient {
     api_key: String,
     /// Base URL for the Resend API
     base_url: String,
+    /// Policy for retrying rate-limited and failed requests
+    retry: RetryPolicy,
+    /// Token bucket shared by every request, or `None` when rate limiting is disabled
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// Trace output for HTTP exchanges, or `None` when tracing is disabled
+    tracer: Option<Arc<Tracer>>,
+    /// Cassette recording or replaying HTTP exchanges, if any
+    cassette: Option<Arc<Cassette>>,
 }
 
 #[async_trait]
@@ -248,21 +644,26 @@ impl ResendApi for ResendClient {
     async fn send_email(
         &self,
         request: emails::SendEmailRequest,
+        idempotency_key: Option<String>,
     ) -> Result<emails::SendEmailResponse> {
         let builder = self.request(Method::POST, "/emails").json(&request);
-        Self::handle_response(builder).await
+        let builder = Self::apply_idempotency_key(builder, idempotency_key);
+        self.handle_response(builder).await
     }
     async fn send_email_batch(
         &self,
         requests: Vec<emails::SendEmailRequest>,
+        idempotency_key: Option<String>,
     ) -> Result<Vec<emails::SendEmailResponse>> {
         let builder = self.request(Method::POST, "/emails/batch").json(&requests);
-        Self::handle_response(builder).await
+        let builder = Self::apply_idempotency_key(builder, idempotency_key);
+        let response: emails::SendBatchResponse = self.handle_response(builder).await?;
+        Ok(response.data)
     }
     async fn get_email(&self, id: &str) -> Result<emails::Email> {
         let path = format!("/emails/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_emails(
         &self,
@@ -270,19 +671,12 @@ impl ResendApi for ResendClient {
     ) -> Result<emails::ListEmailsResponse> {
         let builder = self.request(Method::GET, "/emails");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn cancel_email(&self, id: &str) -> Result<()> {
         let path = format!("/emails/{}/cancel", id);
         let builder = self.request(Method::POST, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
     async fn update_email(
         &self,
@@ -291,12 +685,12 @@ impl ResendApi for ResendClient {
     ) -> Result<emails::SendEmailResponse> {
         let path = format!("/emails/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_email_attachments(&self, id: &str) -> Result<emails::ListAttachmentsResponse> {
         let path = format!("/emails/{}/attachments", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
 
     // API Keys
@@ -305,7 +699,7 @@ impl ResendApi for ResendClient {
         request: api_keys::CreateApiKeyRequest,
     ) -> Result<api_keys::ApiKey> {
         let builder = self.request(Method::POST, "/api-keys").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_api_keys(
         &self,
@@ -313,19 +707,12 @@ impl ResendApi for ResendClient {
     ) -> Result<api_keys::ListApiKeysResponse> {
         let builder = self.request(Method::GET, "/api-keys");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_api_key(&self, id: &str) -> Result<()> {
         let path = format!("/api-keys/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Domains
@@ -334,7 +721,7 @@ impl ResendApi for ResendClient {
         request: domains::CreateDomainRequest,
     ) -> Result<domains::Domain> {
         let builder = self.request(Method::POST, "/domains").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_domains(
         &self,
@@ -342,36 +729,22 @@ impl ResendApi for ResendClient {
     ) -> Result<domains::ListDomainsResponse> {
         let builder = self.request(Method::GET, "/domains");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_domain(&self, id: &str) -> Result<domains::Domain> {
         let path = format!("/domains/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_domain(&self, id: &str) -> Result<()> {
         let path = format!("/domains/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
     async fn verify_domain(&self, id: &str) -> Result<()> {
         let path = format!("/domains/{}/verify", id);
         let builder = self.request(Method::POST, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Segments
@@ -380,7 +753,7 @@ impl ResendApi for ResendClient {
             name: name.to_string(),
         };
         let builder = self.request(Method::POST, "/segments").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_segments(
         &self,
@@ -388,24 +761,17 @@ impl ResendApi for ResendClient {
     ) -> Result<segments::ListSegmentsResponse> {
         let builder = self.request(Method::GET, "/segments");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_segment(&self, id: &str) -> Result<segments::Segment> {
         let path = format!("/segments/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_segment(&self, id: &str) -> Result<()> {
         let path = format!("/segments/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Contacts
@@ -414,7 +780,7 @@ impl ResendApi for ResendClient {
         request: contacts::CreateContactRequest,
     ) -> Result<contacts::Contact> {
         let builder = self.request(Method::POST, "/contacts").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_contacts(
         &self,
@@ -422,12 +788,12 @@ impl ResendApi for ResendClient {
     ) -> Result<contacts::ListContactsResponse> {
         let builder = self.request(Method::GET, "/contacts");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_contact(&self, id: &str) -> Result<contacts::Contact> {
         let path = format!("/contacts/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn update_contact(
         &self,
@@ -436,43 +802,22 @@ impl ResendApi for ResendClient {
     ) -> Result<contacts::Contact> {
         let path = format!("/contacts/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_contact(&self, id: &str) -> Result<()> {
         let path = format!("/contacts/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
     async fn add_contact_to_segment(&self, contact_id: &str, segment_id: &str) -> Result<()> {
         let path = format!("/contacts/{}/segments/{}", contact_id, segment_id);
         let builder = self.request(Method::POST, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
     async fn delete_contact_from_segment(&self, contact_id: &str, segment_id: &str) -> Result<()> {
         let path = format!("/contacts/{}/segments/{}", contact_id, segment_id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Templates
@@ -481,7 +826,7 @@ impl ResendApi for ResendClient {
         request: templates::CreateTemplateRequest,
     ) -> Result<templates::Template> {
         let builder = self.request(Method::POST, "/templates").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_templates(
         &self,
@@ -489,12 +834,12 @@ impl ResendApi for ResendClient {
     ) -> Result<templates::ListTemplatesResponse> {
         let builder = self.request(Method::GET, "/templates");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_template(&self, id: &str) -> Result<templates::Template> {
         let path = format!("/templates/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn update_template(
         &self,
@@ -503,25 +848,18 @@ impl ResendApi for ResendClient {
     ) -> Result<templates::Template> {
         let path = format!("/templates/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_template(&self, id: &str) -> Result<()> {
         let path = format!("/templates/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Topics
     async fn create_topic(&self, request: topics::CreateTopicRequest) -> Result<topics::Topic> {
         let builder = self.request(Method::POST, "/topics").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_topics(
         &self,
@@ -529,12 +867,12 @@ impl ResendApi for ResendClient {
     ) -> Result<topics::ListTopicsResponse> {
         let builder = self.request(Method::GET, "/topics");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_topic(&self, id: &str) -> Result<topics::Topic> {
         let path = format!("/topics/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn update_topic(
         &self,
@@ -543,19 +881,12 @@ impl ResendApi for ResendClient {
     ) -> Result<topics::Topic> {
         let path = format!("/topics/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_topic(&self, id: &str) -> Result<()> {
         let path = format!("/topics/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Webhooks
@@ -564,7 +895,7 @@ impl ResendApi for ResendClient {
         request: webhooks::CreateWebhookRequest,
     ) -> Result<webhooks::Webhook> {
         let builder = self.request(Method::POST, "/webhooks").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_webhooks(
         &self,
@@ -572,24 +903,17 @@ impl ResendApi for ResendClient {
     ) -> Result<webhooks::ListWebhooksResponse> {
         let builder = self.request(Method::GET, "/webhooks");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_webhook(&self, id: &str) -> Result<webhooks::Webhook> {
         let path = format!("/webhooks/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_webhook(&self, id: &str) -> Result<()> {
         let path = format!("/webhooks/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Broadcasts
@@ -598,7 +922,7 @@ impl ResendApi for ResendClient {
         request: broadcasts::CreateBroadcastRequest,
     ) -> Result<broadcasts::Broadcast> {
         let builder = self.request(Method::POST, "/broadcasts").json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_broadcasts(
         &self,
@@ -606,12 +930,12 @@ impl ResendApi for ResendClient {
     ) -> Result<broadcasts::ListBroadcastsResponse> {
         let builder = self.request(Method::GET, "/broadcasts");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_broadcast(&self, id: &str) -> Result<broadcasts::Broadcast> {
         let path = format!("/broadcasts/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn update_broadcast(
         &self,
@@ -620,31 +944,17 @@ impl ResendApi for ResendClient {
     ) -> Result<broadcasts::Broadcast> {
         let path = format!("/broadcasts/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_broadcast(&self, id: &str) -> Result<()> {
         let path = format!("/broadcasts/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
     async fn send_broadcast(&self, id: &str) -> Result<()> {
         let path = format!("/broadcasts/{}/send", id);
         let builder = self.request(Method::POST, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Contact Properties
@@ -655,7 +965,7 @@ impl ResendApi for ResendClient {
         let builder = self
             .request(Method::POST, "/contact-properties")
             .json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_contact_properties(
         &self,
@@ -663,12 +973,12 @@ impl ResendApi for ResendClient {
     ) -> Result<contact_properties::ListContactPropertiesResponse> {
         let builder = self.request(Method::GET, "/contact-properties");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_contact_property(&self, id: &str) -> Result<contact_properties::ContactProperty> {
         let path = format!("/contact-properties/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn update_contact_property(
         &self,
@@ -677,19 +987,12 @@ impl ResendApi for ResendClient {
     ) -> Result<contact_properties::ContactProperty> {
         let path = format!("/contact-properties/{}", id);
         let builder = self.request(Method::PATCH, &path).json(&request);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn delete_contact_property(&self, id: &str) -> Result<()> {
         let path = format!("/contact-properties/{}", id);
         let builder = self.request(Method::DELETE, &path);
-        let response = builder.send().await?;
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
-        }
+        self.handle_empty_response(builder).await
     }
 
     // Receiving
@@ -699,12 +1002,12 @@ impl ResendApi for ResendClient {
     ) -> Result<receiving::ListReceivedEmailsResponse> {
         let builder = self.request(Method::GET, "/emails/receiving");
         let builder = Self::apply_pagination(builder, &pagination);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn get_received_email(&self, id: &str) -> Result<serde_json::Value> {
         let path = format!("/emails/receiving/{}", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
     async fn list_received_attachments(
         &self,
@@ -712,7 +1015,7 @@ impl ResendApi for ResendClient {
     ) -> Result<receiving::ListReceivedAttachmentsResponse> {
         let path = format!("/emails/receiving/{}/attachments", id);
         let builder = self.request(Method::GET, &path);
-        Self::handle_response(builder).await
+        self.handle_response(builder).await
     }
 }
 
@@ -721,17 +1024,27 @@ impl ResendClient {
     ///
     /// # Arguments
     ///
-    /// * `config` - Configuration containing the API key for authentication
+    /// * `config` - Configuration containing the API key, optional base URL,
+    ///   maximum number of attempts, requests-per-second quota and HTTP settings
     ///
     /// # Returns
     ///
-    /// A new instance of ResendClient configured with the provided API key
-    pub fn new(config: Config) -> Self {
-        Self {
-            client: Client::new(),
-            api_key: config.api_key,
-            base_url: "https://api.resend.com".to_string(),
-        }
+    /// A new instance of ResendClient configured with the provided API key,
+    /// targeting `config.base_url` or [`DEFAULT_BASE_URL`] when unset, or an
+    /// error if a timeout, the proxy URL or CA certificate file is invalid
+    pub fn new(config: Config) -> Result<Self> {
+        Self::builder(config.api_key.clone())
+            .config(&config)?
+            .build()
+    }
+
+    /// Returns a builder for a client authenticated with the given API key
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - API key for authenticating with the Resend API
+    pub fn builder(api_key: impl Into<String>) -> ResendClientBuilder {
+        ResendClientBuilder::new(api_key)
     }
 
     /// Constructs an HTTP request with proper authentication headers
@@ -783,6 +1096,28 @@ impl ResendClient {
         builder
     }
 
+    /// Adds an `Idempotency-Key` header to a request builder, if a key is given
+    ///
+    /// Requests carrying an idempotency key are also safe to retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The request builder to modify
+    /// * `idempotency_key` - Optional idempotency key for the request
+    ///
+    /// # Returns
+    ///
+    /// The modified request builder
+    pub fn apply_idempotency_key(
+        builder: RequestBuilder,
+        idempotency_key: Option<String>,
+    ) -> RequestBuilder {
+        match idempotency_key {
+            Some(key) => builder.header(retry::IDEMPOTENCY_KEY_HEADER, key),
+            None => builder,
+        }
+    }
+
     /// Handles API response deserialization and error handling
     ///
     /// This helper method processes API responses, checking for success status codes
@@ -798,56 +1133,333 @@ impl ResendClient {
     ///
     /// # Returns
     ///
-    /// The deserialized response object or an error if the request failed
-    pub async fn handle_response<T>(builder: RequestBuilder) -> Result<T>
+    /// The deserialized response object, or a [`ResendError`] if the request failed
+    pub async fn handle_response<T>(&self, builder: RequestBuilder) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
-        let response = builder.send().await?;
-        let status = response.status();
-
-        if status.is_success() {
-            let text = response.text().await?;
-            // Resend API sometimes returns empty body for 204 or 200 with no content
-            if text.is_empty() {
-                // This is tricky for T. Usually we expect some JSON.
-                // If T is expected but body is empty, it might fail.
-                // We'll try to parse it and see.
-                return serde_json::from_str("{}")
-                    .map_err(|e| anyhow!("Failed to parse empty response: {}", e));
+        let text = self.send(builder).await?;
+        // Resend API sometimes returns empty body for 204 or 200 with no content
+        if text.is_empty() {
+            // This is tricky for T. Usually we expect some JSON.
+            // If T is expected but body is empty, it might fail.
+            // We'll try to parse it and see.
+            return serde_json::from_str("{}")
+                .map_err(|e| anyhow!("Failed to parse empty response: {}", e));
+        }
+        serde_json::from_str(&text)
+            .map_err(|e| anyhow!("Failed to parse response: {}. Body: {}", e, text))
+    }
+
+    /// Handles API responses whose body is not needed
+    ///
+    /// Used for endpoints such as deletes and verifications that may answer with
+    /// an empty body (e.g. 204 No Content).
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The request builder to execute
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success, or a [`ResendError`] if the request failed
+    pub async fn handle_empty_response(&self, builder: RequestBuilder) -> Result<()> {
+        self.send(builder).await?;
+        Ok(())
+    }
+
+    /// Sends a request and returns the body of a successful response
+    ///
+    /// Rate-limited and server error responses, as well as connection failures
+    /// and timeouts, are retried according to the client's [`RetryPolicy`] when
+    /// the request is safe to repeat. Failed responses are converted into a
+    /// [`ResendError`] parsed from the API's error envelope.
+    async fn send(&self, builder: RequestBuilder) -> std::result::Result<String, ResendError> {
+        let mut request = builder.build()?;
+        let retryable = self.retry.allows(request.method(), request.headers());
+        let mut attempt = 1;
+
+        loop {
+            // Requests with streaming bodies cannot be cloned and are sent once
+            let next = if retryable && attempt < self.retry.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            let (delay, next) = match self.execute(request, attempt).await? {
+                Ok((status, headers, text)) => {
+                    if status.is_success() {
+                        return Ok(text);
+                    }
+                    match next {
+                        Some(next) if RetryPolicy::is_retryable_status(status) => {
+                            (self.retry.delay(attempt, Some(&headers)), next)
+                        }
+                        _ => return Err(ResendError::from_response(status.as_u16(), &text)),
+                    }
+                }
+                Err(err) => match next {
+                    Some(next) if RetryPolicy::is_retryable_error(&err) => {
+                        (self.retry.delay(attempt, None), next)
+                    }
+                    _ => return Err(ResendError::Transport(err)),
+                },
+            };
+
+            // Replayed responses are retried without waiting
+            if !self.cassette.as_ref().is_some_and(|c| c.is_replay()) {
+                tokio::time::sleep(delay).await;
+            }
+            request = next;
+            attempt += 1;
+        }
+    }
+
+    /// Performs a single attempt of a request
+    ///
+    /// The attempt first waits for the client's [`RateLimiter`], if any, and is
+    /// recorded by the client's [`Tracer`], if any. With a [`Cassette`], the
+    /// response is either recorded or served from the cassette instead of the
+    /// network.
+    ///
+    /// # Returns
+    ///
+    /// The status, headers and body of the response, or the transport error
+    /// that prevented receiving one. Fails with [`ResendError::Cassette`] when a
+    /// replayed request has no recorded match or the cassette cannot be saved.
+    async fn execute(
+        &self,
+        request: reqwest::Request,
+        attempt: u32,
+    ) -> std::result::Result<std::result::Result<Exchange, reqwest::Error>, ResendError> {
+        let trace = self
+            .tracer
+            .as_ref()
+            .map(|tracer| (tracer, tracer.request(&request, attempt)));
+        let started = Instant::now();
+
+        let result = match &self.cassette {
+            Some(cassette) if cassette.is_replay() => {
+                Ok(cassette.replay(&request).map_err(ResendError::Cassette)?)
+            }
+            cassette => {
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.acquire().await;
+                }
+                let recorded = cassette
+                    .as_ref()
+                    .map(|_| cassette::RecordedRequest::from_request(&request));
+
+                let result = match self.client.execute(request).await {
+                    Ok(response) => {
+                        let status = response.status();
+                        let headers = response.headers().clone();
+                        response.text().await.map(|text| (status, headers, text))
+                    }
+                    Err(err) => Err(err),
+                };
+
+                if let (Some(cassette), Some(recorded), Ok((status, headers, text))) =
+                    (cassette, recorded, &result)
+                {
+                    cassette
+                        .record(recorded, *status, headers, text)
+                        .map_err(|e| ResendError::Cassette(format!("{:#}", e)))?;
+                }
+                result
+            }
+        };
+
+        if let Some((tracer, trace)) = &trace {
+            match &result {
+                Ok((status, headers, text)) => {
+                    tracer.response(trace, *status, headers, text, started.elapsed())
+                }
+                Err(err) => tracer.error(trace, err, started.elapsed()),
             }
-            serde_json::from_str(&text)
-                .map_err(|e| anyhow!("Failed to parse response: {}. Body: {}", e, text))
-        } else {
-            let text = response.text().await?;
-            anyhow::bail!("API Error ({}): {}", status, text)
         }
+        Ok(result)
     }
 }
 
+/// Status, headers and body of a response
+type Exchange = (reqwest::StatusCode, reqwest::header::HeaderMap, String);
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
-    use tokio;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Starts a local server that answers successive requests with the given
+    /// `(status line, extra headers, body)` responses, repeating the last one
+    ///
+    /// Returns a client pointed at the server and a counter of received requests.
+    async fn scripted_server(
+        responses: Vec<(&'static str, &'static str, &'static str)>,
+    ) -> (ResendClient, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, headers, body) = responses[index.min(responses.len() - 1)];
+
+                // Read the request head and body before answering
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let mut client = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            base_url: Some(base_url),
+            requests_per_second: Some(0.0),
+            ..Default::default()
+        })
+        .unwrap();
+        client.retry.base_delay = Duration::from_millis(1);
+        (client, hits)
+    }
+
+    const RATE_LIMITED: (&str, &str, &str) = (
+        "429 Too Many Requests",
+        "Retry-After: 0\r\n",
+        r#"{"statusCode":429,"name":"rate_limit_exceeded","message":"Too many requests."}"#,
+    );
+    const UNAVAILABLE: (&str, &str, &str) = (
+        "503 Service Unavailable",
+        "",
+        r#"{"statusCode":503,"name":"internal_server_error","message":"Unavailable."}"#,
+    );
 
     #[tokio::test]
     async fn test_resend_client_creation() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
 
         assert_eq!(client.api_key, "test_key");
         assert_eq!(client.base_url, "https://api.resend.com");
     }
 
+    #[tokio::test]
+    async fn test_invalid_http_settings_are_rejected() {
+        let proxy = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        });
+        assert!(proxy.is_err());
+
+        let missing = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            ca_cert: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        });
+        assert!(missing.is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "not a certificate").unwrap();
+        let invalid = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            ca_cert: Some(empty),
+            ..Default::default()
+        });
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_enforced() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // Accept connections but never answer
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+
+        let client = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            base_url: Some(base_url),
+            max_attempts: Some(1),
+            requests_per_second: Some(0.0),
+            timeout: Some(0.2),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let err = client.list_domains(PaginationOptions::default()).await;
+        match err.unwrap_err().downcast_ref::<ResendError>() {
+            Some(ResendError::Transport(e)) => assert!(e.is_timeout()),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resend_client_custom_base_url() {
+        let config = Config {
+            api_key: "test_key".to_string(),
+            base_url: Some("http://127.0.0.1:4010/".to_string()),
+            ..Default::default()
+        };
+        let client = ResendClient::new(config).unwrap();
+
+        assert_eq!(client.base_url, "http://127.0.0.1:4010");
+        let request = client.request(Method::GET, "/emails").build().unwrap();
+        assert_eq!(request.url().as_str(), "http://127.0.0.1:4010/emails");
+    }
+
     #[tokio::test]
     async fn test_request_builder() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let _request_builder = client.request(Method::GET, "/test");
 
         // We can't easily test the actual request without sending it,
@@ -859,8 +1471,9 @@ mod tests {
     async fn test_apply_pagination_with_limit() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let request_builder = client.request(Method::GET, "/test");
         let pagination = PaginationOptions {
             limit: Some(10),
@@ -871,15 +1484,15 @@ mod tests {
         let _result = ResendClient::apply_pagination(request_builder, &pagination);
         // We can't easily test the query params without sending the request
         // but we can verify the function executes without error
-        assert!(true); // Basic assertion to satisfy test
     }
 
     #[tokio::test]
     async fn test_apply_pagination_with_after() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let request_builder = client.request(Method::GET, "/test");
         let pagination = PaginationOptions {
             limit: None,
@@ -888,15 +1501,15 @@ mod tests {
         };
 
         let _result = ResendClient::apply_pagination(request_builder, &pagination);
-        assert!(true); // Basic assertion to satisfy test
     }
 
     #[tokio::test]
     async fn test_apply_pagination_with_before() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let request_builder = client.request(Method::GET, "/test");
         let pagination = PaginationOptions {
             limit: None,
@@ -905,15 +1518,15 @@ mod tests {
         };
 
         let _result = ResendClient::apply_pagination(request_builder, &pagination);
-        assert!(true); // Basic assertion to satisfy test
     }
 
     #[tokio::test]
     async fn test_apply_pagination_with_all_params() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let request_builder = client.request(Method::GET, "/test");
         let pagination = PaginationOptions {
             limit: Some(20),
@@ -922,19 +1535,444 @@ mod tests {
         };
 
         let _result = ResendClient::apply_pagination(request_builder, &pagination);
-        assert!(true); // Basic assertion to satisfy test
     }
 
     #[tokio::test]
     async fn test_apply_pagination_with_no_params() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
-        let client = ResendClient::new(config);
+        let client = ResendClient::new(config).unwrap();
         let request_builder = client.request(Method::GET, "/test");
         let pagination = PaginationOptions::default();
 
         let _result = ResendClient::apply_pagination(request_builder, &pagination);
-        assert!(true); // Basic assertion to satisfy test
+    }
+
+    #[test]
+    fn test_resend_error_parses_envelope() {
+        let body =
+            r#"{"statusCode":422,"name":"validation_error","message":"Invalid `to` field."}"#;
+        let err = ResendError::from_response(422, body);
+
+        match &err {
+            ResendError::Validation(details) => {
+                assert_eq!(details.status, 422);
+                assert_eq!(details.name, "validation_error");
+                assert_eq!(details.message, "Invalid `to` field.");
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Validation error: Invalid `to` field. (validation_error, HTTP 422)"
+        );
+    }
+
+    #[test]
+    fn test_resend_error_maps_status_codes() {
+        let envelope = |status: u16| {
+            format!(
+                r#"{{"statusCode":{},"name":"some_error","message":"msg"}}"#,
+                status
+            )
+        };
+
+        assert!(matches!(
+            ResendError::from_response(401, &envelope(401)),
+            ResendError::Auth(_)
+        ));
+        assert!(matches!(
+            ResendError::from_response(403, &envelope(403)),
+            ResendError::Auth(_)
+        ));
+        assert!(matches!(
+            ResendError::from_response(404, &envelope(404)),
+            ResendError::NotFound(_)
+        ));
+        assert!(matches!(
+            ResendError::from_response(429, &envelope(429)),
+            ResendError::RateLimit(_)
+        ));
+        assert!(matches!(
+            ResendError::from_response(503, &envelope(503)),
+            ResendError::Server(_)
+        ));
+        assert!(matches!(
+            ResendError::from_response(400, &envelope(400)),
+            ResendError::Validation(_)
+        ));
+    }
+
+    #[test]
+    fn test_resend_error_without_envelope() {
+        let err = ResendError::from_response(502, "<html>Bad Gateway</html>");
+        let details = err.details().unwrap();
+        assert_eq!(details.name, "internal_server_error");
+        assert_eq!(details.message, "<html>Bad Gateway</html>");
+
+        let err = ResendError::from_response(404, "");
+        assert_eq!(err.details().unwrap().message, "Not Found");
+        assert_eq!(err.details().unwrap().status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_transport_error_is_typed() {
+        // Bind and immediately release a port so the connection is refused
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut client = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            base_url: Some(format!("http://127.0.0.1:{}", port)),
+            requests_per_second: Some(0.0),
+            ..Default::default()
+        })
+        .unwrap();
+        client.retry.base_delay = Duration::from_millis(1);
+
+        let err = client.delete_domain("dom_1").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ResendError>(),
+            Some(ResendError::Transport(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_retries_after_rate_limit() {
+        let (client, hits) = scripted_server(vec![
+            RATE_LIMITED,
+            (
+                "200 OK",
+                "",
+                r#"{"id":"seg_1","name":"VIP","created_at":"2023-01-01"}"#,
+            ),
+        ])
+        .await;
+
+        let segment = client.get_segment("seg_1").await.unwrap();
+        assert_eq!(segment.name, "VIP");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_without_idempotency_key_is_not_retried() {
+        let (client, hits) = scripted_server(vec![RATE_LIMITED]).await;
+
+        let err = client.create_segment("VIP").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ResendError>(),
+            Some(ResendError::RateLimit(_))
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_with_idempotency_key_is_retried() {
+        let (client, hits) =
+            scripted_server(vec![UNAVAILABLE, ("200 OK", "", r#"{"id":"email_1"}"#)]).await;
+
+        let builder = client
+            .request(Method::POST, "/emails")
+            .header(retry::IDEMPOTENCY_KEY_HEADER, "key-1")
+            .json(&serde_json::json!({"from": "a@example.com"}));
+        let response: emails::SendEmailResponse = client.handle_response(builder).await.unwrap();
+        assert_eq!(response.id, "email_1");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_after_max_attempts() {
+        let (mut client, hits) = scripted_server(vec![UNAVAILABLE]).await;
+        client.retry.max_attempts = 2;
+
+        let err = client.delete_domain("dom_1").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ResendError>(),
+            Some(ResendError::Server(_))
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_rate_limiter() {
+        let client = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            requests_per_second: Some(5.0),
+            ..Default::default()
+        })
+        .unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(
+            client.rate_limiter.as_ref().unwrap(),
+            clone.rate_limiter.as_ref().unwrap()
+        ));
+
+        let unlimited = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            requests_per_second: Some(0.0),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(unlimited.rate_limiter.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_every_call_goes_through_rate_limiter() {
+        let (mut client, hits) = scripted_server(vec![("200 OK", "", r#"{"data":[]}"#)]).await;
+        client.rate_limiter = Some(Arc::new(RateLimiter::new(20.0)));
+
+        let start = std::time::Instant::now();
+        for _ in 0..25 {
+            client
+                .list_contacts(PaginationOptions::default())
+                .await
+                .unwrap();
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 25);
+        // 5 calls over the burst capacity need 5 / 20 = 250ms of refill
+        assert!(start.elapsed() >= Duration::from_millis(240));
+    }
+
+    #[tokio::test]
+    async fn test_cassette_replays_recorded_exchanges_offline() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let (mut recorder, hits) = scripted_server(vec![
+            (
+                "200 OK",
+                "",
+                r#"{"id":"seg_1","name":"VIP","created_at":"2023-01-01"}"#,
+            ),
+            ("204 No Content", "", ""),
+        ])
+        .await;
+        recorder.cassette = Some(Arc::new(
+            Cassette::open(&path, CassetteMode::Record).unwrap(),
+        ));
+        recorder.create_segment("VIP").await.unwrap();
+        recorder.delete_domain("dom_1").await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // Nothing listens on the replaying client's base URL
+        let player = ResendClient::builder("test_key")
+            .base_url("http://127.0.0.1:9")
+            .cassette(Cassette::open(&path, CassetteMode::Replay).unwrap())
+            .build()
+            .unwrap();
+        let segment = player.create_segment("VIP").await.unwrap();
+        assert_eq!(segment.id, "seg_1");
+        player.delete_domain("dom_1").await.unwrap();
+
+        let err = player.create_segment("Other").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ResendError>(),
+            Some(ResendError::Cassette(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_every_attempt_is_traced() {
+        let (mut client, _) =
+            scripted_server(vec![UNAVAILABLE, ("200 OK", "", r#"{"data":[]}"#)]).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        client.tracer = Some(Arc::new(Tracer::new(false, Some(&path)).unwrap()));
+
+        client
+            .list_contacts(PaginationOptions {
+                limit: Some(10),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("test_key"));
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["status"], 503);
+        assert_eq!(lines[1]["status"], 200);
+        assert_eq!(lines[1]["request"]["attempt"], 2);
+        assert_eq!(lines[1]["request"]["query"], "limit=10");
+        assert!(lines[1]["request"]["url"]
+            .as_str()
+            .unwrap()
+            .ends_with("/contacts"));
+    }
+
+    fn page(ids: &[&str], has_more: bool) -> segments::ListSegmentsResponse {
+        segments::ListSegmentsResponse {
+            data: ids
+                .iter()
+                .map(|id| segments::Segment {
+                    id: id.to_string(),
+                    name: format!("Segment {}", id),
+                    created_at: "2023-01-01".to_string(),
+                })
+                .collect(),
+            has_more,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paginate_follows_after_cursor() {
+        let mut requests = Vec::new();
+        let items: Vec<segments::Segment> =
+            paginate(PaginationOptions::default(), |p: PaginationOptions| {
+                requests.push(p.after.clone());
+                let response = match p.after.as_deref() {
+                    None => page(&["s1", "s2"], true),
+                    Some("s2") => page(&["s3"], false),
+                    Some(other) => panic!("unexpected cursor {}", other),
+                };
+                async move { Ok(response) }
+            })
+            .try_collect()
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = items.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["s1", "s2", "s3"]);
+        assert_eq!(requests, [None, Some("s2".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_follows_before_cursor() {
+        let start = PaginationOptions {
+            before: Some("s9".to_string()),
+            ..Default::default()
+        };
+        let items: Vec<segments::Segment> = paginate(start, |p: PaginationOptions| {
+            let response = match p.before.as_deref() {
+                Some("s9") => page(&["s7", "s8"], true),
+                Some("s7") => page(&["s6"], false),
+                other => panic!("unexpected cursor {:?}", other),
+            };
+            async move { Ok(response) }
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_stops_on_empty_page() {
+        let mut calls = 0;
+        let items: Vec<segments::Segment> =
+            paginate(PaginationOptions::default(), |_: PaginationOptions| {
+                calls += 1;
+                async move { Ok(page(&[], true)) }
+            })
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert!(items.is_empty());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_options_max_items_stops_early() {
+        let mut calls = 0;
+        let options = ListOptions {
+            max_items: Some(3),
+            ..Default::default()
+        };
+        let items = options
+            .collect(|p: PaginationOptions| {
+                calls += 1;
+                assert_eq!(p.limit, Some(MAX_PAGE_SIZE));
+                let response = match p.after.as_deref() {
+                    None => page(&["s1", "s2"], true),
+                    Some("s2") => page(&["s3", "s4"], true),
+                    Some(other) => panic!("unexpected cursor {}", other),
+                };
+                async move { Ok(response) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_options_without_all_fetches_one_page() {
+        let mut calls = 0;
+        let items = ListOptions::default()
+            .collect(|p: PaginationOptions| {
+                calls += 1;
+                assert_eq!(p.limit, None);
+                async move { Ok(page(&["s1"], true)) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_options_stream_fetches_pages_lazily() {
+        let calls = std::cell::Cell::new(0);
+        let options = ListOptions {
+            all: true,
+            ..Default::default()
+        };
+        let mut items = options.stream(|p: PaginationOptions| {
+            calls.set(calls.get() + 1);
+            let response = match p.after.as_deref() {
+                None => page(&["s1", "s2"], true),
+                Some("s2") => page(&["s3"], false),
+                Some(other) => panic!("unexpected cursor {}", other),
+            };
+            async move { Ok(response) }
+        });
+
+        assert_eq!(items.try_next().await.unwrap().unwrap().id, "s1");
+        assert_eq!(items.try_next().await.unwrap().unwrap().id, "s2");
+        assert_eq!(calls.get(), 1);
+        assert_eq!(items.try_next().await.unwrap().unwrap().id, "s3");
+        assert!(items.try_next().await.unwrap().is_none());
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_apply_idempotency_key() {
+        let client = ResendClient::new(Config {
+            api_key: "test_key".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let builder = client.request(Method::POST, "/emails");
+        let request = ResendClient::apply_idempotency_key(builder, Some("key-1".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            request
+                .headers()
+                .get(retry::IDEMPOTENCY_KEY_HEADER)
+                .unwrap(),
+            "key-1"
+        );
+
+        let builder = client.request(Method::POST, "/emails");
+        let request = ResendClient::apply_idempotency_key(builder, None)
+            .build()
+            .unwrap();
+        assert!(!request
+            .headers()
+            .contains_key(retry::IDEMPOTENCY_KEY_HEADER));
     }
 }
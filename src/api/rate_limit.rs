@@ -0,0 +1,142 @@
+//! # Rate Limiter Module
+//!
+//! This module provides the client-side token bucket that keeps `ResendClient`
+//! under Resend's per-second request quota. Every request, including retries,
+//! takes a token from the bucket before it is sent, so bulk commands and
+//! concurrent tasks sharing a client never have to sleep by hand.
+
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Default request quota of a Resend team, in requests per second
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 2.0;
+
+/// Lowest request rate accepted from the configuration, one request every 100 seconds
+pub const MIN_REQUESTS_PER_SECOND: f64 = 0.01;
+
+/// Token bucket limiting the rate of outgoing requests
+///
+/// The bucket holds up to `requests_per_second` tokens (at least one) and is
+/// refilled continuously. Callers that find it empty reserve a token ahead of
+/// time and sleep until it becomes available, so waiting callers are served in
+/// the order they arrived.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Number of tokens added to the bucket per second
+    rate: f64,
+    /// Maximum number of tokens the bucket can hold
+    capacity: f64,
+    /// Current state of the bucket
+    bucket: Mutex<Bucket>,
+}
+
+/// Mutable state of a token bucket
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens; negative when tokens have been reserved ahead of time
+    tokens: f64,
+    /// When the bucket was last refilled
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing the given number of requests per second
+    ///
+    /// # Arguments
+    ///
+    /// * `requests_per_second` - Sustained request rate, which must be positive
+    pub fn new(requests_per_second: f64) -> Self {
+        let capacity = requests_per_second.max(1.0);
+        Self {
+            rate: requests_per_second,
+            capacity,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a request may be sent
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.capacity);
+            bucket.refilled_at = now;
+            bucket.tokens -= 1.0;
+
+            if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::try_from_secs_f64(-bucket.tokens / self.rate).unwrap_or(Duration::MAX)
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_burst_up_to_capacity_is_immediate() {
+        let limiter = RateLimiter::new(10.0);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_capacity_are_delayed() {
+        let limiter = RateLimiter::new(50.0);
+        let start = Instant::now();
+        for _ in 0..60 {
+            limiter.acquire().await;
+        }
+        // 10 requests over the burst capacity need 10 / 50 = 200ms of refill
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_tasks_share_the_quota() {
+        let limiter = Arc::new(RateLimiter::new(50.0));
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        limiter.acquire().await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_tiny_rates_do_not_overflow() {
+        let limiter = RateLimiter::new(1e-300);
+        limiter.acquire().await;
+        let second = tokio::time::timeout(Duration::from_millis(10), limiter.acquire()).await;
+        assert!(second.is_err());
+    }
+
+    #[test]
+    fn test_capacity_is_at_least_one() {
+        let limiter = RateLimiter::new(0.5);
+        assert_eq!(limiter.capacity, 1.0);
+    }
+}
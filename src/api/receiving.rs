@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct ReceivedEmail {
     pub id: String,
     pub from: String,
+    #[tabled(display_with = "crate::output::display::list")]
     pub to: Vec<String>,
     pub subject: String,
     pub created_at: String,
@@ -12,9 +14,11 @@ pub struct ReceivedEmail {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListReceivedEmailsResponse {
     pub data: Vec<ReceivedEmail>,
+    #[serde(default)]
+    pub has_more: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct ReceivedAttachment {
     pub id: String,
     pub filename: String,
@@ -25,4 +29,6 @@ pub struct ReceivedAttachment {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListReceivedAttachmentsResponse {
     pub data: Vec<ReceivedAttachment>,
+    #[serde(default)]
+    pub has_more: bool,
 }
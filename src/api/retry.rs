@@ -0,0 +1,250 @@
+//! # Retry Policy Module
+//!
+//! This module defines how `ResendClient` retries failed requests. Rate-limited
+//! (429) and server error (5xx) responses, as well as connection failures and
+//! timeouts, are retried with jittered exponential backoff. Delays requested by
+//! the API through the `Retry-After` or `ratelimit-reset` headers take precedence
+//! over the computed backoff.
+
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// Name of the header carrying an idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Policy controlling how failed requests are retried
+///
+/// Only idempotent methods (GET, HEAD, OPTIONS, PUT, DELETE) are retried, as well
+/// as POST and PATCH requests that carry an `Idempotency-Key` header, so that a
+/// retry can never cause a side effect twice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one (1 disables retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on every subsequent retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including server-requested ones
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns whether a request may be retried at all
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTP method of the request
+    /// * `headers` - Headers of the request, checked for an idempotency key
+    pub fn allows(&self, method: &Method, headers: &HeaderMap) -> bool {
+        if self.max_attempts <= 1 {
+            return false;
+        }
+        let idempotent = matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+        );
+        idempotent || headers.contains_key(IDEMPOTENCY_KEY_HEADER)
+    }
+
+    /// Returns whether a response status is worth retrying
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+    }
+
+    /// Returns whether a transport error is worth retrying
+    pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+        err.is_connect() || err.is_timeout()
+    }
+
+    /// Computes the delay before the next attempt
+    ///
+    /// A delay requested by the server through response headers wins over the
+    /// jittered exponential backoff. Either is capped at `max_delay`.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - Number of the attempt that just failed, starting at 1
+    /// * `headers` - Headers of the failed response, if any
+    pub fn delay(&self, attempt: u32, headers: Option<&HeaderMap>) -> Duration {
+        let delay = headers
+            .and_then(server_requested_delay)
+            .unwrap_or_else(|| self.backoff(attempt));
+        delay.min(self.max_delay)
+    }
+
+    /// Computes a jittered exponential backoff for the given attempt
+    ///
+    /// The delay is `base_delay * 2^(attempt - 1)`, capped at `max_delay`, of which
+    /// a random amount of up to one half is subtracted ("equal jitter").
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let half = delay / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter)
+    }
+}
+
+/// Reads the delay requested by the server from response headers
+///
+/// Supports `Retry-After` as a number of seconds or an HTTP date, and the
+/// `ratelimit-reset` header that Resend sends with rate-limited responses.
+/// Non-finite values are ignored, and delays too large for a `Duration` saturate,
+/// to be capped by the retry policy.
+pub fn server_requested_delay(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(value) = header_str(headers, "retry-after") {
+        if let Ok(seconds) = value.parse::<f64>() {
+            return seconds_to_duration(seconds);
+        }
+        if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value) {
+            let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+            return Some(wait.to_std().unwrap_or(Duration::ZERO));
+        }
+    }
+    header_str(headers, "ratelimit-reset")
+        .and_then(|value| value.parse::<f64>().ok())
+        .and_then(seconds_to_duration)
+}
+
+/// Converts a number of seconds sent by the server into a duration
+fn seconds_to_duration(seconds: f64) -> Option<Duration> {
+    if !seconds.is_finite() {
+        return None;
+    }
+    Some(Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(Duration::MAX))
+}
+
+/// Returns a header value as a trimmed string, if present and valid UTF-8
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_only_idempotent_requests_are_retried() {
+        let policy = RetryPolicy::default();
+        let empty = HeaderMap::new();
+        let mut keyed = HeaderMap::new();
+        keyed.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("key-1"));
+
+        assert!(policy.allows(&Method::GET, &empty));
+        assert!(policy.allows(&Method::DELETE, &empty));
+        assert!(!policy.allows(&Method::POST, &empty));
+        assert!(!policy.allows(&Method::PATCH, &empty));
+        assert!(policy.allows(&Method::POST, &keyed));
+
+        let disabled = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        assert!(!disabled.allows(&Method::GET, &empty));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(RetryPolicy::is_retryable_status(
+            StatusCode::TOO_MANY_REQUESTS
+        ));
+        assert!(RetryPolicy::is_retryable_status(
+            StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!RetryPolicy::is_retryable_status(
+            StatusCode::UNPROCESSABLE_ENTITY
+        ));
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let tenth = policy.backoff(10);
+            assert!(tenth >= Duration::from_millis(500) && tenth <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        assert_eq!(
+            server_requested_delay(&headers),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn test_retry_after_http_date_in_the_past() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(server_requested_delay(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_ratelimit_reset_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-reset", HeaderValue::from_static("1"));
+        assert_eq!(
+            server_requested_delay(&headers),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_server_delay_is_capped() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_secs(5),
+            ..RetryPolicy::default()
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("3600"));
+        assert_eq!(policy.delay(1, Some(&headers)), Duration::from_secs(5));
+
+        headers.insert("retry-after", HeaderValue::from_static("1e30"));
+        assert_eq!(policy.delay(1, Some(&headers)), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_non_finite_server_delays_are_ignored() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("inf"));
+        assert_eq!(server_requested_delay(&headers), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-reset", HeaderValue::from_static("NaN"));
+        assert_eq!(server_requested_delay(&headers), None);
+    }
+}
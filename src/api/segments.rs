@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSegmentRequest {
     pub name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Segment {
     pub id: String,
     pub name: String,
@@ -15,4 +16,6 @@ pub struct Segment {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListSegmentsResponse {
     pub data: Vec<Segment>,
+    #[serde(default)]
+    pub has_more: bool,
 }
@@ -1,4 +1,7 @@
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTemplateRequest {
@@ -12,14 +15,158 @@ pub struct UpdateTemplateRequest {
     pub html: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[non_exhaustive]
 pub struct Template {
     pub id: String,
     pub name: String,
     pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tabled(skip)]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tabled(skip)]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[tabled(skip)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// Variable of a template, required when it has no fallback value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub key: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_value: Option<Value>,
+}
+
+impl Template {
+    /// Creates a template with the required fields, leaving every optional one unset
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the template
+    /// * `name` - Name of the template
+    /// * `created_at` - Creation timestamp of the template
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        created_at: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            created_at: created_at.into(),
+            subject: None,
+            from: None,
+            variables: Vec::new(),
+        }
+    }
+
+    /// Returns the keys of the required variables missing from `variables`
+    pub fn missing_variables(&self, variables: &Map<String, Value>) -> Vec<&str> {
+        self.variables
+            .iter()
+            .filter(|variable| {
+                variable.fallback_value.is_none() && !variables.contains_key(&variable.key)
+            })
+            .map(|variable| variable.key.as_str())
+            .collect()
+    }
+
+    /// Converts the text values of `number` variables to JSON numbers
+    ///
+    /// Values given as text, such as those of `--var`, are sent as strings
+    /// otherwise. Values of any other type are left unchanged.
+    pub fn convert_variables(&self, variables: &mut Map<String, Value>) -> anyhow::Result<()> {
+        for variable in &self.variables {
+            if variable.kind.as_deref() != Some("number") {
+                continue;
+            }
+            let Some(Value::String(text)) = variables.get(&variable.key) else {
+                continue;
+            };
+            let number = text.trim().parse::<Number>().map_err(|_| {
+                anyhow!(
+                    "Variable '{}' of template '{}' must be a number, got '{}'",
+                    variable.key,
+                    self.name,
+                    text
+                )
+            })?;
+            variables.insert(variable.key.clone(), Value::Number(number));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListTemplatesResponse {
     pub data: Vec<Template>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_variables_ignore_fallbacks() {
+        let template: Template = serde_json::from_value(json!({
+            "id": "tpl_1",
+            "name": "welcome",
+            "created_at": "2023-01-01",
+            "subject": "Welcome, {{{FIRST_NAME}}}",
+            "variables": [
+                {"key": "FIRST_NAME", "type": "string", "fallback_value": null},
+                {"key": "PLAN", "type": "string"},
+                {"key": "SEATS", "type": "number", "fallback_value": 1}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            template.missing_variables(&Map::new()),
+            vec!["FIRST_NAME", "PLAN"]
+        );
+        let provided = json!({"FIRST_NAME": "Ada", "PLAN": "pro"});
+        assert!(template
+            .missing_variables(provided.as_object().unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_convert_variables_parses_numbers() {
+        let template: Template = serde_json::from_value(json!({
+            "id": "tpl_1",
+            "name": "invoice",
+            "created_at": "2023-01-01",
+            "variables": [
+                {"key": "NAME", "type": "string"},
+                {"key": "SEATS", "type": "number"},
+                {"key": "TOTAL", "type": "number"}
+            ]
+        }))
+        .unwrap();
+
+        let mut variables = json!({"NAME": "42", "SEATS": "3", "TOTAL": 9.5});
+        let variables = variables.as_object_mut().unwrap();
+        template.convert_variables(variables).unwrap();
+        assert_eq!(variables["NAME"], "42");
+        assert_eq!(variables["SEATS"], 3);
+        assert_eq!(variables["TOTAL"], 9.5);
+
+        let mut variables = json!({"SEATS": "many"});
+        let err = template
+            .convert_variables(variables.as_object_mut().unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Variable 'SEATS' of template 'invoice' must be a number, got 'many'"
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTopicRequest {
@@ -11,7 +12,7 @@ pub struct UpdateTopicRequest {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Topic {
     pub id: String,
     pub name: String,
@@ -21,4 +22,6 @@ pub struct Topic {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListTopicsResponse {
     pub data: Vec<Topic>,
+    #[serde(default)]
+    pub has_more: bool,
 }
@@ -0,0 +1,276 @@
+//! # HTTP Trace Module
+//!
+//! This module records what `ResendClient` actually sends and receives. Each
+//! attempt is logged with its method, URL, query string, headers, JSON body,
+//! response status, headers, body and latency, either as human-readable lines
+//! on stderr (`--verbose`), as structured JSON lines in a file (`--trace-file`),
+//! or both. The `Authorization` header is always redacted.
+
+use anyhow::{Context, Result};
+use reqwest::header::HeaderMap;
+use reqwest::{Request, StatusCode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Placeholder written in place of credentials
+const REDACTED: &str = "[REDACTED]";
+
+/// Destination of HTTP traces
+#[derive(Debug, Default)]
+pub struct Tracer {
+    /// Whether to print traces to stderr
+    verbose: bool,
+    /// File receiving one JSON object per exchange, if any
+    file: Option<Mutex<File>>,
+}
+
+/// Snapshot of an outgoing request, taken before it is sent
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestTrace {
+    /// Number of the attempt, starting at 1
+    pub attempt: u32,
+    /// HTTP method
+    pub method: String,
+    /// URL without its query string
+    pub url: String,
+    /// Query string, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Request headers, with credentials redacted
+    pub headers: BTreeMap<String, String>,
+    /// Request body, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// One traced request/response exchange, as written to the trace file
+#[derive(Debug, Serialize)]
+struct ExchangeTrace<'a> {
+    timestamp: String,
+    request: &'a RequestTrace,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_headers: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    latency_ms: u128,
+}
+
+impl Tracer {
+    /// Creates a tracer
+    ///
+    /// # Arguments
+    ///
+    /// * `verbose` - Whether to print traces to stderr
+    /// * `trace_file` - Optional path of a file to append JSON lines to
+    pub fn new(verbose: bool, trace_file: Option<&Path>) -> Result<Self> {
+        let file = match trace_file {
+            Some(path) => Some(Mutex::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Could not open trace file {}", path.display()))?,
+            )),
+            None => None,
+        };
+        Ok(Self { verbose, file })
+    }
+
+    /// Returns whether any trace output is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.verbose || self.file.is_some()
+    }
+
+    /// Records an outgoing request and returns its snapshot
+    pub fn request(&self, request: &Request, attempt: u32) -> RequestTrace {
+        let mut url = request.url().clone();
+        let query = url.query().map(str::to_string);
+        url.set_query(None);
+        let trace = RequestTrace {
+            attempt,
+            method: request.method().to_string(),
+            url: url.to_string(),
+            query,
+            headers: redacted_headers(request.headers()),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map(|b| String::from_utf8_lossy(b).into_owned()),
+        };
+
+        if self.verbose {
+            let mut out = format!("> {} {}", trace.method, trace.url);
+            if attempt > 1 {
+                out.push_str(&format!(" (attempt {})", attempt));
+            }
+            if let Some(query) = &trace.query {
+                out.push_str(&format!("\n> query: {}", query));
+            }
+            for (name, value) in &trace.headers {
+                out.push_str(&format!("\n> {}: {}", name, value));
+            }
+            if let Some(body) = &trace.body {
+                out.push_str(&format!("\n> {}", body));
+            }
+            eprintln!("{}", out);
+        }
+        trace
+    }
+
+    /// Records the response received for a request
+    pub fn response(
+        &self,
+        request: &RequestTrace,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+        latency: Duration,
+    ) {
+        let headers = redacted_headers(headers);
+        if self.verbose {
+            let mut out = format!("< {} ({} ms)", status, latency.as_millis());
+            for (name, value) in &headers {
+                out.push_str(&format!("\n< {}: {}", name, value));
+            }
+            if !body.is_empty() {
+                out.push_str(&format!("\n< {}", body));
+            }
+            eprintln!("{}", out);
+        }
+        self.write(ExchangeTrace {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            request,
+            status: Some(status.as_u16()),
+            response_headers: Some(headers),
+            response_body: Some(body),
+            error: None,
+            latency_ms: latency.as_millis(),
+        });
+    }
+
+    /// Records a request that failed without a response
+    pub fn error(&self, request: &RequestTrace, error: &reqwest::Error, latency: Duration) {
+        if self.verbose {
+            eprintln!("< error after {} ms: {}", latency.as_millis(), error);
+        }
+        self.write(ExchangeTrace {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            request,
+            status: None,
+            response_headers: None,
+            response_body: None,
+            error: Some(error.to_string()),
+            latency_ms: latency.as_millis(),
+        });
+    }
+
+    /// Appends an exchange to the trace file, if any
+    fn write(&self, exchange: ExchangeTrace) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let Ok(line) = serde_json::to_string(&exchange) else {
+            return;
+        };
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        // Tracing must never make a request fail, so write errors are ignored
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Converts headers to a sorted map, redacting credentials
+fn redacted_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == reqwest::header::AUTHORIZATION {
+                redact_authorization(value.to_str().unwrap_or_default())
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Redacts the credentials of an `Authorization` header value, keeping its scheme
+fn redact_authorization(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+        None => REDACTED.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_authorization_is_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer re_secret_key"),
+        );
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+
+        let redacted = redacted_headers(&headers);
+        assert_eq!(redacted["authorization"], "Bearer [REDACTED]");
+        assert_eq!(redacted["content-type"], "application/json");
+        assert_eq!(redact_authorization("re_secret_key"), "[REDACTED]");
+    }
+
+    #[test]
+    fn test_trace_file_contains_redacted_exchange() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let tracer = Tracer::new(false, Some(&path)).unwrap();
+        assert!(tracer.is_enabled());
+
+        let request = reqwest::Client::new()
+            .post("https://api.resend.com/emails?limit=10")
+            .header("Authorization", "Bearer re_secret_key")
+            .body(r#"{"subject":"Hi"}"#)
+            .build()
+            .unwrap();
+        let trace = tracer.request(&request, 1);
+        tracer.response(
+            &trace,
+            StatusCode::OK,
+            &HeaderMap::new(),
+            r#"{"id":"email_1"}"#,
+            Duration::from_millis(42),
+        );
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("re_secret_key"));
+        let line: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(line["request"]["method"], "POST");
+        assert_eq!(line["request"]["url"], "https://api.resend.com/emails");
+        assert_eq!(line["request"]["query"], "limit=10");
+        assert_eq!(line["request"]["body"], r#"{"subject":"Hi"}"#);
+        assert_eq!(
+            line["request"]["headers"]["authorization"],
+            "Bearer [REDACTED]"
+        );
+        assert_eq!(line["status"], 200);
+        assert_eq!(line["response_body"], r#"{"id":"email_1"}"#);
+        assert_eq!(line["latency_ms"], 42);
+    }
+
+    #[test]
+    fn test_disabled_tracer() {
+        assert!(!Tracer::default().is_enabled());
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tabled::Tabled;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateWebhookRequest {
@@ -6,16 +7,20 @@ pub struct CreateWebhookRequest {
     pub events: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Webhook {
     pub id: String,
+    #[tabled(display_with = "crate::output::display::option")]
     pub endpoint: Option<String>,
+    #[tabled(display_with = "crate::output::display::option")]
     pub created_at: Option<String>,
+    #[tabled(display_with = "crate::output::display::option")]
     pub signing_secret: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-
 pub struct ListWebhooksResponse {
     pub data: Vec<Webhook>,
+    #[serde(default)]
+    pub has_more: bool,
 }
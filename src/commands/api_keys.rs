@@ -20,15 +20,20 @@ pub enum ApiKeysSubcommand {
         domain_id: Option<String>,
     },
     /// List API keys
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Delete an API key
     Delete { id: String },
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl ApiKeysCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             ApiKeysSubcommand::Create {
                 name,
@@ -41,20 +46,23 @@ impl ApiKeysCommand {
                     domain_id,
                 };
                 let response = client.create_api_key(request).await?;
-                println!("API Key created successfully!");
-                println!("ID: {}", response.id);
-                if let Some(token) = response.token {
-                    println!("Token: {}", token);
-                    println!("WARNING: This token is only shown once!");
+                let mut message = format!("API Key created successfully!\nID: {}", response.id);
+                if let Some(token) = &response.token {
+                    message.push_str(&format!(
+                        "\nToken: {}\nWARNING: This token is only shown once!",
+                        token
+                    ));
                 }
+                output.message(&message, &response)?;
             }
-            ApiKeysSubcommand::List(pagination) => {
-                let response = client.list_api_keys(pagination).await?;
-                crate::output::print_table(response.data);
+            ApiKeysSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_api_keys(p)), None)
+                    .await?;
             }
             ApiKeysSubcommand::Delete { id } => {
                 client.delete_api_key(&id).await?;
-                println!("API Key {} deleted successfully!", id);
+                output.deleted("API Key", &id)?;
             }
         }
         Ok(())
@@ -65,7 +73,7 @@ impl ApiKeysCommand {
 mod tests {
     use super::*;
     use crate::api::api_keys::{ApiKey, ListApiKeysResponse};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
 
     #[tokio::test]
     async fn test_list_api_keys() {
@@ -79,14 +87,15 @@ mod tests {
                     created_at: "2023-01-01".to_string(),
                     token: None,
                 }],
+                has_more: false,
             })
         });
 
         let cmd = ApiKeysCommand {
-            command: ApiKeysSubcommand::List(PaginationOptions::default()),
+            command: ApiKeysSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -111,7 +120,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -127,7 +136,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 }
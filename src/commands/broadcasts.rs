@@ -1,5 +1,7 @@
 use crate::api::broadcasts::{CreateBroadcastRequest, UpdateBroadcastRequest};
-use anyhow::Result;
+use crate::commands::emails::require_sender;
+use crate::config::{fill_default, Config};
+use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 
 #[derive(Args)]
@@ -14,19 +16,24 @@ pub enum BroadcastsSubcommand {
     Create {
         #[arg(short, long)]
         name: String,
+        /// Segment to send the broadcast to (defaults to the project's segment)
+        #[arg(long)]
+        segment_id: Option<String>,
+        /// Sender's email address (defaults to the profile's default sender)
         #[arg(short, long)]
-        segment_id: String,
-        #[arg(short, long)]
-        from: String,
+        from: Option<String>,
         #[arg(short, long)]
         subject: String,
         #[arg(long)]
         html: Option<String>,
         #[arg(long)]
         text: Option<String>,
+        /// Reply-to addresses (defaults to the project's reply-to addresses)
+        #[arg(long)]
+        reply_to: Option<Vec<String>>,
     },
     /// List broadcasts
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single broadcast
     Get { id: String },
     /// Update a broadcast
@@ -34,7 +41,7 @@ pub enum BroadcastsSubcommand {
         id: String,
         #[arg(short, long)]
         name: Option<String>,
-        #[arg(short, long)]
+        #[arg(long)]
         segment_id: Option<String>,
         #[arg(short, long)]
         from: Option<String>,
@@ -54,9 +61,30 @@ pub enum BroadcastsSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
+use serde_json::json;
 
 impl BroadcastsCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    /// Fills in settings left unset on the command line from the configuration
+    pub fn apply_defaults(&mut self, config: &Config) {
+        if let BroadcastsSubcommand::Create {
+            segment_id,
+            from,
+            reply_to,
+            ..
+        } = &mut self.command
+        {
+            fill_default(segment_id, &config.segment_id);
+            fill_default(from, &config.from);
+            fill_default(reply_to, &config.reply_to);
+        }
+    }
+
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             BroadcastsSubcommand::Create {
                 name,
@@ -65,27 +93,33 @@ impl BroadcastsCommand {
                 subject,
                 html,
                 text,
+                reply_to,
             } => {
                 let request = CreateBroadcastRequest {
                     name,
-                    segment_id,
-                    from,
+                    segment_id: segment_id.context(
+                        "No segment: pass --segment-id or set segment_id in .resend.toml",
+                    )?,
+                    from: require_sender(from)?,
                     subject,
                     html,
                     text,
-                    reply_to: None,
+                    reply_to,
                 };
                 let broadcast = client.create_broadcast(request).await?;
-                println!("Broadcast created successfully!");
-                println!("{:#?}", broadcast);
+                output.mutated("Broadcast created successfully!", &broadcast)?;
             }
-            BroadcastsSubcommand::List(pagination) => {
-                let response = client.list_broadcasts(pagination).await?;
-                println!("{:#?}", response.data);
+            BroadcastsSubcommand::List(options) => {
+                output
+                    .list_stream(
+                        options.stream(|p| client.list_broadcasts(p)),
+                        Some("broadcasts"),
+                    )
+                    .await?;
             }
             BroadcastsSubcommand::Get { id } => {
                 let broadcast = client.get_broadcast(&id).await?;
-                println!("{:#?}", broadcast);
+                output.item(&broadcast)?;
             }
             BroadcastsSubcommand::Update {
                 id,
@@ -107,16 +141,18 @@ impl BroadcastsCommand {
                     reply_to,
                 };
                 let broadcast = client.update_broadcast(&id, request).await?;
-                println!("Broadcast updated successfully!");
-                println!("{:#?}", broadcast);
+                output.mutated("Broadcast updated successfully!", &broadcast)?;
             }
             BroadcastsSubcommand::Delete { id } => {
                 client.delete_broadcast(&id).await?;
-                println!("Broadcast {} deleted successfully!", id);
+                output.deleted("Broadcast", &id)?;
             }
             BroadcastsSubcommand::Send { id } => {
                 client.send_broadcast(&id).await?;
-                println!("Broadcast {} sent successfully!", id);
+                output.message(
+                    &format!("Broadcast {} sent successfully!", id),
+                    &json!({ "id": id }),
+                )?;
             }
         }
         Ok(())
@@ -127,7 +163,7 @@ impl BroadcastsCommand {
 mod tests {
     use super::*;
     use crate::api::broadcasts::{Broadcast, ListBroadcastsResponse};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
 
     #[tokio::test]
     async fn test_create_broadcast() {
@@ -145,25 +181,30 @@ mod tests {
         let cmd = BroadcastsCommand {
             command: BroadcastsSubcommand::Create {
                 name: "Test".to_string(),
-                segment_id: "s_123".to_string(),
-                from: "me@example.com".to_string(),
+                segment_id: Some("s_123".to_string()),
+                from: Some("me@example.com".to_string()),
                 subject: "Sub".to_string(),
                 html: None,
                 text: None,
+                reply_to: None,
             },
         };
-        assert!(cmd.execute(mock).await.is_ok());
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
     }
 
     #[tokio::test]
     async fn test_list_broadcasts() {
         let mut mock = MockResendApi::new();
-        mock.expect_list_broadcasts()
-            .returning(|_| Ok(ListBroadcastsResponse { data: vec![] }));
+        mock.expect_list_broadcasts().returning(|_| {
+            Ok(ListBroadcastsResponse {
+                data: vec![],
+                has_more: false,
+            })
+        });
         let cmd = BroadcastsCommand {
-            command: BroadcastsSubcommand::List(PaginationOptions::default()),
+            command: BroadcastsSubcommand::List(ListOptions::default()),
         };
-        assert!(cmd.execute(mock).await.is_ok());
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
     }
 
     #[tokio::test]
@@ -183,7 +224,7 @@ mod tests {
                 id: "b_123".to_string(),
             },
         };
-        assert!(cmd.execute(mock).await.is_ok());
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
     }
 
     #[tokio::test]
@@ -195,7 +236,7 @@ mod tests {
                 id: "b_123".to_string(),
             },
         };
-        assert!(cmd.execute(mock).await.is_ok());
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
     }
 
     #[tokio::test]
@@ -207,6 +248,6 @@ mod tests {
                 id: "b_123".to_string(),
             },
         };
-        assert!(cmd.execute(mock).await.is_ok());
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
     }
 }
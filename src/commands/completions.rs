@@ -0,0 +1,332 @@
+//! # Completions Command Module
+//!
+//! This module generates shell completion scripts and man pages from the clap
+//! definition of the CLI. On top of the static completions generated by
+//! `clap_complete`, the scripts complete resource IDs (domains, segments, templates
+//! and broadcasts) by calling the hidden `__complete-ids` command, which reads the
+//! IDs cached by previous `list` commands.
+
+use crate::config::Config;
+use crate::output::cache::{IdCache, CACHED_RESOURCES};
+use anyhow::{Context, Result};
+use clap::{Args, Command, ValueEnum};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Shells completion scripts can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Command structure for generating shell completions
+#[derive(Args)]
+pub struct CompletionsCommand {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+/// Command structure for generating man pages
+#[derive(Args)]
+pub struct ManCommand {
+    /// Directory to write a page per command to, instead of printing the main page
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+}
+
+/// Command structure for the hidden command completing resource IDs
+#[derive(Args)]
+pub struct CompleteIdsCommand {
+    /// Words of the command line before the one being completed, without `resend`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub words: Vec<String>,
+}
+
+const BASH_IDS: &str = r#"
+_resend_with_ids() {
+    local ids
+    ids=$(resend __complete-ids "${COMP_WORDS[@]:1:COMP_CWORD-1}" 2>/dev/null | cut -f1)
+    if [[ -n "$ids" && "${COMP_WORDS[COMP_CWORD]}" != -* ]]; then
+        COMPREPLY=($(compgen -W "$ids" -- "${COMP_WORDS[COMP_CWORD]}"))
+        return 0
+    fi
+    _resend "$@"
+}
+complete -F _resend_with_ids -o bashdefault -o default resend
+"#;
+
+const ZSH_IDS: &str = r#"
+_resend_with_ids() {
+    local line
+    local -a ids
+    for line in ${(f)"$(resend __complete-ids "${(@)words[2,CURRENT-1]}" 2>/dev/null)"}; do
+        ids+=("${${line%%$'\t'*}//:/\\:}:${line#*$'\t'}")
+    done
+    if (( ${#ids} )) && [[ $PREFIX != -* ]]; then
+        _describe -t ids 'ID' ids
+        return
+    fi
+    _resend "$@"
+}
+compdef _resend_with_ids resend
+"#;
+
+const FISH_IDS: &str = r#"
+function __fish_resend_complete_ids
+    resend __complete-ids (commandline -opc)[2..-1] 2>/dev/null
+end
+complete -c resend -a '(__fish_resend_complete_ids)'
+"#;
+
+impl CompletionsCommand {
+    /// Prints the completion script of a shell
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Clap definition of the CLI
+    pub fn execute(self, mut cmd: Command) -> Result<()> {
+        let (shell, ids) = match self.shell {
+            CompletionShell::Bash => (Shell::Bash, BASH_IDS),
+            CompletionShell::Zsh => (Shell::Zsh, ZSH_IDS),
+            CompletionShell::Fish => (Shell::Fish, FISH_IDS),
+        };
+        let name = cmd.get_name().to_string();
+        let mut stdout = std::io::stdout();
+        clap_complete::generate(shell, &mut cmd, name, &mut stdout);
+        stdout.write_all(ids.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl ManCommand {
+    /// Prints the main man page, or writes a page per command to a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Clap definition of the CLI
+    pub fn execute(self, cmd: Command) -> Result<()> {
+        match self.out_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir)
+                    .with_context(|| format!("Could not create {}", dir.display()))?;
+                clap_mangen::generate_to(cmd, &dir)
+                    .with_context(|| format!("Could not write man pages to {}", dir.display()))?;
+                println!("Man pages written to {}", dir.display());
+            }
+            None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?,
+        }
+        Ok(())
+    }
+}
+
+impl CompleteIdsCommand {
+    /// Prints the cached IDs, tab-separated from their names, that fit the next word
+    ///
+    /// Nothing is printed when the next word is not a resource ID or no IDs are
+    /// cached, so that the shell falls back to its static completions.
+    ///
+    /// The IDs are read from the cache of the profile and base URL the completed
+    /// command would use, as selected by its words, the environment or the
+    /// configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Clap definition of the CLI
+    /// * `profile` - Profile given with `--profile` or RESEND_PROFILE
+    /// * `base_url` - Base URL given with `--base-url` or RESEND_BASE_URL
+    pub fn execute(
+        self,
+        cmd: Command,
+        profile: Option<String>,
+        base_url: Option<String>,
+    ) -> Result<()> {
+        let Some(resource) = expected_resource(cmd, &self.words) else {
+            return Ok(());
+        };
+        let profile = option_value(&self.words, "--profile").or(profile);
+        let base_url = option_value(&self.words, "--base-url").or(base_url);
+        let (profile, base_url) = Config::locate(profile.as_deref(), base_url.as_deref());
+        let Some(cache) = IdCache::default_location(profile.as_deref(), &base_url) else {
+            return Ok(());
+        };
+        for cached in cache.get(resource) {
+            println!("{}\t{}", cached.id, cached.name.unwrap_or_default());
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value of the last occurrence of a long option among the words
+fn option_value(words: &[String], option: &str) -> Option<String> {
+    words.iter().enumerate().rev().find_map(|(i, word)| {
+        match word.strip_prefix(option)?.strip_prefix('=') {
+            Some(value) => Some(value.to_string()),
+            None if word == option => words.get(i + 1).cloned(),
+            None => None,
+        }
+    })
+}
+
+/// Finds which resource's ID the word following `words` should be
+///
+/// # Arguments
+///
+/// * `cmd` - Clap definition of the CLI
+/// * `words` - Words of the command line before the one being completed
+///
+/// # Returns
+///
+/// The cached resource type, or `None` if the next word is not a cached ID
+pub fn expected_resource(mut cmd: Command, words: &[String]) -> Option<&'static str> {
+    cmd.build();
+    let mut path: Vec<String> = Vec::new();
+    let mut current = &cmd;
+    let mut positionals = 0;
+    let mut pending: Option<String> = None;
+    let mut only_positionals = false;
+
+    for word in words {
+        // The word is the value of the previous option
+        if pending.take().is_some() {
+            continue;
+        }
+        if only_positionals || word == "-" || !word.starts_with('-') {
+            match current.find_subcommand(word) {
+                Some(sub) if !only_positionals && positionals == 0 => {
+                    path.push(sub.get_name().to_string());
+                    current = sub;
+                }
+                _ => positionals += 1,
+            }
+        } else if word == "--" {
+            only_positionals = true;
+        } else if let Some(long) = word.strip_prefix("--") {
+            if !long.contains('=') {
+                pending = current
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(long))
+                    .filter(|arg| arg.get_action().takes_values())
+                    .map(|arg| arg.get_id().to_string());
+            }
+        } else {
+            let shorts: Vec<char> = word.chars().skip(1).collect();
+            for (i, short) in shorts.iter().enumerate() {
+                let takes_value = current
+                    .get_arguments()
+                    .find(|arg| arg.get_short() == Some(*short))
+                    .filter(|arg| arg.get_action().takes_values());
+                if let Some(arg) = takes_value {
+                    if i == shorts.len() - 1 {
+                        pending = Some(arg.get_id().to_string());
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    let arg_id = match pending {
+        Some(id) => id,
+        None => {
+            let args: Vec<_> = current.get_positionals().collect();
+            let arg = args.get(positionals).or_else(|| {
+                args.last()
+                    .filter(|arg| arg.get_num_args().is_some_and(|n| n.max_values() > 1))
+            })?;
+            arg.get_id().to_string()
+        }
+    };
+    resource_for(path.first().map(String::as_str), &arg_id)
+}
+
+/// Maps an argument to the resource whose ID it takes
+fn resource_for(command: Option<&str>, arg_id: &str) -> Option<&'static str> {
+    let resource = match arg_id {
+        "segment_id" => "segments",
+        "domain_id" => "domains",
+        "template_id" => "templates",
+        "broadcast_id" => "broadcasts",
+        "id" => command?,
+        _ => return None,
+    };
+    CACHED_RESOURCES.iter().copied().find(|r| *r == resource)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Parser, Subcommand};
+
+    #[derive(Parser)]
+    #[command(name = "resend")]
+    struct TestCli {
+        #[arg(long, global = true)]
+        profile: Option<String>,
+        #[command(subcommand)]
+        command: TestCommands,
+    }
+
+    #[derive(Subcommand)]
+    enum TestCommands {
+        Broadcasts(crate::commands::broadcasts::BroadcastsCommand),
+        Contacts(crate::commands::contacts::ContactsCommand),
+        Domains(crate::commands::domains::DomainsCommand),
+        Topics(crate::commands::topics::TopicsCommand),
+    }
+
+    fn resource(line: &str) -> Option<&'static str> {
+        use clap::CommandFactory;
+        let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        expected_resource(TestCli::command(), &words)
+    }
+
+    #[test]
+    fn test_expected_resource_of_positionals() {
+        assert_eq!(resource("broadcasts send"), Some("broadcasts"));
+        assert_eq!(
+            resource("--profile staging broadcasts get"),
+            Some("broadcasts")
+        );
+        assert_eq!(resource("domains verify"), Some("domains"));
+        assert_eq!(resource("broadcasts send b_1"), None);
+        assert_eq!(resource("broadcasts"), None);
+        assert_eq!(resource("topics get"), None);
+        assert_eq!(resource("contacts add-to-segment c_1"), Some("segments"));
+    }
+
+    #[test]
+    fn test_expected_resource_of_options() {
+        assert_eq!(
+            resource("broadcasts create --name Launch --segment-id"),
+            Some("segments")
+        );
+        assert_eq!(
+            resource("broadcasts update b_1 --name x --segment-id"),
+            Some("segments")
+        );
+        assert_eq!(resource("broadcasts create --name"), None);
+        assert_eq!(resource("broadcasts update --name x"), Some("broadcasts"));
+    }
+
+    #[test]
+    fn test_option_value() {
+        let words = |line: &str| -> Vec<String> { line.split(' ').map(String::from).collect() };
+        assert_eq!(
+            option_value(&words("--profile staging domains verify"), "--profile"),
+            Some("staging".to_string())
+        );
+        assert_eq!(
+            option_value(
+                &words("--base-url=http://x --base-url=http://y"),
+                "--base-url"
+            ),
+            Some("http://y".to_string())
+        );
+        assert_eq!(option_value(&words("--profiles x"), "--profile"), None);
+        assert_eq!(option_value(&words("domains --profile"), "--profile"), None);
+    }
+}
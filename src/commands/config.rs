@@ -0,0 +1,424 @@
+//! # Config Commands Module
+//!
+//! This module provides the `config` command, which manages the configuration file:
+//! setting the API key and settings of a named profile, listing profiles and
+//! switching the current one. It also shows the resolved configuration and checks
+//! that its API key works.
+
+use crate::api::{PaginationOptions, ResendApi, ResendError, DEFAULT_BASE_URL};
+use crate::config::profiles::mask_key;
+use crate::config::secrets;
+use crate::config::{Config, ConfigFile, ConfigSource, ProjectConfig};
+use crate::output::Output;
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+use tabled::Tabled;
+
+/// Command structure for configuration operations
+///
+/// Without a subcommand, saves the given settings to the selected profile
+/// (`--profile`), or to the current one.
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct ConfigCommand {
+    /// API key for authenticating with the Resend API
+    #[arg(long)]
+    pub api_key: Option<String>,
+    /// Store the API key in the OS keyring instead of the configuration file
+    #[arg(long, conflicts_with = "api_key_command")]
+    pub keyring: bool,
+    /// Shell command printing the API key, e.g. "pass show resend/api-key"
+    #[arg(long, value_name = "COMMAND", conflicts_with = "api_key")]
+    pub api_key_command: Option<String>,
+    /// Default sender address for emails and broadcasts, e.g. "Acme <hi@acme.com>"
+    #[arg(long)]
+    pub from: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<ConfigSubcommand>,
+}
+
+/// Subcommands for configuration operations
+#[derive(Subcommand)]
+pub enum ConfigSubcommand {
+    /// List the profiles of the configuration file
+    List,
+    /// Make a profile the current one
+    Use {
+        /// Name of the profile
+        profile: String,
+    },
+    /// Show the resolved configuration and where its API key comes from
+    Show,
+    /// Create a .resend.toml with project defaults in the current directory
+    Init {
+        /// Default sender address, e.g. "Acme <hi@acme.com>"
+        #[arg(long)]
+        from: Option<String>,
+        /// Default reply-to addresses
+        #[arg(long)]
+        reply_to: Option<Vec<String>>,
+        /// Default segment for broadcasts and contact segment commands
+        #[arg(long)]
+        segment_id: Option<String>,
+        /// Default domain for `domains get` and `domains verify`
+        #[arg(long)]
+        domain_id: Option<String>,
+        /// Replace an existing .resend.toml
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check that the API key works and report its permission
+    Validate,
+}
+
+/// Commands that an API key restricted to sending emails cannot run
+pub const SENDING_ACCESS_UNAVAILABLE: &[&str] = &[
+    "emails get",
+    "emails list",
+    "emails cancel",
+    "emails update",
+    "emails attachments",
+    "api-keys",
+    "domains",
+    "segments",
+    "contacts",
+    "templates",
+    "topics",
+    "webhooks",
+    "broadcasts",
+    "contact-properties",
+    "receiving",
+];
+
+/// Permission of an API key, as found by [`check_access`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAccess {
+    /// The key can call every endpoint
+    FullAccess,
+    /// The key can only send emails
+    SendingAccess,
+    /// The key is missing, invalid or revoked
+    Invalid,
+}
+
+impl std::fmt::Display for KeyAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            KeyAccess::FullAccess => "full_access",
+            KeyAccess::SendingAccess => "sending_access",
+            KeyAccess::Invalid => "invalid",
+        })
+    }
+}
+
+/// Finds the permission of the client's API key with a cheap authenticated call
+///
+/// Listing a single API key requires full access; keys restricted to sending
+/// emails are rejected with a `restricted_api_key` error.
+///
+/// # Returns
+///
+/// The permission of the key, or an error if the API could not be reached
+pub async fn check_access<T: ResendApi + Send + Sync>(client: &T) -> Result<KeyAccess> {
+    let pagination = PaginationOptions {
+        limit: Some(1),
+        ..Default::default()
+    };
+    match client.list_api_keys(pagination).await {
+        Ok(_) => Ok(KeyAccess::FullAccess),
+        Err(err) => match err.downcast_ref::<ResendError>() {
+            Some(ResendError::Auth(details)) if details.name == "restricted_api_key" => {
+                Ok(KeyAccess::SendingAccess)
+            }
+            Some(ResendError::Auth(_)) => Ok(KeyAccess::Invalid),
+            _ => Err(err),
+        },
+    }
+}
+
+/// Resolved configuration, as shown by `config show`
+#[derive(Debug, Serialize, Tabled)]
+pub struct ResolvedConfig {
+    /// Where the API key comes from
+    pub source: ConfigSource,
+    /// Masked API key
+    pub api_key: String,
+    /// Base URL of the API
+    pub base_url: String,
+    /// Project file supplying defaults, if any
+    #[tabled(display_with = "crate::output::display::option")]
+    pub project_file: Option<String>,
+    /// Default sender address
+    #[tabled(display_with = "crate::output::display::option")]
+    pub from: Option<String>,
+    /// Default reply-to addresses
+    #[tabled(display_with = "crate::output::display::option_list")]
+    pub reply_to: Option<Vec<String>>,
+    /// Default segment
+    #[tabled(display_with = "crate::output::display::option")]
+    pub segment_id: Option<String>,
+    /// Default domain
+    #[tabled(display_with = "crate::output::display::option")]
+    pub domain_id: Option<String>,
+    /// Maximum number of attempts per request
+    #[tabled(display_with = "crate::output::display::option")]
+    pub max_attempts: Option<u32>,
+    /// Client-side request quota, in requests per second
+    #[tabled(display_with = "crate::output::display::option")]
+    pub requests_per_second: Option<f64>,
+    /// Timeout for a whole request, in seconds
+    #[tabled(display_with = "crate::output::display::option")]
+    pub timeout: Option<f64>,
+    /// Timeout for establishing a connection, in seconds
+    #[tabled(display_with = "crate::output::display::option")]
+    pub connect_timeout: Option<f64>,
+    /// Proxy URL
+    #[tabled(display_with = "crate::output::display::option")]
+    pub proxy: Option<String>,
+    /// Path to a PEM file of additional CA certificates
+    #[tabled(display_with = "crate::output::display::option")]
+    pub ca_cert: Option<String>,
+}
+
+impl ResolvedConfig {
+    /// Describes a loaded configuration, masking its API key
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Loaded configuration
+    /// * `source` - Where the API key of the configuration came from
+    /// * `project_file` - Path of the `.resend.toml` supplying defaults, if any
+    pub fn new(config: &Config, source: &ConfigSource, project_file: Option<&Path>) -> Self {
+        Self {
+            source: source.clone(),
+            project_file: project_file.map(|path| path.display().to_string()),
+            api_key: mask_key(&config.api_key),
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            from: config.from.clone(),
+            reply_to: config.reply_to.clone(),
+            segment_id: config.segment_id.clone(),
+            domain_id: config.domain_id.clone(),
+            max_attempts: config.max_attempts,
+            requests_per_second: config.requests_per_second,
+            timeout: config.timeout,
+            connect_timeout: config.connect_timeout,
+            proxy: config.proxy.clone(),
+            ca_cert: config
+                .ca_cert
+                .as_ref()
+                .map(|path| path.display().to_string()),
+        }
+    }
+}
+
+/// Result of `config validate`
+#[derive(Debug, Serialize, Tabled)]
+pub struct KeyCheck {
+    /// Masked API key
+    pub api_key: String,
+    /// Permission of the key
+    pub access: KeyAccess,
+    /// Commands the key cannot run
+    #[tabled(display_with = "crate::output::display::list")]
+    pub unavailable_commands: Vec<String>,
+}
+
+impl KeyCheck {
+    /// Describes the permission of an API key
+    pub fn new(api_key: &str, access: KeyAccess) -> Self {
+        let unavailable_commands = match access {
+            KeyAccess::FullAccess => Vec::new(),
+            KeyAccess::SendingAccess => SENDING_ACCESS_UNAVAILABLE
+                .iter()
+                .map(|command| command.to_string())
+                .collect(),
+            KeyAccess::Invalid => vec!["all".to_string()],
+        };
+        Self {
+            api_key: mask_key(api_key),
+            access,
+            unavailable_commands,
+        }
+    }
+}
+
+/// Summary of a profile, as shown by `config list`
+#[derive(Debug, Serialize, Tabled)]
+pub struct ProfileSummary {
+    /// Name of the profile
+    pub name: String,
+    /// Whether this is the current profile
+    pub current: bool,
+    /// Masked API key, or where it is kept when not in the file
+    pub api_key: String,
+    /// Base URL of the API, if not the default one
+    #[tabled(display_with = "crate::output::display::option")]
+    pub base_url: Option<String>,
+    /// Default sender address
+    #[tabled(display_with = "crate::output::display::option")]
+    pub from: Option<String>,
+}
+
+impl ConfigCommand {
+    /// Returns whether the command only manages the configuration file, needing
+    /// neither a loaded configuration nor an API client
+    pub fn is_local(&self) -> bool {
+        !matches!(
+            self.command,
+            Some(ConfigSubcommand::Show | ConfigSubcommand::Validate)
+        )
+    }
+
+    /// Executes the `show` and `validate` subcommands against a loaded configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The API client used to check the API key
+    /// * `config` - Loaded configuration
+    /// * `source` - Where the API key of the configuration came from
+    /// * `output` - Renderer for the command result
+    pub async fn inspect<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        config: &Config,
+        source: &ConfigSource,
+        output: &Output,
+    ) -> Result<()> {
+        match self.command {
+            Some(ConfigSubcommand::Show) => {
+                let project = ProjectConfig::discover_from_current_dir()?;
+                let project_file = project.as_ref().map(|(path, _)| path.as_path());
+                output.item(&ResolvedConfig::new(config, source, project_file))
+            }
+            Some(ConfigSubcommand::Validate) => {
+                let access = check_access(&client).await?;
+                output.item(&KeyCheck::new(&config.api_key, access))?;
+                if access == KeyAccess::Invalid {
+                    bail!("The API key from {} is invalid", source);
+                }
+                Ok(())
+            }
+            _ => unreachable!("local config commands are run by execute"),
+        }
+    }
+
+    /// Executes the config command
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile selected with `--profile` or RESEND_PROFILE, if any
+    /// * `overrides` - Settings given as global flags, saved along with the API key
+    /// * `output` - Renderer for the command result
+    pub fn execute(
+        self,
+        profile: Option<String>,
+        overrides: Config,
+        output: &Output,
+    ) -> Result<()> {
+        match self.command {
+            None => {
+                if self.api_key.is_none()
+                    && self.api_key_command.is_none()
+                    && !self.keyring
+                    && self.from.is_none()
+                    && overrides == Config::default()
+                {
+                    bail!("Nothing to configure. Pass --api-key, --from or another setting, or use 'resend config show'.");
+                }
+                let file = ConfigFile::load()?;
+                let name = profile.unwrap_or_else(|| file.current_name().to_string());
+                let mut config = file.profiles.get(&name).cloned().unwrap_or_default();
+                if let Some(api_key) = self.api_key {
+                    config.api_key = api_key;
+                    config.api_key_command = None;
+                }
+                if let Some(command) = self.api_key_command {
+                    config.api_key.clear();
+                    config.api_key_command = Some(command);
+                    config.keyring = false;
+                }
+                // A new key of a keyring profile goes to the keyring too
+                if (self.keyring || config.keyring) && !config.api_key.is_empty() {
+                    secrets::keyring_set(&name, &config.api_key)?;
+                    config.api_key.clear();
+                    config.keyring = true;
+                }
+                if config.api_key.is_empty() && config.api_key_command.is_none() && !config.keyring
+                {
+                    bail!(
+                        "Profile '{}' does not exist yet. Pass --api-key <KEY> or --api-key-command <COMMAND> to create it.",
+                        name
+                    );
+                }
+                config.merge(overrides);
+                config.merge(Config {
+                    from: self.from,
+                    ..Default::default()
+                });
+                config.save_profile(&name)?;
+                output.message(
+                    &format!("Configuration saved successfully to profile '{}'!", name),
+                    &json!({ "profile": name, "saved": true }),
+                )?;
+            }
+            Some(ConfigSubcommand::List) => {
+                let file = ConfigFile::load()?;
+                let profiles = file
+                    .profiles
+                    .iter()
+                    .map(|(name, config)| ProfileSummary {
+                        name: name.clone(),
+                        current: name == file.current_name(),
+                        api_key: match &config.api_key_command {
+                            Some(command) => format!("command: {}", command),
+                            None if config.keyring => "keyring".to_string(),
+                            None => mask_key(&config.api_key),
+                        },
+                        base_url: config.base_url.clone(),
+                        from: config.from.clone(),
+                    })
+                    .collect();
+                output.list::<ProfileSummary>(profiles)?;
+            }
+            Some(ConfigSubcommand::Use { profile }) => {
+                let mut file = ConfigFile::load()?;
+                file.use_profile(&profile)?;
+                file.save()?;
+                output.message(
+                    &format!("Now using profile '{}'", profile),
+                    &json!({ "profile": profile, "current": true }),
+                )?;
+            }
+            Some(ConfigSubcommand::Init {
+                from,
+                reply_to,
+                segment_id,
+                domain_id,
+                force,
+            }) => {
+                let project = ProjectConfig {
+                    from,
+                    reply_to,
+                    segment_id,
+                    domain_id,
+                };
+                let path = project.init(&std::env::current_dir()?, force)?;
+                output.message(
+                    &format!("Created {}", path.display()),
+                    &json!({ "path": path }),
+                )?;
+            }
+            Some(ConfigSubcommand::Show | ConfigSubcommand::Validate) => {
+                unreachable!("config show and validate are run by inspect")
+            }
+        }
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@ pub enum ContactPropertiesSubcommand {
         fallback_value: Option<String>,
     },
     /// List contact properties
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single contact property
     Get { id: String },
     /// Update a contact property
@@ -34,9 +34,14 @@ pub enum ContactPropertiesSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl ContactPropertiesCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             ContactPropertiesSubcommand::Create {
                 key,
@@ -50,16 +55,16 @@ impl ContactPropertiesCommand {
                     fallback_value: fallback,
                 };
                 let property = client.create_contact_property(request).await?;
-                println!("Contact property created successfully!");
-                println!("{:#?}", property);
+                output.mutated("Contact property created successfully!", &property)?;
             }
-            ContactPropertiesSubcommand::List(pagination) => {
-                let response = client.list_contact_properties(pagination).await?;
-                println!("{:#?}", response.data);
+            ContactPropertiesSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_contact_properties(p)), None)
+                    .await?;
             }
             ContactPropertiesSubcommand::Get { id } => {
                 let property = client.get_contact_property(&id).await?;
-                println!("{:#?}", property);
+                output.item(&property)?;
             }
             ContactPropertiesSubcommand::Update { id, fallback_value } => {
                 let fallback = fallback_value.map(serde_json::Value::String);
@@ -67,12 +72,11 @@ impl ContactPropertiesCommand {
                     fallback_value: fallback,
                 };
                 let property = client.update_contact_property(&id, request).await?;
-                println!("Contact property updated successfully!");
-                println!("{:#?}", property);
+                output.mutated("Contact property updated successfully!", &property)?;
             }
             ContactPropertiesSubcommand::Delete { id } => {
                 client.delete_contact_property(&id).await?;
-                println!("Contact property {} deleted successfully!", id);
+                output.deleted("Contact property", &id)?;
             }
         }
         Ok(())
@@ -1,5 +1,6 @@
 use crate::api::contacts::{CreateContactRequest, UpdateContactRequest};
-use anyhow::Result;
+use crate::config::{fill_default, Config};
+use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 
 #[derive(Args)]
@@ -22,7 +23,7 @@ pub enum ContactsSubcommand {
         unsubscribed: Option<bool>,
     },
     /// List contacts
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single contact
     Get { id: String },
     /// Update a contact
@@ -40,19 +41,41 @@ pub enum ContactsSubcommand {
     /// Add contact to segment
     AddToSegment {
         contact_id: String,
-        segment_id: String,
+        /// Segment ID (defaults to the project's segment)
+        segment_id: Option<String>,
     },
     /// Remove contact from segment
     RemoveFromSegment {
         contact_id: String,
-        segment_id: String,
+        /// Segment ID (defaults to the project's segment)
+        segment_id: Option<String>,
     },
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
+use serde_json::json;
+
+/// Returns the segment ID, failing with a hint when there is none
+fn require_segment(segment_id: Option<String>) -> Result<String> {
+    segment_id.context("No segment: pass a segment ID or set segment_id in .resend.toml")
+}
 
 impl ContactsCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    /// Fills in settings left unset on the command line from the configuration
+    pub fn apply_defaults(&mut self, config: &Config) {
+        if let ContactsSubcommand::AddToSegment { segment_id, .. }
+        | ContactsSubcommand::RemoveFromSegment { segment_id, .. } = &mut self.command
+        {
+            fill_default(segment_id, &config.segment_id);
+        }
+    }
+
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             ContactsSubcommand::Create {
                 email,
@@ -68,16 +91,16 @@ impl ContactsCommand {
                     properties: None,
                 };
                 let contact = client.create_contact(request).await?;
-                println!("Contact created successfully!");
-                println!("{:#?}", contact);
+                output.mutated("Contact created successfully!", &contact)?;
             }
-            ContactsSubcommand::List(pagination) => {
-                let response = client.list_contacts(pagination).await?;
-                println!("{:#?}", response.data);
+            ContactsSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_contacts(p)), None)
+                    .await?;
             }
             ContactsSubcommand::Get { id } => {
                 let contact = client.get_contact(&id).await?;
-                println!("{:#?}", contact);
+                output.item(&contact)?;
             }
             ContactsSubcommand::Update {
                 id,
@@ -91,36 +114,43 @@ impl ContactsCommand {
                     unsubscribed,
                 };
                 let contact = client.update_contact(&id, request).await?;
-                println!("Contact updated successfully!");
-                println!("{:#?}", contact);
+                output.mutated("Contact updated successfully!", &contact)?;
             }
             ContactsSubcommand::Delete { id } => {
                 client.delete_contact(&id).await?;
-                println!("Contact {} deleted successfully!", id);
+                output.deleted("Contact", &id)?;
             }
             ContactsSubcommand::AddToSegment {
                 contact_id,
                 segment_id,
             } => {
+                let segment_id = require_segment(segment_id)?;
                 client
                     .add_contact_to_segment(&contact_id, &segment_id)
                     .await?;
-                println!(
-                    "Contact {} added to segment {} successfully!",
-                    contact_id, segment_id
-                );
+                output.message(
+                    &format!(
+                        "Contact {} added to segment {} successfully!",
+                        contact_id, segment_id
+                    ),
+                    &json!({ "contact_id": contact_id, "segment_id": segment_id }),
+                )?;
             }
             ContactsSubcommand::RemoveFromSegment {
                 contact_id,
                 segment_id,
             } => {
+                let segment_id = require_segment(segment_id)?;
                 client
                     .delete_contact_from_segment(&contact_id, &segment_id)
                     .await?;
-                println!(
-                    "Contact {} removed from segment {} successfully!",
-                    contact_id, segment_id
-                );
+                output.message(
+                    &format!(
+                        "Contact {} removed from segment {} successfully!",
+                        contact_id, segment_id
+                    ),
+                    &json!({ "contact_id": contact_id, "segment_id": segment_id }),
+                )?;
             }
         }
         Ok(())
@@ -131,7 +161,7 @@ impl ContactsCommand {
 mod tests {
     use super::*;
     use crate::api::contacts::{Contact, ListContactsResponse};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
 
     #[tokio::test]
     async fn test_list_contacts() {
@@ -147,14 +177,15 @@ mod tests {
                     created_at: "2023-01-01".to_string(),
                     unsubscribed: false,
                 }],
+                has_more: false,
             })
         });
 
         let cmd = ContactsCommand {
-            command: ContactsSubcommand::List(PaginationOptions::default()),
+            command: ContactsSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 }
@@ -5,7 +5,8 @@
 //! deleting, and verifying domains.
 
 use crate::api::domains::CreateDomainRequest;
-use anyhow::Result;
+use crate::config::{fill_default, Config};
+use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 
 /// Command structure for domain-related operations
@@ -34,11 +35,11 @@ pub enum DomainsSubcommand {
         region: Option<String>,
     },
     /// List domains with optional pagination
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single domain by its ID
     Get {
-        /// ID of the domain to retrieve
-        id: String,
+        /// ID of the domain to retrieve (defaults to the project's domain)
+        id: Option<String>,
     },
     /// Delete a domain by its ID
     Delete {
@@ -47,14 +48,33 @@ pub enum DomainsSubcommand {
     },
     /// Verify a domain by its ID
     Verify {
-        /// ID of the domain to verify
-        id: String,
+        /// ID of the domain to verify (defaults to the project's domain)
+        id: Option<String>,
     },
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
+use serde_json::json;
+
+/// Returns the domain ID, failing with a hint when there is none
+fn require_domain(id: Option<String>) -> Result<String> {
+    id.context("No domain: pass a domain ID or set domain_id in .resend.toml")
+}
 
 impl DomainsCommand {
+    /// Fills in settings left unset on the command line from the configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Loaded configuration, holding the default domain
+    pub fn apply_defaults(&mut self, config: &Config) {
+        if let DomainsSubcommand::Get { id } | DomainsSubcommand::Verify { id } = &mut self.command
+        {
+            fill_default(id, &config.domain_id);
+        }
+    }
+
     /// Executes the domain command based on the selected subcommand
     ///
     /// This method handles the execution of different domain-related operations
@@ -68,33 +88,43 @@ impl DomainsCommand {
     ///
     /// * `self` - The domain command with its selected subcommand
     /// * `client` - The API client to use for executing the command
+    /// * `output` - Renderer for the command result
     ///
     /// # Returns
     ///
     /// Ok(()) if the command executed successfully, or an error if the operation failed
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             DomainsSubcommand::Create { name, region } => {
                 let request = CreateDomainRequest { name, region };
                 let domain = client.create_domain(request).await?;
-                println!("Domain created successfully!");
-                println!("{:#?}", domain);
+                output.mutated("Domain created successfully!", &domain)?;
             }
-            DomainsSubcommand::List(pagination) => {
-                let response = client.list_domains(pagination).await?;
-                crate::output::print_table(response.data);
+            DomainsSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_domains(p)), Some("domains"))
+                    .await?;
             }
             DomainsSubcommand::Get { id } => {
+                let id = require_domain(id)?;
                 let domain = client.get_domain(&id).await?;
-                println!("{:#?}", domain);
+                output.item(&domain)?;
             }
             DomainsSubcommand::Delete { id } => {
                 client.delete_domain(&id).await?;
-                println!("Domain {} deleted successfully!", id);
+                output.deleted("Domain", &id)?;
             }
             DomainsSubcommand::Verify { id } => {
+                let id = require_domain(id)?;
                 client.verify_domain(&id).await?;
-                println!("Verification process initiated for domain {}!", id);
+                output.message(
+                    &format!("Verification process initiated for domain {}!", id),
+                    &json!({ "id": id }),
+                )?;
             }
         }
         Ok(())
@@ -105,7 +135,7 @@ impl DomainsCommand {
 mod tests {
     use super::*;
     use crate::api::domains::{Domain, ListDomainsResponse};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
 
     #[tokio::test]
     async fn test_list_domains() {
@@ -120,14 +150,56 @@ mod tests {
                     status: "verified".to_string(),
                     region: "us-east-1".to_string(),
                 }],
+                has_more: false,
             })
         });
 
         let cmd = DomainsCommand {
-            command: DomainsSubcommand::List(PaginationOptions::default()),
+            command: DomainsSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_list_all_domains_follows_cursors() {
+        let mut mock = MockResendApi::new();
+        let domain = |id: &str| Domain {
+            id: id.to_string(),
+            name: format!("{}.example.com", id),
+            created_at: "2023-01-01".to_string(),
+            status: "verified".to_string(),
+            region: "us-east-1".to_string(),
+        };
+
+        mock.expect_list_domains()
+            .withf(|p| p.after.is_none())
+            .times(1)
+            .returning(move |_| {
+                Ok(ListDomainsResponse {
+                    data: vec![domain("dom_1"), domain("dom_2")],
+                    has_more: true,
+                })
+            });
+        mock.expect_list_domains()
+            .withf(|p| p.after.as_deref() == Some("dom_2"))
+            .times(1)
+            .returning(move |_| {
+                Ok(ListDomainsResponse {
+                    data: vec![domain("dom_3")],
+                    has_more: false,
+                })
+            });
+
+        let cmd = DomainsCommand {
+            command: DomainsSubcommand::List(ListOptions {
+                all: true,
+                ..Default::default()
+            }),
+        };
+
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -152,7 +224,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -172,11 +244,11 @@ mod tests {
 
         let cmd = DomainsCommand {
             command: DomainsSubcommand::Get {
-                id: "dom_get_id".to_string(),
+                id: Some("dom_get_id".to_string()),
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -192,7 +264,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -204,11 +276,37 @@ mod tests {
 
         let cmd = DomainsCommand {
             command: DomainsSubcommand::Verify {
-                id: "dom_verify_id".to_string(),
+                id: Some("dom_verify_id".to_string()),
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_verify_domain_uses_project_default() {
+        let mut mock = MockResendApi::new();
+
+        mock.expect_verify_domain()
+            .withf(|id| id == "dom_project")
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let verify = || DomainsCommand {
+            command: DomainsSubcommand::Verify { id: None },
+        };
+        let err = verify()
+            .execute(MockResendApi::new(), &Output::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(".resend.toml"));
+
+        let mut cmd = verify();
+        cmd.apply_defaults(&Config {
+            domain_id: Some("dom_project".to_string()),
+            ..Default::default()
+        });
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
 }
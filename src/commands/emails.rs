@@ -4,10 +4,19 @@
 //! through the Resend API. It includes commands for sending, retrieving, listing,
 //! canceling, and updating emails.
 
-use crate::api::emails::{SendEmailRequest, UpdateEmailRequest};
-use anyhow::Result;
+use crate::api::emails::{
+    derive_idempotency_key, parse_header, EmailAttachment, EmailTemplate, SendEmailRequest, Tag,
+    UpdateEmailRequest, MAX_ATTACHMENTS_SIZE,
+};
+use crate::api::templates::Template;
+use crate::commands::templates::resolve_template_to_send;
+use crate::config::{fill_default, Config};
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
 use clap::{Args, Subcommand};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Command structure for email-related operations
 ///
@@ -27,33 +36,86 @@ pub struct EmailsCommand {
 pub enum EmailsSubcommand {
     /// Send an email with specified parameters
     Send {
-        /// Sender's email address
+        /// Sender's email address (defaults to the template's sender, then to the
+        /// profile's default sender)
         #[arg(short, long)]
-        from: String,
+        from: Option<String>,
+        /// Default sender of the profile or project, used after the template's
+        #[arg(skip)]
+        default_from: Option<String>,
         /// Recipient email addresses (can be multiple)
         #[arg(short, long)]
         to: Vec<String>,
-        /// Email subject line
-        #[arg(short, long)]
-        subject: String,
+        /// Carbon copy recipients (can be multiple)
+        #[arg(long)]
+        cc: Vec<String>,
+        /// Blind carbon copy recipients (can be multiple)
+        #[arg(long)]
+        bcc: Vec<String>,
+        /// Reply-to addresses (defaults to the project's reply-to addresses)
+        #[arg(long)]
+        reply_to: Option<Vec<String>>,
+        /// Email subject line (defaults to the template's subject)
+        #[arg(short, long, required_unless_present = "template")]
+        subject: Option<String>,
         /// HTML content of the email
         #[arg(long)]
         html: Option<String>,
         /// Plain text content of the email
         #[arg(long)]
         text: Option<String>,
+        /// ID, alias or name of a template to render the email from
+        #[arg(long, conflicts_with_all = ["html", "text", "inline_images"])]
+        template: Option<String>,
+        /// Template variable as key=value, a number if the template declares it as
+        /// one (can be multiple)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable, requires = "template")]
+        vars: Vec<(String, String)>,
+        /// JSON file of template variables, overridden by --var
+        #[arg(long, value_name = "PATH", requires = "template")]
+        vars_file: Option<PathBuf>,
         /// Scheduled delivery time for the email
         #[arg(long)]
         scheduled_at: Option<String>,
+        /// File to attach (can be multiple)
+        #[arg(long, value_name = "PATH")]
+        attach: Vec<PathBuf>,
+        /// URL of a file for Resend to fetch and attach (can be multiple)
+        #[arg(long, value_name = "URL")]
+        attach_url: Vec<String>,
+        /// Embed images referenced by local paths in the HTML as inline attachments
+        #[arg(long)]
+        inline_images: bool,
+        /// Custom header as Name:Value, e.g. "X-Entity-Ref-ID: 1234" (can be multiple)
+        #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Tag as name=value, e.g. campaign=launch (can be multiple)
+        #[arg(long = "tag", value_name = "NAME=VALUE")]
+        tags: Vec<Tag>,
+        /// Idempotency key preventing the email from being sent twice
+        #[arg(long, conflicts_with = "idempotency_key_from_hash")]
+        idempotency_key: Option<String>,
+        /// Derive the idempotency key from a hash of the request
+        #[arg(long)]
+        idempotency_key_from_hash: bool,
     },
     /// Save an email as a draft
     Draft {
-        /// Sender's email address
+        /// Sender's email address (defaults to the profile's default sender)
         #[arg(short, long)]
-        from: String,
+        from: Option<String>,
         /// Recipient email addresses (can be multiple)
         #[arg(short, long)]
         to: Vec<String>,
+        /// Carbon copy recipients (can be multiple)
+        #[arg(long)]
+        cc: Vec<String>,
+        /// Blind carbon copy recipients (can be multiple)
+        #[arg(long)]
+        bcc: Vec<String>,
+        /// Reply-to addresses (defaults to the project's reply-to addresses)
+        #[arg(long)]
+        reply_to: Option<Vec<String>>,
         /// Email subject line
         #[arg(short, long)]
         subject: String,
@@ -72,6 +134,21 @@ pub enum EmailsSubcommand {
         /// Scheduled delivery time for the email
         #[arg(long)]
         scheduled_at: Option<String>,
+        /// File to attach (can be multiple)
+        #[arg(long, value_name = "PATH")]
+        attach: Vec<PathBuf>,
+        /// URL of a file for Resend to fetch and attach (can be multiple)
+        #[arg(long, value_name = "URL")]
+        attach_url: Vec<String>,
+        /// Embed images referenced by local paths in the HTML as inline attachments
+        #[arg(long)]
+        inline_images: bool,
+        /// Custom header as Name:Value, e.g. "X-Entity-Ref-ID: 1234" (can be multiple)
+        #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Tag as name=value, e.g. campaign=launch (can be multiple)
+        #[arg(long = "tag", value_name = "NAME=VALUE")]
+        tags: Vec<Tag>,
     },
     /// Retrieve a single email by its ID
     Get {
@@ -79,7 +156,7 @@ pub enum EmailsSubcommand {
         id: String,
     },
     /// List sent emails with optional pagination
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Cancel a scheduled email
     Cancel {
         /// ID of the email to cancel
@@ -102,12 +179,153 @@ pub enum EmailsSubcommand {
     SendBatch {
         /// Path to a JSON file containing an array of SendEmailRequest objects
         file: String,
+        /// Idempotency key preventing the batch from being sent twice
+        #[arg(long, conflicts_with = "idempotency_key_from_hash")]
+        idempotency_key: Option<String>,
+        /// Derive the idempotency key from a hash of the batch
+        #[arg(long)]
+        idempotency_key_from_hash: bool,
+        /// Embed images referenced by local paths in the HTML as inline attachments,
+        /// relative to the batch file
+        #[arg(long)]
+        inline_images: bool,
+        /// ID, alias or name of a template for the emails without html, text or template
+        #[arg(long)]
+        template: Option<String>,
+        /// Template variable as key=value shared by the emails, which can override it
+        /// (can be multiple)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+        /// JSON file of template variables shared by the emails, overridden by --var
+        #[arg(long, value_name = "PATH")]
+        vars_file: Option<PathBuf>,
+        /// Default sender of the profile or project, for emails without a sender of
+        /// their own or from their template
+        #[arg(skip)]
+        default_from: Option<String>,
     },
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
+use serde_json::json;
+
+/// Returns the sender address, failing with a hint when there is none
+///
+/// # Arguments
+///
+/// * `from` - Sender given with `--from`, or the default sender of the profile
+pub(crate) fn require_sender(from: Option<String>) -> Result<String> {
+    from.context(
+        "No sender address: pass --from or set a default one with 'resend config --from <ADDRESS>'",
+    )
+}
+
+/// Builds the attachments of an email from `--attach` files and `--attach-url` URLs
+///
+/// # Returns
+///
+/// The attachments, `None` if there are none, or an error if a file cannot be read
+fn attachments(files: &[PathBuf], urls: &[String]) -> Result<Option<Vec<EmailAttachment>>> {
+    let mut attachments = Vec::new();
+    let mut budget = MAX_ATTACHMENTS_SIZE;
+    for file in files {
+        let attachment = EmailAttachment::from_file_within(file, budget)?;
+        budget -= attachment.content_size();
+        attachments.push(attachment);
+    }
+    for url in urls {
+        attachments.push(EmailAttachment::from_url(url)?);
+    }
+    Ok(Some(attachments).filter(|attachments| !attachments.is_empty()))
+}
+
+/// Parses a template variable given as `key=value`
+fn parse_variable(variable: &str) -> Result<(String, String)> {
+    match variable.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => bail!("Variable '{}' must be given as key=value", variable),
+    }
+}
+
+/// Reads template variables from `--vars-file`, overridden by `--var`
+///
+/// # Arguments
+///
+/// * `vars` - Variables given with `--var`, as strings
+/// * `vars_file` - JSON file holding an object of variables
+fn template_variables(
+    vars: Vec<(String, String)>,
+    vars_file: Option<&Path>,
+) -> Result<Map<String, Value>> {
+    let mut variables = match vars_file {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Could not read {}", path.display()))?;
+            match serde_json::from_str(&content)
+                .with_context(|| format!("Invalid variables file {}", path.display()))?
+            {
+                Value::Object(variables) => variables,
+                _ => bail!("Variables file {} must hold a JSON object", path.display()),
+            }
+        }
+        None => Map::new(),
+    };
+    variables.extend(
+        vars.into_iter()
+            .map(|(key, value)| (key, Value::String(value))),
+    );
+    Ok(variables)
+}
+
+/// Checks that the variables of an email include every variable its template requires
+fn check_variables(template: &Template, variables: &Map<String, Value>) -> Result<()> {
+    let missing = template.missing_variables(variables);
+    if !missing.is_empty() {
+        bail!(
+            "Template '{}' requires variables that were not provided: {}. Pass them with --var or --vars-file",
+            template.name,
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Returns `None` for an empty list, which is left out of requests
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    Some(items).filter(|items| !items.is_empty())
+}
 
 impl EmailsCommand {
+    /// Fills in settings left unset on the command line from the configuration
+    ///
+    /// The default sender of emails that may come from a template is kept aside,
+    /// since the template's own sender takes precedence over it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Loaded configuration, holding the default sender and reply-to
+    pub fn apply_defaults(&mut self, config: &Config) {
+        match &mut self.command {
+            EmailsSubcommand::Send {
+                default_from,
+                reply_to,
+                ..
+            } => {
+                default_from.clone_from(&config.from);
+                fill_default(reply_to, &config.reply_to);
+            }
+            EmailsSubcommand::Draft { from, reply_to, .. } => {
+                fill_default(from, &config.from);
+                fill_default(reply_to, &config.reply_to);
+            }
+            EmailsSubcommand::SendBatch { default_from, .. } => {
+                default_from.clone_from(&config.from);
+            }
+            _ => {}
+        }
+    }
+
     /// Executes the email command based on the selected subcommand
     ///
     /// This method handles the execution of different email-related operations
@@ -121,45 +339,121 @@ impl EmailsCommand {
     ///
     /// * `self` - The email command with its selected subcommand
     /// * `client` - The API client to use for executing the command
+    /// * `output` - Renderer for the command result
     ///
     /// # Returns
     ///
     /// Ok(()) if the command executed successfully, or an error if the operation failed
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             EmailsSubcommand::Send {
                 from,
+                default_from,
                 to,
+                cc,
+                bcc,
+                reply_to,
                 subject,
                 html,
                 text,
+                template,
+                vars,
+                vars_file,
                 scheduled_at,
+                attach,
+                attach_url,
+                inline_images,
+                headers,
+                tags,
+                idempotency_key,
+                idempotency_key_from_hash,
             } => {
-                let request = SendEmailRequest {
+                let (template_id, template) = match template {
+                    Some(id_or_name) => match resolve_template_to_send(&client, &id_or_name).await?
+                    {
+                        Some(template) => (Some(template.id.clone()), Some(template)),
+                        None => (Some(id_or_name), None),
+                    },
+                    None => (None, None),
+                };
+                let mut variables = template_variables(vars, vars_file.as_deref())?;
+                if let Some(template) = &template {
+                    check_variables(template, &variables)?;
+                    template.convert_variables(&mut variables)?;
+                }
+                // The sender and subject of a template a dry run did not fetch are
+                // unknown, so only those given on the command line are sent
+                let (from, subject) = if template_id.is_some() && template.is_none() {
+                    (from.unwrap_or_default(), subject.unwrap_or_default())
+                } else {
+                    let from = from
+                        .or_else(|| template.as_ref()?.from.clone())
+                        .or(default_from);
+                    let subject = subject
+                        .or_else(|| template.as_ref()?.subject.clone())
+                        .context("No subject: pass --subject or set one on the template")?;
+                    (require_sender(from)?, subject)
+                };
+
+                let mut request = SendEmailRequest {
                     from,
                     to,
                     subject,
                     html,
                     text,
-                    cc: None,
-                    bcc: None,
-                    reply_to: None,
+                    cc: non_empty(cc),
+                    bcc: non_empty(bcc),
+                    reply_to,
                     scheduled_at,
+                    attachments: attachments(&attach, &attach_url)?,
+                    headers: (!headers.is_empty()).then(|| headers.into_iter().collect()),
+                    tags: non_empty(tags),
+                    template: template_id.map(|id| EmailTemplate { id, variables }),
+                };
+                if inline_images {
+                    request.embed_local_images(Path::new(""))?;
+                }
+                request.validate()?;
+                let idempotency_key = if idempotency_key_from_hash {
+                    Some(derive_idempotency_key(&request)?)
+                } else {
+                    idempotency_key
                 };
-                let response = client.send_email(request).await?;
-                println!("Email sent successfully! ID: {}", response.id);
+                let response = client.send_email(request, idempotency_key).await?;
+                output.message(
+                    &format!("Email sent successfully! ID: {}", response.id),
+                    &response,
+                )?;
             }
             EmailsSubcommand::Draft {
                 from,
                 to,
+                cc,
+                bcc,
+                reply_to,
                 subject,
                 html,
                 text,
                 html_file,
                 text_file,
                 scheduled_at,
+                attach,
+                attach_url,
+                inline_images,
+                headers,
+                tags,
             } => {
                 // Read content from files if provided
+                // Local images are relative to the HTML file they appear in
+                let html_dir = html_file
+                    .as_deref()
+                    .and_then(|file| Path::new(file).parent())
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default();
                 let html_content = if let Some(file) = html_file {
                     std::fs::read_to_string(&file)?
                 } else {
@@ -173,8 +467,8 @@ impl EmailsCommand {
                 };
 
                 // Create the email request with the content
-                let request = SendEmailRequest {
-                    from,
+                let mut request = SendEmailRequest {
+                    from: require_sender(from)?,
                     to,
                     subject,
                     html: if !html_content.is_empty() {
@@ -187,11 +481,19 @@ impl EmailsCommand {
                     } else {
                         None
                     },
-                    cc: None,
-                    bcc: None,
-                    reply_to: None,
+                    cc: non_empty(cc),
+                    bcc: non_empty(bcc),
+                    reply_to,
                     scheduled_at,
+                    attachments: attachments(&attach, &attach_url)?,
+                    headers: (!headers.is_empty()).then(|| headers.into_iter().collect()),
+                    tags: non_empty(tags),
+                    template: None,
                 };
+                if inline_images {
+                    request.embed_local_images(&html_dir)?;
+                }
+                request.validate()?;
 
                 // For draft functionality, we'll save the email request to a local file
                 // instead of sending it to the API
@@ -199,40 +501,144 @@ impl EmailsCommand {
                 let draft_filename = format!("draft_{}.json", Utc::now().timestamp());
                 std::fs::write(&draft_filename, draft_content)?;
 
-                println!("Email draft saved successfully to: {}", draft_filename);
+                output.message(
+                    &format!("Email draft saved successfully to: {}", draft_filename),
+                    &json!({ "path": draft_filename }),
+                )?;
             }
-            EmailsSubcommand::SendBatch { file } => {
-                let content = std::fs::read_to_string(file)?;
-                let requests: Vec<SendEmailRequest> = serde_json::from_str(&content)?;
-                let responses = client.send_email_batch(requests).await?;
-                println!(
+            EmailsSubcommand::SendBatch {
+                file,
+                idempotency_key,
+                idempotency_key_from_hash,
+                inline_images,
+                template,
+                vars,
+                vars_file,
+                default_from,
+            } => {
+                let content = std::fs::read_to_string(&file)?;
+                let mut requests: Vec<SendEmailRequest> = serde_json::from_str(&content)?;
+                let shared_variables = template_variables(vars, vars_file.as_deref())?;
+                // Templates resolved so far, by the ID or name used in the batch
+                // (`None` for templates a dry run did not fetch)
+                let mut templates: HashMap<String, Option<Template>> = HashMap::new();
+                // Local attachment and image paths are relative to the batch file
+                let base_dir = Path::new(&file).parent().unwrap_or(Path::new(""));
+                for (i, request) in requests.iter_mut().enumerate() {
+                    if let Some(attachments) = request.attachments.take() {
+                        let mut budget = MAX_ATTACHMENTS_SIZE.saturating_sub(
+                            attachments.iter().map(EmailAttachment::content_size).sum(),
+                        );
+                        let mut loaded = Vec::with_capacity(attachments.len());
+                        for attachment in attachments {
+                            let local = attachment.is_local();
+                            let attachment = attachment
+                                .load_local(base_dir, budget)
+                                .with_context(|| format!("Email {} of the batch", i + 1))?;
+                            if local {
+                                budget = budget.saturating_sub(attachment.content_size());
+                            }
+                            loaded.push(attachment);
+                        }
+                        request.attachments = Some(loaded);
+                    }
+                    if inline_images {
+                        request.embed_local_images(base_dir)?;
+                    }
+                    if request.template.is_none()
+                        && request.html.is_none()
+                        && request.text.is_none()
+                    {
+                        request.template = template.as_ref().map(|id| EmailTemplate {
+                            id: id.clone(),
+                            variables: Map::new(),
+                        });
+                    }
+                    if let Some(email_template) = &mut request.template {
+                        let mut variables = shared_variables.clone();
+                        variables.extend(std::mem::take(&mut email_template.variables));
+                        email_template.variables = variables;
+
+                        if !templates.contains_key(&email_template.id) {
+                            let resolved =
+                                resolve_template_to_send(&client, &email_template.id).await?;
+                            templates.insert(email_template.id.clone(), resolved);
+                        }
+                        if let Some(resolved) = &templates[&email_template.id] {
+                            check_variables(resolved, &email_template.variables)
+                                .and_then(|()| {
+                                    resolved.convert_variables(&mut email_template.variables)
+                                })
+                                .with_context(|| format!("Email {} of the batch", i + 1))?;
+                            email_template.id = resolved.id.clone();
+                            // The sender defaults to the template's, then to the profile's
+                            if request.from.is_empty() {
+                                request.from = resolved
+                                    .from
+                                    .clone()
+                                    .or_else(|| default_from.clone())
+                                    .context("No sender address: set \"from\" in the batch file, on the template or with 'resend config --from <ADDRESS>'")
+                                    .with_context(|| format!("Email {} of the batch", i + 1))?;
+                            }
+                            if request.subject.is_empty() {
+                                request.subject = resolved
+                                    .subject
+                                    .clone()
+                                    .context(
+                                        "No subject: set one in the batch file or on the template",
+                                    )
+                                    .with_context(|| format!("Email {} of the batch", i + 1))?;
+                            }
+                        }
+                    } else if request.from.is_empty() {
+                        request.from = default_from.clone().unwrap_or_default();
+                    }
+                    request
+                        .validate()
+                        .with_context(|| format!("Email {} of the batch", i + 1))?;
+                }
+                let idempotency_key = if idempotency_key_from_hash {
+                    Some(derive_idempotency_key(&requests)?)
+                } else {
+                    idempotency_key
+                };
+                let responses = client.send_email_batch(requests, idempotency_key).await?;
+                let mut message = format!(
                     "Batch sent successfully! {} emails processed.",
                     responses.len()
                 );
                 for (i, resp) in responses.iter().enumerate() {
-                    println!("  Email {}: ID {}", i + 1, resp.id);
+                    message.push_str(&format!("\n  Email {}: ID {}", i + 1, resp.id));
                 }
+                output.message(&message, &responses)?;
             }
             EmailsSubcommand::Get { id } => {
                 let email = client.get_email(&id).await?;
-                println!("{:#?}", email);
+                output.item(&email)?;
             }
-            EmailsSubcommand::List(pagination) => {
-                let emails = client.list_emails(pagination).await?;
-                crate::output::print_table(emails.data);
+            EmailsSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_emails(p)), None)
+                    .await?;
             }
             EmailsSubcommand::Cancel { id } => {
                 client.cancel_email(&id).await?;
-                println!("Email {} canceled successfully!", id);
+                output.message(
+                    &format!("Email {} canceled successfully!", id),
+                    &json!({ "id": id }),
+                )?;
             }
             EmailsSubcommand::Update { id, scheduled_at } => {
                 let request = UpdateEmailRequest { scheduled_at };
                 let response = client.update_email(&id, request).await?;
-                println!("Email updated successfully! ID: {}", response.id);
+                output.message(
+                    &format!("Email updated successfully! ID: {}", response.id),
+                    &response,
+                )?;
             }
             EmailsSubcommand::Attachments { id } => {
                 let response = client.list_email_attachments(&id).await?;
-                crate::output::print_table(response.data);
+                output.list(response.data)?;
             }
         }
         Ok(())
@@ -245,30 +651,422 @@ mod tests {
     use crate::api::emails::{
         Attachment, Email, ListAttachmentsResponse, ListEmailsResponse, SendEmailResponse,
     };
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
+    use clap::Parser;
+
+    /// Parses the arguments of an `emails` command, e.g. `["get", "email_1"]`
+    fn emails(args: &[&str]) -> EmailsCommand {
+        #[derive(Parser)]
+        struct TestCli {
+            #[command(flatten)]
+            emails: EmailsCommand,
+        }
+        TestCli::parse_from(std::iter::once("emails").chain(args.iter().copied())).emails
+    }
+
+    /// Parses the arguments of `emails send` to recipient@example.com
+    fn send(args: &[&str]) -> EmailsCommand {
+        let line = ["send", "--to", "recipient@example.com"];
+        emails(&[&line[..], args].concat())
+    }
 
     #[tokio::test]
     async fn test_send_email_command() {
         let mut mock = MockResendApi::new();
 
-        mock.expect_send_email().returning(|_| {
+        mock.expect_send_email().returning(|_, _| {
             Ok(SendEmailResponse {
                 id: "test_id".to_string(),
             })
         });
 
-        let cmd = EmailsCommand {
-            command: EmailsSubcommand::Send {
-                from: "test@example.com".to_string(),
-                to: vec!["recipient@example.com".to_string()],
-                subject: "Test Subject".to_string(),
-                html: Some("<h1>Test</h1>".to_string()),
-                text: None,
-                scheduled_at: None,
-            },
+        let cmd = send(&[
+            "--from",
+            "test@example.com",
+            "--subject",
+            "Test Subject",
+            "--html",
+            "<h1>Test</h1>",
+        ]);
+
+        let result = cmd.execute(mock, &Output::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_uses_configured_defaults() {
+        let mut mock = MockResendApi::new();
+
+        mock.expect_send_email()
+            .withf(|request, _| {
+                request.from == "Acme <hi@acme.com>"
+                    && request.reply_to == Some(vec!["support@acme.com".to_string()])
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        let send = || send(&["--subject", "Test Subject", "--text", "Hello"]);
+
+        let err = send()
+            .execute(MockResendApi::new(), &Output::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("resend config --from"));
+
+        let mut cmd = send();
+        cmd.apply_defaults(&Config {
+            from: Some("Acme <hi@acme.com>".to_string()),
+            reply_to: Some(vec!["support@acme.com".to_string()]),
+            ..Default::default()
+        });
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_with_cc_and_bcc() {
+        let mut mock = MockResendApi::new();
+
+        mock.expect_send_email()
+            .withf(|request, _| {
+                request.cc == Some(vec!["Ops Team <ops@x.com>".to_string()])
+                    && request.bcc == Some(vec!["audit@x.com".to_string()])
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        let send = |cc: &str| {
+            send(&[
+                "--from",
+                "test@example.com",
+                "--cc",
+                cc,
+                "--bcc",
+                "audit@x.com",
+                "--subject",
+                "Test Subject",
+                "--text",
+                "Hello",
+            ])
+        };
+
+        // Invalid addresses are rejected before any request is made
+        let err = send("Ops Team <ops@x>")
+            .execute(MockResendApi::new(), &Output::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid cc address"));
+
+        assert!(send("Ops Team <ops@x.com>")
+            .execute(mock, &Output::default())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_with_headers_and_tags() {
+        let mut mock = MockResendApi::new();
+
+        mock.expect_send_email()
+            .withf(|request, _| {
+                let headers = request.headers.as_ref().unwrap();
+                let tags = request.tags.as_ref().unwrap();
+                headers.get("List-Unsubscribe").map(String::as_str)
+                    == Some("<https://acme.com/unsubscribe>")
+                    && tags[0].to_string() == "campaign=launch"
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        let cmd = send(&[
+            "--from",
+            "test@example.com",
+            "--subject",
+            "Test Subject",
+            "--text",
+            "Hello",
+            "--header",
+            "List-Unsubscribe: <https://acme.com/unsubscribe>",
+            "--tag",
+            "campaign=launch",
+        ]);
+
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
+
+    fn welcome_template() -> Template {
+        serde_json::from_value(json!({
+            "id": "tpl_1",
+            "name": "welcome",
+            "created_at": "2023-01-01",
+            "subject": "Welcome!",
+            "from": "Acme <hi@acme.com>",
+            "variables": [
+                {"key": "FIRST_NAME", "type": "string"},
+                {"key": "PLAN", "type": "string", "fallback_value": "free"},
+                {"key": "SEATS", "type": "number", "fallback_value": 1}
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn send_template(vars: &[&str]) -> EmailsCommand {
+        let mut args = vec!["--from", "test@example.com", "--template", "tpl_1"];
+        for var in vars {
+            args.extend(["--var", var]);
+        }
+        send(&args)
+    }
+
+    #[tokio::test]
+    async fn test_send_email_from_template() {
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template()
+            .returning(|_| Ok(welcome_template()));
+        mock.expect_send_email()
+            .withf(|request, _| {
+                let template = request.template.as_ref().unwrap();
+                request.subject == "Welcome!"
+                    && template.id == "tpl_1"
+                    && template.variables["FIRST_NAME"] == "Ada"
+                    && template.variables["SEATS"] == 3
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        assert!(send_template(&["FIRST_NAME=Ada", "SEATS=3"])
+            .execute(mock, &Output::default())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_from_template_requires_variables() {
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template()
+            .returning(|_| Ok(welcome_template()));
+
+        let err = send_template(&[])
+            .execute(mock, &Output::default())
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Template 'welcome' requires variables that were not provided: FIRST_NAME"));
+    }
+
+    #[test]
+    fn test_template_variables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("vars.json");
+        std::fs::write(&file, r#"{"FIRST_NAME": "Ada", "SEATS": 3}"#).unwrap();
+
+        let variables = template_variables(
+            vec![parse_variable("FIRST_NAME=Grace").unwrap()],
+            Some(&file),
+        )
+        .unwrap();
+        assert_eq!(variables["FIRST_NAME"], "Grace");
+        assert_eq!(variables["SEATS"], 3);
+
+        std::fs::write(&file, "[]").unwrap();
+        assert!(template_variables(vec![], Some(&file)).is_err());
+        assert!(parse_variable("=x").is_err());
+        assert_eq!(parse_variable("A=b=c").unwrap().1, "b=c");
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_from_template() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let batch = dir.path().join("batch.json");
+        std::fs::write(
+            &batch,
+            r#"[{"from":"a@acme.com","to":["b@acme.com"],
+                 "template":{"id":"welcome","variables":{"FIRST_NAME":"Ada"}}},
+                {"to":["c@acme.com"]},
+                {"from":"a@acme.com","to":["d@acme.com"],"subject":"Hi","text":"Plain"}]"#,
+        )
+        .unwrap();
+
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template()
+            .times(1)
+            .returning(|_| Ok(welcome_template()));
+        mock.expect_send_email_batch()
+            .withf(|requests, _| {
+                let first = requests[0].template.as_ref().unwrap();
+                let second = requests[1].template.as_ref().unwrap();
+                first.id == "tpl_1"
+                    && first.variables["FIRST_NAME"] == "Ada"
+                    && first.variables["PLAN"] == "pro"
+                    && second.variables["FIRST_NAME"] == "Team"
+                    && requests[0].from == "a@acme.com"
+                    && requests[1].from == "Acme <hi@acme.com>"
+                    && requests[1].subject == "Welcome!"
+                    && requests[2].template.is_none()
+            })
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+
+        let cmd = emails(&[
+            "send-batch",
+            batch.to_str().unwrap(),
+            "--template",
+            "welcome",
+            "--var",
+            "FIRST_NAME=Team",
+            "--var",
+            "PLAN=pro",
+        ]);
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_template_sender_wins_over_the_profile_default() {
+        let profile = Config {
+            from: Some("Profile <profile@acme.com>".to_string()),
+            ..Default::default()
         };
+        let send_as = |expected: &'static str| {
+            let mut mock = MockResendApi::new();
+            mock.expect_get_template()
+                .returning(|_| Ok(welcome_template()));
+            mock.expect_send_email()
+                .withf(move |request, _| request.from == expected)
+                .times(1)
+                .returning(|_, _| {
+                    Ok(SendEmailResponse {
+                        id: "test_id".to_string(),
+                    })
+                });
+            mock
+        };
+
+        let mut cmd = send(&["--template", "tpl_1", "--var", "FIRST_NAME=Ada"]);
+        cmd.apply_defaults(&profile);
+        let mock = send_as("Acme <hi@acme.com>");
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+
+        let mut cmd = send_template(&["FIRST_NAME=Ada"]);
+        cmd.apply_defaults(&profile);
+        let mock = send_as("test@example.com");
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let batch = dir.path().join("batch.json");
+        std::fs::write(
+            &batch,
+            r#"[{"to":["b@acme.com"],"template":{"id":"welcome","variables":{"FIRST_NAME":"Ada"}}},
+                {"to":["c@acme.com"],"subject":"Hi","text":"Plain"}]"#,
+        )
+        .unwrap();
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template()
+            .returning(|_| Ok(welcome_template()));
+        mock.expect_send_email_batch()
+            .withf(|requests, _| {
+                requests[0].from == "Acme <hi@acme.com>"
+                    && requests[1].from == "Profile <profile@acme.com>"
+            })
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        let mut cmd = emails(&["send-batch", batch.to_str().unwrap()]);
+        cmd.apply_defaults(&profile);
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_requires_a_subject_from_somewhere() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let batch = dir.path().join("batch.json");
+        std::fs::write(
+            &batch,
+            r#"[{"from":"a@acme.com","to":["b@acme.com"],"template":{"id":"tpl_1"}}]"#,
+        )
+        .unwrap();
+
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template().returning(|_| {
+            let mut template = welcome_template();
+            template.subject = None;
+            template.variables.clear();
+            Ok(template)
+        });
+        let err = emails(&["send-batch", batch.to_str().unwrap()])
+            .execute(mock, &Output::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Email 1 of the batch");
+        assert!(err.root_cause().to_string().starts_with("No subject"));
+    }
+
+    #[tokio::test]
+    async fn test_send_email_with_idempotency_key() {
+        let mut mock = MockResendApi::new();
 
-        let result = cmd.execute(mock).await;
+        mock.expect_send_email()
+            .withf(|_, key| key.as_deref() == Some("order-123"))
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        let cmd = send(&[
+            "--from",
+            "test@example.com",
+            "--subject",
+            "Test Subject",
+            "--text",
+            "Hello",
+            "--idempotency-key",
+            "order-123",
+        ]);
+
+        let result = cmd.execute(mock, &Output::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_with_idempotency_key_from_hash() {
+        let mut mock = MockResendApi::new();
+
+        mock.expect_send_email()
+            .withf(|request, key| {
+                key.as_deref() == Some(derive_idempotency_key(request).unwrap().as_str())
+            })
+            .returning(|_, _| {
+                Ok(SendEmailResponse {
+                    id: "test_id".to_string(),
+                })
+            });
+
+        let cmd = send(&[
+            "--from",
+            "test@example.com",
+            "--subject",
+            "Test Subject",
+            "--text",
+            "Hello",
+            "--idempotency-key-from-hash",
+        ]);
+
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -276,25 +1074,54 @@ mod tests {
     async fn test_send_email_batch_command() {
         let mut mock = MockResendApi::new();
 
-        mock.expect_send_email_batch().returning(|_| {
+        mock.expect_send_email_batch().returning(|_, _| {
             Ok(vec![SendEmailResponse {
                 id: "batch_test_id".to_string(),
             }])
         });
 
-        let cmd = EmailsCommand {
-            command: EmailsSubcommand::SendBatch {
-                file: "test_data.json".to_string(), // This will fail in real execution but not in mock
-            },
-        };
+        // This will fail in real execution but not in mock
+        let cmd = emails(&["send-batch", "test_data.json"]);
 
         // Since the file doesn't exist, we expect an error when trying to read it
         // But with the mock, the send_email_batch call should succeed
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         // This will fail because the file doesn't exist, so we'll test differently
         assert!(result.is_err()); // Expected to fail due to missing file
     }
 
+    #[tokio::test]
+    async fn test_send_batch_reads_local_attachments() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hi").unwrap();
+        let batch = dir.path().join("batch.json");
+        std::fs::write(
+            &batch,
+            r#"[{"from":"a@acme.com","to":["b@acme.com"],"subject":"S","text":"T",
+                "attachments":[{"path":"notes.txt"},{"path":"https://acme.com/a.pdf"}]}]"#,
+        )
+        .unwrap();
+
+        let mut mock = MockResendApi::new();
+        mock.expect_send_email_batch()
+            .withf(|requests, _| {
+                let attachments = requests[0].attachments.as_ref().unwrap();
+                attachments[0].content.as_deref() == Some("aGk=")
+                    && attachments[0].filename.as_deref() == Some("notes.txt")
+                    && attachments[0].content_type.as_deref() == Some("text/plain")
+                    && attachments[1].path.as_deref() == Some("https://acme.com/a.pdf")
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(vec![SendEmailResponse {
+                    id: "batch_test_id".to_string(),
+                }])
+            });
+
+        let cmd = emails(&["send-batch", batch.to_str().unwrap()]);
+        assert!(cmd.execute(mock, &Output::default()).await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_get_email_command() {
         let mut mock = MockResendApi::new();
@@ -307,6 +1134,7 @@ mod tests {
                 subject: "Subject".to_string(),
                 created_at: "2023-01-01".to_string(),
                 last_event: "delivered".to_string(),
+                tags: None,
             })
         });
 
@@ -316,7 +1144,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -333,15 +1161,17 @@ mod tests {
                     subject: "Subject".to_string(),
                     created_at: "2023-01-01".to_string(),
                     last_event: "delivered".to_string(),
+                    tags: None,
                 }],
+                has_more: false,
             })
         });
 
         let cmd = EmailsCommand {
-            command: EmailsSubcommand::List(PaginationOptions::default()),
+            command: EmailsSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -357,7 +1187,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -378,7 +1208,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 
@@ -394,6 +1224,7 @@ mod tests {
                     size: 1024,
                     content_type: "application/pdf".to_string(),
                 }],
+                has_more: false,
             })
         });
 
@@ -403,7 +1234,7 @@ mod tests {
             },
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 }
@@ -0,0 +1,41 @@
+use crate::mock_server::{load_seed, MockServer, MockState};
+use anyhow::Result;
+use clap::Args;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct MockServerCommand {
+    /// Address to listen on
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub host: IpAddr,
+    /// Port to listen on (0 picks a free port)
+    #[arg(short, long, default_value_t = 4010)]
+    pub port: u16,
+    /// JSON file of resources to start with, keyed by collection (emails, domains, ...)
+    #[arg(long)]
+    pub seed: Option<PathBuf>,
+}
+
+impl MockServerCommand {
+    pub async fn execute(self) -> Result<()> {
+        let state = match &self.seed {
+            Some(path) => load_seed(path)?,
+            None => MockState::default(),
+        };
+        let server = MockServer::bind(SocketAddr::new(self.host, self.port), state)?;
+        println!(
+            "Resend mock server listening on http://{}",
+            server.local_addr()?
+        );
+        // Scripts read the URL from a pipe, which is not line-buffered
+        std::io::stdout().flush()?;
+
+        server
+            .run_until(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+    }
+}
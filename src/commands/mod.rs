@@ -1,11 +1,15 @@
 pub mod api_keys;
 pub mod broadcasts;
+pub mod completions;
+pub mod config;
 pub mod contact_properties;
 pub mod contacts;
 pub mod domains;
 pub mod emails;
+pub mod mock_server;
 pub mod receiving;
 pub mod segments;
 pub mod templates;
 pub mod topics;
 pub mod webhooks;
+pub mod whoami;
@@ -10,7 +10,7 @@ pub struct ReceivingCommand {
 #[derive(Subcommand)]
 pub enum ReceivingSubcommand {
     /// List received emails
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single received email
     Get { id: String },
     /// List attachments for a received email
@@ -18,21 +18,27 @@ pub enum ReceivingSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl ReceivingCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
-            ReceivingSubcommand::List(pagination) => {
-                let response = client.list_received_emails(pagination).await?;
-                println!("{:#?}", response.data);
+            ReceivingSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_received_emails(p)), None)
+                    .await?;
             }
             ReceivingSubcommand::Get { id } => {
                 let email = client.get_received_email(&id).await?;
-                println!("{:#?}", email);
+                output.value(&email)?;
             }
             ReceivingSubcommand::Attachments { id } => {
                 let response = client.list_received_attachments(&id).await?;
-                println!("{:#?}", response.data);
+                output.list(response.data)?;
             }
         }
         Ok(())
@@ -15,7 +15,7 @@ pub enum SegmentsSubcommand {
         name: String,
     },
     /// List segments
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single segment
     Get { id: String },
     /// Delete a segment
@@ -23,26 +23,34 @@ pub enum SegmentsSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl SegmentsCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             SegmentsSubcommand::Create { name } => {
                 let segment = client.create_segment(&name).await?;
-                println!("Segment created successfully!");
-                println!("{:#?}", segment);
+                output.mutated("Segment created successfully!", &segment)?;
             }
-            SegmentsSubcommand::List(pagination) => {
-                let response = client.list_segments(pagination).await?;
-                println!("{:#?}", response.data);
+            SegmentsSubcommand::List(options) => {
+                output
+                    .list_stream(
+                        options.stream(|p| client.list_segments(p)),
+                        Some("segments"),
+                    )
+                    .await?;
             }
             SegmentsSubcommand::Get { id } => {
                 let segment = client.get_segment(&id).await?;
-                println!("{:#?}", segment);
+                output.item(&segment)?;
             }
             SegmentsSubcommand::Delete { id } => {
                 client.delete_segment(&id).await?;
-                println!("Segment {} deleted successfully!", id);
+                output.deleted("Segment", &id)?;
             }
         }
         Ok(())
@@ -53,7 +61,7 @@ impl SegmentsCommand {
 mod tests {
     use super::*;
     use crate::api::segments::{ListSegmentsResponse, Segment};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::{ListOptions, MockResendApi};
 
     #[tokio::test]
     async fn test_list_segments() {
@@ -66,14 +74,15 @@ mod tests {
                     name: "Test Segment".to_string(),
                     created_at: "2023-01-01".to_string(),
                 }],
+                has_more: false,
             })
         });
 
         let cmd = SegmentsCommand {
-            command: SegmentsSubcommand::List(PaginationOptions::default()),
+            command: SegmentsSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
 }
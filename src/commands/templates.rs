@@ -1,5 +1,5 @@
-use crate::api::templates::{CreateTemplateRequest, UpdateTemplateRequest};
-use anyhow::Result;
+use crate::api::templates::{CreateTemplateRequest, Template, UpdateTemplateRequest};
+use anyhow::{bail, Result};
 use clap::{Args, Subcommand};
 
 #[derive(Args)]
@@ -18,7 +18,7 @@ pub enum TemplatesSubcommand {
         html: String,
     },
     /// List templates
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single template
     Get { id: String },
     /// Update a template
@@ -33,34 +33,100 @@ pub enum TemplatesSubcommand {
     Delete { id: String },
 }
 
-use crate::api::ResendApi;
+use crate::api::dry_run::NotFetched;
+use crate::api::{ListOptions, ResendApi, ResendError};
+use crate::output::Output;
+
+/// Fetches a template by ID or alias, falling back to a lookup by name
+///
+/// # Arguments
+///
+/// * `client` - The API client
+/// * `id_or_name` - ID, alias or name of the template
+///
+/// # Returns
+///
+/// The template, or an error if no template or several templates match
+pub(crate) async fn resolve_template<T: ResendApi + Send + Sync>(
+    client: &T,
+    id_or_name: &str,
+) -> Result<Template> {
+    match client.get_template(id_or_name).await {
+        Ok(template) => return Ok(template),
+        Err(err) if matches!(err.downcast_ref(), Some(ResendError::NotFound(_))) => {}
+        Err(err) => return Err(err),
+    }
+
+    let all = ListOptions {
+        all: true,
+        ..Default::default()
+    };
+    let templates = all.collect(|p| client.list_templates(p)).await?;
+    let named: Vec<_> = templates
+        .iter()
+        .filter(|template| template.name == id_or_name)
+        .collect();
+    match named.as_slice() {
+        [] => bail!("No template has the ID or name '{}'", id_or_name),
+        [template] => client.get_template(&template.id).await,
+        _ => bail!(
+            "Several templates are named '{}': pass the template ID instead",
+            id_or_name
+        ),
+    }
+}
+
+/// Resolves the template an email is sent from, unless the client cannot fetch it
+///
+/// A dry run does not fetch templates: the email then names the template as
+/// given, and its sender, subject and required variables are not checked.
+///
+/// # Returns
+///
+/// The template, or `None` if the client is a dry run
+pub(crate) async fn resolve_template_to_send<T: ResendApi + Send + Sync>(
+    client: &T,
+    id_or_name: &str,
+) -> Result<Option<Template>> {
+    match resolve_template(client, id_or_name).await {
+        Ok(template) => Ok(Some(template)),
+        Err(err) if err.downcast_ref::<NotFetched>().is_some() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
 
 impl TemplatesCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             TemplatesSubcommand::Create { name, html } => {
                 let request = CreateTemplateRequest { name, html };
                 let template = client.create_template(request).await?;
-                println!("Template created successfully!");
-                println!("{:#?}", template);
+                output.mutated("Template created successfully!", &template)?;
             }
-            TemplatesSubcommand::List(pagination) => {
-                let response = client.list_templates(pagination).await?;
-                println!("{:#?}", response.data);
+            TemplatesSubcommand::List(options) => {
+                output
+                    .list_stream(
+                        options.stream(|p| client.list_templates(p)),
+                        Some("templates"),
+                    )
+                    .await?;
             }
             TemplatesSubcommand::Get { id } => {
                 let template = client.get_template(&id).await?;
-                println!("{:#?}", template);
+                output.item(&template)?;
             }
             TemplatesSubcommand::Update { id, name, html } => {
                 let request = UpdateTemplateRequest { name, html };
                 let template = client.update_template(&id, request).await?;
-                println!("Template updated successfully!");
-                println!("{:#?}", template);
+                output.mutated("Template updated successfully!", &template)?;
             }
             TemplatesSubcommand::Delete { id } => {
                 client.delete_template(&id).await?;
-                println!("Template {} deleted successfully!", id);
+                output.deleted("Template", &id)?;
             }
         }
         Ok(())
@@ -70,8 +136,8 @@ impl TemplatesCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::templates::{ListTemplatesResponse, Template};
-    use crate::api::{MockResendApi, PaginationOptions};
+    use crate::api::templates::ListTemplatesResponse;
+    use crate::api::MockResendApi;
 
     #[tokio::test]
     async fn test_list_templates() {
@@ -83,15 +149,53 @@ mod tests {
                     id: "tpl_1".to_string(),
                     name: "Test Template".to_string(),
                     created_at: "2023-01-01".to_string(),
+                    subject: None,
+                    from: None,
+                    variables: vec![],
                 }],
+                has_more: false,
             })
         });
 
         let cmd = TemplatesCommand {
-            command: TemplatesSubcommand::List(PaginationOptions::default()),
+            command: TemplatesSubcommand::List(ListOptions::default()),
         };
 
-        let result = cmd.execute(mock).await;
+        let result = cmd.execute(mock, &Output::default()).await;
         assert!(result.is_ok());
     }
+
+    fn template(id: &str, name: &str) -> Template {
+        Template::new(id, name, "2023-01-01")
+    }
+
+    #[tokio::test]
+    async fn test_resolve_template_by_name() {
+        let mut mock = MockResendApi::new();
+        mock.expect_get_template().returning(|id| match id {
+            "tpl_2" => Ok(template("tpl_2", "welcome")),
+            _ => Err(ResendError::from_response(404, "").into()),
+        });
+        mock.expect_list_templates().returning(|_| {
+            Ok(ListTemplatesResponse {
+                data: vec![
+                    template("tpl_1", "receipt"),
+                    template("tpl_2", "welcome"),
+                    template("tpl_3", "digest"),
+                    template("tpl_4", "digest"),
+                ],
+                has_more: false,
+            })
+        });
+
+        assert_eq!(resolve_template(&mock, "tpl_2").await.unwrap().id, "tpl_2");
+        assert_eq!(
+            resolve_template(&mock, "welcome").await.unwrap().id,
+            "tpl_2"
+        );
+        let err = resolve_template(&mock, "missing").await.unwrap_err();
+        assert!(err.to_string().contains("No template has the ID or name"));
+        let err = resolve_template(&mock, "digest").await.unwrap_err();
+        assert!(err.to_string().contains("Several templates"));
+    }
 }
@@ -18,7 +18,7 @@ pub enum TopicsSubcommand {
         default_subscription: String,
     },
     /// List topics
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single topic
     Get { id: String },
     /// Update a topic
@@ -32,9 +32,14 @@ pub enum TopicsSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl TopicsCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             TopicsSubcommand::Create {
                 name,
@@ -45,26 +50,25 @@ impl TopicsCommand {
                     default_subscription,
                 };
                 let topic = client.create_topic(request).await?;
-                println!("Topic created successfully!");
-                println!("{:#?}", topic);
+                output.mutated("Topic created successfully!", &topic)?;
             }
-            TopicsSubcommand::List(pagination) => {
-                let response = client.list_topics(pagination).await?;
-                println!("{:#?}", response.data);
+            TopicsSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_topics(p)), None)
+                    .await?;
             }
             TopicsSubcommand::Get { id } => {
                 let topic = client.get_topic(&id).await?;
-                println!("{:#?}", topic);
+                output.item(&topic)?;
             }
             TopicsSubcommand::Update { id, name } => {
                 let request = UpdateTopicRequest { name };
                 let topic = client.update_topic(&id, request).await?;
-                println!("Topic updated successfully!");
-                println!("{:#?}", topic);
+                output.mutated("Topic updated successfully!", &topic)?;
             }
             TopicsSubcommand::Delete { id } => {
                 client.delete_topic(&id).await?;
-                println!("Topic {} deleted successfully!", id);
+                output.deleted("Topic", &id)?;
             }
         }
         Ok(())
@@ -14,11 +14,11 @@ pub enum WebhooksSubcommand {
     Create {
         #[arg(short, long)]
         endpoint: String,
-        #[arg(short, long)]
+        #[arg(long)]
         events: Vec<String>,
     },
     /// List webhooks
-    List(crate::api::PaginationOptions),
+    List(crate::api::ListOptions),
     /// Get a single webhook
     Get { id: String },
     /// Delete a webhook
@@ -26,27 +26,32 @@ pub enum WebhooksSubcommand {
 }
 
 use crate::api::ResendApi;
+use crate::output::Output;
 
 impl WebhooksCommand {
-    pub async fn execute<T: ResendApi + Send + Sync>(self, client: T) -> Result<()> {
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        output: &Output,
+    ) -> Result<()> {
         match self.command {
             WebhooksSubcommand::Create { endpoint, events } => {
                 let request = CreateWebhookRequest { endpoint, events };
                 let webhook = client.create_webhook(request).await?;
-                println!("Webhook created successfully!");
-                println!("{:#?}", webhook);
+                output.mutated("Webhook created successfully!", &webhook)?;
             }
-            WebhooksSubcommand::List(pagination) => {
-                let response = client.list_webhooks(pagination).await?;
-                println!("{:#?}", response.data);
+            WebhooksSubcommand::List(options) => {
+                output
+                    .list_stream(options.stream(|p| client.list_webhooks(p)), None)
+                    .await?;
             }
             WebhooksSubcommand::Get { id } => {
                 let webhook = client.get_webhook(&id).await?;
-                println!("{:#?}", webhook);
+                output.item(&webhook)?;
             }
             WebhooksSubcommand::Delete { id } => {
                 client.delete_webhook(&id).await?;
-                println!("Webhook {} deleted successfully!", id);
+                output.deleted("Webhook", &id)?;
             }
         }
         Ok(())
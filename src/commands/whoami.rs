@@ -0,0 +1,161 @@
+//! # Whoami Command Module
+//!
+//! This module provides the `whoami` command, which tells which API key the CLI
+//! uses, where it comes from and what it is allowed to do.
+
+use crate::api::{ResendApi, DEFAULT_BASE_URL};
+use crate::commands::config::{check_access, KeyAccess, SENDING_ACCESS_UNAVAILABLE};
+use crate::config::profiles::mask_key;
+use crate::config::{Config, ConfigSource};
+use crate::output::Output;
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Serialize;
+use tabled::Tabled;
+
+/// Command structure for the whoami command
+#[derive(Args)]
+pub struct WhoamiCommand {}
+
+/// Identity of the active API key, as shown by `whoami`
+#[derive(Debug, Serialize, Tabled)]
+pub struct Identity {
+    /// Where the API key comes from
+    pub source: ConfigSource,
+    /// Masked API key
+    pub api_key: String,
+    /// Base URL of the API
+    pub base_url: String,
+    /// Permission of the key
+    pub access: KeyAccess,
+}
+
+impl WhoamiCommand {
+    /// Executes the whoami command
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The API client used to check the API key
+    /// * `config` - Loaded configuration
+    /// * `source` - Where the API key of the configuration came from
+    /// * `output` - Renderer for the command result
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) if the key works, or an error if it is invalid or the API could
+    /// not be reached
+    pub async fn execute<T: ResendApi + Send + Sync>(
+        self,
+        client: T,
+        config: &Config,
+        source: &ConfigSource,
+        output: &Output,
+    ) -> Result<()> {
+        let access = check_access(&client).await?;
+        output.item(&Identity {
+            source: source.clone(),
+            api_key: mask_key(&config.api_key),
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            access,
+        })?;
+        match access {
+            KeyAccess::Invalid => bail!("The API key from {} is invalid", source),
+            KeyAccess::SendingAccess => {
+                eprintln!(
+                    "This key can only send emails. It cannot run: {}",
+                    SENDING_ACCESS_UNAVAILABLE.join(", ")
+                );
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::api_keys::ListApiKeysResponse;
+    use crate::api::MockResendApi;
+
+    fn restricted() -> anyhow::Error {
+        crate::api::ResendError::from_response(
+            401,
+            r#"{"statusCode":401,"name":"restricted_api_key","message":"This API key is restricted to only send emails"}"#,
+        )
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_check_access() {
+        let mut full = MockResendApi::new();
+        full.expect_list_api_keys()
+            .withf(|pagination| pagination.limit == Some(1))
+            .returning(|_| {
+                Ok(ListApiKeysResponse {
+                    data: vec![],
+                    has_more: false,
+                })
+            });
+        assert_eq!(check_access(&full).await.unwrap(), KeyAccess::FullAccess);
+
+        let mut sending = MockResendApi::new();
+        sending
+            .expect_list_api_keys()
+            .returning(|_| Err(restricted()));
+        assert_eq!(
+            check_access(&sending).await.unwrap(),
+            KeyAccess::SendingAccess
+        );
+
+        let mut invalid = MockResendApi::new();
+        invalid.expect_list_api_keys().returning(|_| {
+            Err(crate::api::ResendError::from_response(
+                403,
+                r#"{"statusCode":403,"name":"invalid_api_key","message":"API key is invalid"}"#,
+            )
+            .into())
+        });
+        assert_eq!(check_access(&invalid).await.unwrap(), KeyAccess::Invalid);
+
+        let mut down = MockResendApi::new();
+        down.expect_list_api_keys()
+            .returning(|_| Err(crate::api::ResendError::from_response(503, "").into()));
+        assert!(check_access(&down).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_whoami_fails_for_invalid_key() {
+        let mut mock = MockResendApi::new();
+        mock.expect_list_api_keys()
+            .returning(|_| Err(crate::api::ResendError::from_response(401, "").into()));
+        let config = Config {
+            api_key: "re_123456789abcd".to_string(),
+            ..Default::default()
+        };
+        let err = WhoamiCommand {}
+            .execute(mock, &config, &ConfigSource::EnvVar, &Output::default())
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("RESEND_API_KEY environment variable is invalid"));
+    }
+
+    #[tokio::test]
+    async fn test_whoami_reports_sending_access() {
+        let mut mock = MockResendApi::new();
+        mock.expect_list_api_keys().returning(|_| Err(restricted()));
+        let config = Config {
+            api_key: "re_123456789abcd".to_string(),
+            ..Default::default()
+        };
+        assert!(WhoamiCommand {}
+            .execute(mock, &config, &ConfigSource::EnvVar, &Output::default())
+            .await
+            .is_ok());
+    }
+}
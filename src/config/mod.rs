@@ -1,86 +1,526 @@
 //! # Configuration Module
 //!
 //! This module handles configuration management for the Resend CLI.
-//! It supports loading configuration from environment variables or a configuration file,
-//! and provides methods for saving configuration to disk.
+//! It supports loading configuration from environment variables or a named profile
+//! of the configuration file, and provides methods for saving configuration to disk.
 
-use anyhow::{Context, Result};
+use crate::api::rate_limit::MIN_REQUESTS_PER_SECOND;
+use crate::api::DEFAULT_BASE_URL;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Named profiles stored in the configuration file
+pub mod profiles;
+/// Project-level defaults read from `.resend.toml`
+pub mod project;
+/// API keys stored in the OS keyring or fetched by a secret command
+pub mod secrets;
+
+pub use profiles::{ConfigFile, DEFAULT_PROFILE};
+pub use project::{ProjectConfig, PROJECT_FILE};
 
 /// Configuration struct containing API credentials and settings
 ///
 /// This struct holds the configuration for the Resend CLI, primarily the API key
 /// used for authenticating with the Resend API. The configuration can be loaded
 /// from environment variables or a configuration file.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct Config {
     /// API key for authenticating with the Resend API
+    ///
+    /// Left out of the configuration file when the key is stored in the OS
+    /// keyring or fetched by `api_key_command`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub api_key: String,
+    /// Shell command printing the API key, e.g. `pass show resend/api-key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    /// Whether the API key is stored in the OS keyring rather than in the file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+    /// Base URL of the Resend API (defaults to https://api.resend.com)
+    ///
+    /// Useful for pointing the CLI at a local mock server, a recording proxy
+    /// or a staging gateway.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Maximum number of attempts per request, including the first one
+    ///
+    /// Rate-limited and failed requests are retried with backoff up to this
+    /// many times. Defaults to 3; set to 1 to disable retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    /// Client-side request quota, in requests per second
+    ///
+    /// Defaults to Resend's standard quota of 2 requests per second; set to 0 to
+    /// disable client-side rate limiting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<f64>,
+    /// Timeout for a whole request, in seconds
+    ///
+    /// Defaults to 30 seconds; set to 0 to wait indefinitely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+    /// Timeout for establishing a connection, in seconds
+    ///
+    /// Defaults to 10 seconds; set to 0 to wait indefinitely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<f64>,
+    /// URL of a proxy to send every request through, e.g. http://proxy.corp:3128
+    ///
+    /// When unset, the standard HTTPS_PROXY, HTTP_PROXY and NO_PROXY environment
+    /// variables are honoured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Path to a PEM file of additional CA certificates to trust
+    ///
+    /// Needed behind TLS-intercepting corporate proxies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    /// Default sender address for emails and broadcasts, e.g. "Acme <hi@acme.com>"
+    ///
+    /// Used when `--from` is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Default reply-to addresses for emails and broadcasts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<Vec<String>>,
+    /// Default segment for broadcasts and contact segment commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<String>,
+    /// Default domain for `domains get` and `domains verify`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_id: Option<String>,
+}
+
+impl From<ProjectConfig> for Config {
+    fn from(project: ProjectConfig) -> Self {
+        Config {
+            from: project.from,
+            reply_to: project.reply_to,
+            segment_id: project.segment_id,
+            domain_id: project.domain_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// Where the API key of a loaded configuration came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// The RESEND_API_KEY environment variable
+    EnvVar,
+    /// RESEND_API_KEY set by a `.env` file
+    DotEnv {
+        /// Path of the `.env` file
+        path: PathBuf,
+    },
+    /// The file named by the RESEND_API_KEY_FILE environment variable
+    KeyFile {
+        /// Path of the key file
+        path: PathBuf,
+    },
+    /// A profile of the configuration file
+    Profile {
+        /// Name of the profile
+        name: String,
+        /// Path of the configuration file
+        path: PathBuf,
+        /// Where the profile keeps its API key
+        key_storage: KeyStorage,
+    },
+    /// No API key was found, which dry runs and replayed cassettes tolerate
+    None,
+}
+
+/// Where a profile keeps its API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStorage {
+    /// In plaintext, in the configuration file
+    File,
+    /// In the OS keyring
+    Keyring,
+    /// Fetched by the profile's `api_key_command`
+    Command,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::EnvVar => write!(f, "RESEND_API_KEY environment variable"),
+            ConfigSource::DotEnv { path } => write!(f, ".env file {}", path.display()),
+            ConfigSource::KeyFile { path } => write!(f, "RESEND_API_KEY_FILE {}", path.display()),
+            ConfigSource::Profile {
+                name,
+                path,
+                key_storage,
+            } => {
+                let storage = match key_storage {
+                    KeyStorage::File => "key stored in the file",
+                    KeyStorage::Keyring => "key stored in the OS keyring",
+                    KeyStorage::Command => "key fetched by api_key_command",
+                };
+                write!(f, "profile '{}' of {} ({})", name, path.display(), storage)
+            }
+            ConfigSource::None => write!(f, "none"),
+        }
+    }
 }
 
 impl Config {
     /// Loads configuration from environment variables or configuration file
     ///
+    /// Equivalent to [`Config::load_profile`] with the profile named by the
+    /// RESEND_PROFILE environment variable, if set.
+    pub fn load() -> Result<Self> {
+        let profile = std::env::var("RESEND_PROFILE").ok();
+        Self::load_profile(profile.as_deref())
+    }
+
+    /// Loads configuration from environment variables or a profile of the
+    /// configuration file
+    ///
     /// This method attempts to load the configuration in the following order:
-    /// 1. From the RESEND_API_KEY environment variable
-    /// 2. From the configuration file at ~/.resend-cli/config.json
+    /// 1. From the given profile of the configuration file, if one is selected
+    /// 2. From the RESEND_API_KEY environment variable
+    /// 3. From the file named by the RESEND_API_KEY_FILE environment variable
+    /// 4. From the current profile of the configuration file at ~/.resend-cli/config.json
+    ///
+    /// The API key of a profile is fetched from its `api_key_command` or the OS
+    /// keyring when it is not stored in the file. Defaults from the nearest
+    /// `.resend.toml` take precedence over those of the profile.
+    ///
+    /// The RESEND_BASE_URL, RESEND_MAX_ATTEMPTS, RESEND_RATE_LIMIT, RESEND_TIMEOUT,
+    /// RESEND_CONNECT_TIMEOUT, RESEND_PROXY and RESEND_CA_CERT environment variables,
+    /// when set, override the corresponding settings from any source.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile explicitly selected with `--profile` or RESEND_PROFILE
     ///
     /// # Returns
     ///
-    /// A Config instance with the loaded configuration, or an error if neither
-    /// the environment variable nor the config file could be found
-    pub fn load() -> Result<Self> {
+    /// A Config instance with the loaded configuration, or an error if the
+    /// selected profile does not exist, or if neither the environment variable
+    /// nor the config file could be found
+    pub fn load_profile(profile: Option<&str>) -> Result<Self> {
+        Self::load_profile_with_source(profile).map(|(config, _)| config)
+    }
+
+    /// Loads configuration like [`Config::load_profile`], also telling where
+    /// its API key came from
+    pub fn load_profile_with_source(profile: Option<&str>) -> Result<(Self, ConfigSource)> {
+        let in_environment = std::env::var_os("RESEND_API_KEY").is_some();
+        let dotenv = dotenv::dotenv().ok();
+
+        let overrides = Self::from_env_overrides()?;
+        let file = ConfigFile::load()?;
+
+        let (mut config, source) = if let Some(name) = profile {
+            Self::from_profile(&file, name)?
+        } else if let Ok(key) = std::env::var("RESEND_API_KEY") {
+            let source = match dotenv {
+                Some(path) if !in_environment => ConfigSource::DotEnv { path },
+                _ => ConfigSource::EnvVar,
+            };
+            let config = Config {
+                api_key: key,
+                ..Default::default()
+            };
+            (config, source)
+        } else if let Some(path) = std::env::var_os("RESEND_API_KEY_FILE") {
+            let path = PathBuf::from(path);
+            let config = Config {
+                api_key: secrets::read_key_file(&path)?,
+                ..Default::default()
+            };
+            (config, ConfigSource::KeyFile { path })
+        } else if !file.profiles.is_empty() {
+            // Fall back to the config file if env var not set
+            Self::from_profile(&file, file.current_name())?
+        } else {
+            anyhow::bail!("RESEND_API_KEY environment variable not set and config file not found. Use 'resend config --api-key <KEY>' to set it.")
+        };
+        config.merge(Self::project_defaults()?);
+        config.merge(overrides);
+        Ok((config, source))
+    }
+
+    /// Finds the profile and base URL a command uses, without fetching its API key
+    ///
+    /// Follows the order of [`Config::load_profile_with_source`], so that shell
+    /// completion, which must stay fast, reads the ID cache of the same account as
+    /// the commands listing resources.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile explicitly selected with `--profile` or RESEND_PROFILE
+    /// * `base_url` - Base URL given with `--base-url` or RESEND_BASE_URL
+    ///
+    /// # Returns
+    ///
+    /// The profile, `None` if the API key comes from the environment, and the base URL
+    pub fn locate(profile: Option<&str>, base_url: Option<&str>) -> (Option<String>, String) {
         dotenv::dotenv().ok();
+        let file = ConfigFile::load().unwrap_or_default();
+        let profile = match profile {
+            Some(name) => Some(name.to_string()),
+            None if std::env::var_os("RESEND_API_KEY").is_some()
+                || std::env::var_os("RESEND_API_KEY_FILE").is_some() =>
+            {
+                None
+            }
+            None => Some(file.current_name().to_string()),
+        };
+        let base_url = base_url
+            .map(str::to_string)
+            .or_else(|| std::env::var("RESEND_BASE_URL").ok())
+            .or_else(|| {
+                let name = profile.as_deref()?;
+                file.profiles.get(name)?.base_url.clone()
+            })
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        (profile, base_url)
+    }
 
-        let api_key = std::env::var("RESEND_API_KEY").ok();
+    /// Reads the defaults of the nearest `.resend.toml`, walking up from the
+    /// current directory
+    ///
+    /// # Returns
+    ///
+    /// A Config with an empty API key holding the project defaults, none if there
+    /// is no project file, or an error if the file cannot be parsed
+    pub fn project_defaults() -> Result<Self> {
+        Ok(ProjectConfig::discover_from_current_dir()?
+            .map(|(_, project)| project.into())
+            .unwrap_or_default())
+    }
 
-        if let Some(key) = api_key {
-            return Ok(Config { api_key: key });
+    /// Reads a profile of the configuration file and fetches its API key
+    fn from_profile(file: &ConfigFile, name: &str) -> Result<(Self, ConfigSource)> {
+        let config = file.profile(name)?.clone();
+        let key_storage = if config.api_key_command.is_some() {
+            KeyStorage::Command
+        } else if config.keyring {
+            KeyStorage::Keyring
+        } else {
+            KeyStorage::File
+        };
+        let source = ConfigSource::Profile {
+            name: name.to_string(),
+            path: ConfigFile::path()?,
+            key_storage,
+        };
+        Ok((config.resolve_api_key(name)?, source))
+    }
+
+    /// Fetches the API key of a profile that does not store it in the file
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Name of the profile, under which the key is kept in the keyring
+    ///
+    /// # Returns
+    ///
+    /// The configuration with its API key filled in, or an error if the secret
+    /// command or the keyring failed
+    pub fn resolve_api_key(mut self, profile: &str) -> Result<Self> {
+        if let Some(command) = &self.api_key_command {
+            self.api_key = secrets::run_command(command)?;
+        } else if self.keyring {
+            self.api_key = secrets::keyring_get(profile)?;
         }
+        Ok(self)
+    }
 
-        // Try loading from config file if env var not set
-        let config_path = Self::config_path()?;
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = serde_json::from_str(&content)?;
-            return Ok(config);
+    /// Overrides settings with those set in another configuration
+    ///
+    /// Every optional setting of `other` that is `Some` replaces the current
+    /// value. The API key and where it is stored are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Configuration holding the overriding settings
+    pub fn merge(&mut self, other: Config) {
+        if other.base_url.is_some() {
+            self.base_url = other.base_url;
+        }
+        if other.max_attempts.is_some() {
+            self.max_attempts = other.max_attempts;
+        }
+        if other.requests_per_second.is_some() {
+            self.requests_per_second = other.requests_per_second;
         }
+        if other.timeout.is_some() {
+            self.timeout = other.timeout;
+        }
+        if other.connect_timeout.is_some() {
+            self.connect_timeout = other.connect_timeout;
+        }
+        if other.proxy.is_some() {
+            self.proxy = other.proxy;
+        }
+        if other.ca_cert.is_some() {
+            self.ca_cert = other.ca_cert;
+        }
+        if other.from.is_some() {
+            self.from = other.from;
+        }
+        if other.reply_to.is_some() {
+            self.reply_to = other.reply_to;
+        }
+        if other.segment_id.is_some() {
+            self.segment_id = other.segment_id;
+        }
+        if other.domain_id.is_some() {
+            self.domain_id = other.domain_id;
+        }
+    }
 
-        anyhow::bail!("RESEND_API_KEY environment variable not set and config file not found. Use 'resend config --api-key <KEY>' to set it.")
+    /// Reads the optional settings overridden by environment variables
+    ///
+    /// # Returns
+    ///
+    /// A Config with an empty API key holding the settings found in the
+    /// environment, or an error if a numeric variable could not be parsed
+    fn from_env_overrides() -> Result<Self> {
+        Ok(Config {
+            api_key: String::new(),
+            api_key_command: None,
+            keyring: false,
+            base_url: std::env::var("RESEND_BASE_URL").ok(),
+            max_attempts: parse_env_var(
+                "RESEND_MAX_ATTEMPTS",
+                "RESEND_MAX_ATTEMPTS must be a positive integer",
+                |value| Ok(value.parse()?),
+            )?,
+            requests_per_second: parse_env_var(
+                "RESEND_RATE_LIMIT",
+                "RESEND_RATE_LIMIT must be a number of requests per second",
+                parse_requests_per_second,
+            )?,
+            timeout: parse_env_var(
+                "RESEND_TIMEOUT",
+                "RESEND_TIMEOUT must be a number of seconds",
+                parse_seconds,
+            )?,
+            connect_timeout: parse_env_var(
+                "RESEND_CONNECT_TIMEOUT",
+                "RESEND_CONNECT_TIMEOUT must be a number of seconds",
+                parse_seconds,
+            )?,
+            proxy: std::env::var("RESEND_PROXY").ok(),
+            ca_cert: std::env::var_os("RESEND_CA_CERT").map(PathBuf::from),
+            from: None,
+            reply_to: None,
+            segment_id: None,
+            domain_id: None,
+        })
     }
 
-    /// Saves the current configuration to the configuration file
+    /// Saves the current configuration as the current profile
     ///
     /// This method writes the current configuration to the configuration file
     /// at ~/.resend-cli/config.json, creating the directory structure if needed.
+    /// Other profiles are kept.
     ///
     /// # Returns
     ///
     /// Ok(()) if the configuration was saved successfully, or an error if the
     /// file could not be written
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
-        if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(config_path, content)?;
-        Ok(())
+        let name = ConfigFile::load()?.current_name().to_string();
+        self.save_profile(&name)
     }
 
-    /// Gets the default configuration file path
+    /// Saves the current configuration as a named profile
     ///
-    /// This method returns the path to the configuration file at
-    /// ~/.resend-cli/config.json
+    /// The profile becomes the current one if the current profile does not
+    /// exist yet, e.g. when it is the first one saved.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The path to the configuration file, or an error if the home directory
-    /// could not be determined
-    fn config_path() -> Result<PathBuf> {
-        let home = dirs::home_dir().context("Could not find home directory")?;
-        Ok(home.join(".resend-cli").join("config.json"))
+    /// * `name` - Name of the profile to create or replace
+    pub fn save_profile(&self, name: &str) -> Result<()> {
+        let mut file = ConfigFile::load()?;
+        file.profiles.insert(name.to_string(), self.clone());
+        if !file.profiles.contains_key(file.current_name()) {
+            file.current_profile = Some(name.to_string());
+        }
+        file.save()
+    }
+}
+
+/// Fills in an argument left unset on the command line with its configured default
+///
+/// # Arguments
+///
+/// * `value` - Value given on the command line, if any
+/// * `default` - Default value from the configuration
+pub fn fill_default<T: Clone>(value: &mut Option<T>, default: &Option<T>) {
+    if value.is_none() {
+        value.clone_from(default);
+    }
+}
+
+/// Parses a number of seconds, such as a timeout
+///
+/// # Returns
+///
+/// The number of seconds, or an error if it is negative, not finite or too large
+/// to be a duration
+pub fn parse_seconds(value: &str) -> Result<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(seconds) if Duration::try_from_secs_f64(seconds).is_ok() => Ok(seconds),
+        _ => bail!("expected a non-negative number of seconds, got '{}'", value),
+    }
+}
+
+/// Checks a client-side request quota, in requests per second
+///
+/// # Returns
+///
+/// The rate, or an error unless it is 0 (no limit) or a finite number of at
+/// least [`MIN_REQUESTS_PER_SECOND`]
+pub fn check_requests_per_second(rate: f64) -> Result<f64> {
+    if rate == 0.0 || (rate.is_finite() && rate >= MIN_REQUESTS_PER_SECOND) {
+        return Ok(rate);
+    }
+    bail!(
+        "expected 0 or a number of requests per second of at least {}, got {:?}",
+        MIN_REQUESTS_PER_SECOND,
+        rate
+    )
+}
+
+/// Parses a client-side request quota, checked with [`check_requests_per_second`]
+pub fn parse_requests_per_second(value: &str) -> Result<f64> {
+    let rate = value
+        .trim()
+        .parse::<f64>()
+        .with_context(|| format!("expected a number of requests per second, got '{}'", value))?;
+    check_requests_per_second(rate)
+}
+
+/// Parses an environment variable, if set
+///
+/// # Arguments
+///
+/// * `name` - Name of the environment variable
+/// * `message` - Error message used when the value cannot be parsed
+/// * `parse` - Parser of the trimmed value
+fn parse_env_var<T>(
+    name: &str,
+    message: &'static str,
+    parse: fn(&str) -> Result<T>,
+) -> Result<Option<T>> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(parse(value.trim()).context(message)?)),
+        Err(_) => Ok(None),
     }
 }
 
@@ -89,10 +529,16 @@ mod tests {
     use super::*;
     use std::env;
     use std::fs;
+    use std::sync::Mutex;
     use tempfile::TempDir;
 
+    /// Serializes tests that mutate process-wide environment variables
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
     #[test]
     fn test_config_load_from_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
         // Set up environment variable
         env::set_var("RESEND_API_KEY", "test_api_key_from_env");
 
@@ -103,8 +549,131 @@ mod tests {
         env::remove_var("RESEND_API_KEY");
     }
 
+    #[test]
+    fn test_config_load_base_url_from_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        env::set_var("RESEND_API_KEY", "test_api_key_from_env");
+        env::set_var("RESEND_BASE_URL", "http://127.0.0.1:4010");
+
+        let config = Config::load().unwrap();
+        assert_eq!(config.base_url.as_deref(), Some("http://127.0.0.1:4010"));
+
+        env::remove_var("RESEND_API_KEY");
+        env::remove_var("RESEND_BASE_URL");
+    }
+
+    #[test]
+    fn test_config_load_http_settings_from_env_vars() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        env::set_var("RESEND_API_KEY", "test_api_key_from_env");
+        env::set_var("RESEND_TIMEOUT", "12.5");
+        env::set_var("RESEND_CONNECT_TIMEOUT", "3");
+        env::set_var("RESEND_PROXY", "http://proxy.corp:3128");
+        env::set_var("RESEND_CA_CERT", "/etc/ssl/corp.pem");
+
+        let config = Config::load().unwrap();
+        assert_eq!(config.timeout, Some(12.5));
+        assert_eq!(config.connect_timeout, Some(3.0));
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(config.ca_cert, Some(PathBuf::from("/etc/ssl/corp.pem")));
+
+        for invalid in ["soon", "-1", "inf", "NaN"] {
+            env::set_var("RESEND_TIMEOUT", invalid);
+            assert!(Config::load().is_err(), "{}", invalid);
+        }
+
+        for name in [
+            "RESEND_API_KEY",
+            "RESEND_TIMEOUT",
+            "RESEND_CONNECT_TIMEOUT",
+            "RESEND_PROXY",
+            "RESEND_CA_CERT",
+        ] {
+            env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("12.5").unwrap(), 12.5);
+        assert_eq!(parse_seconds(" 0 ").unwrap(), 0.0);
+        for invalid in ["-1", "inf", "-inf", "NaN", "1e30", "soon"] {
+            let err = parse_seconds(invalid).unwrap_err().to_string();
+            assert!(err.contains("non-negative number of seconds"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_requests_per_second() {
+        assert_eq!(parse_requests_per_second("2").unwrap(), 2.0);
+        assert_eq!(parse_requests_per_second("0").unwrap(), 0.0);
+        assert_eq!(parse_requests_per_second("0.01").unwrap(), 0.01);
+        for invalid in ["-1", "1e-300", "inf", "NaN", "fast"] {
+            let err = parse_requests_per_second(invalid).unwrap_err().to_string();
+            assert!(err.contains("requests per second"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_config_merge_keeps_unset_settings() {
+        let mut config = Config {
+            api_key: "file_key".to_string(),
+            base_url: Some("http://file".to_string()),
+            timeout: Some(5.0),
+            ..Default::default()
+        };
+        config.merge(Config {
+            timeout: Some(60.0),
+            proxy: Some("http://proxy".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(config.api_key, "file_key");
+        assert_eq!(config.base_url.as_deref(), Some("http://file"));
+        assert_eq!(config.timeout, Some(60.0));
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_api_key_from_command() {
+        let config = Config {
+            api_key_command: Some("echo re_from_command".to_string()),
+            ..Default::default()
+        }
+        .resolve_api_key(DEFAULT_PROFILE)
+        .unwrap();
+        assert_eq!(config.api_key, "re_from_command");
+
+        // A stored key is used as is
+        let config = Config {
+            api_key: "re_stored".to_string(),
+            ..Default::default()
+        }
+        .resolve_api_key(DEFAULT_PROFILE)
+        .unwrap();
+        assert_eq!(config.api_key, "re_stored");
+
+        let failing = Config {
+            api_key_command: Some("exit 1".to_string()),
+            ..Default::default()
+        };
+        assert!(failing.resolve_api_key(DEFAULT_PROFILE).is_err());
+    }
+
+    #[test]
+    fn test_config_without_base_url_deserializes() {
+        let config: Config = serde_json::from_str(r#"{"api_key": "legacy_key"}"#).unwrap();
+        assert_eq!(config.api_key, "legacy_key");
+        assert!(config.base_url.is_none());
+    }
+
     #[test]
     fn test_config_save_and_load_from_file() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
         // Create a temporary directory
         let temp_dir = TempDir::new().unwrap();
         let config_path = temp_dir.path().join(".resend-cli").join("config.json");
@@ -112,6 +681,7 @@ mod tests {
         // Create a config and save it to the temp location
         let config = Config {
             api_key: "test_api_key_from_file".to_string(),
+            ..Default::default()
         };
 
         // Override the config_path function to use our temp directory
@@ -144,6 +714,7 @@ mod tests {
 
         let config = Config {
             api_key: "test_api_key_for_saving".to_string(),
+            ..Default::default()
         };
 
         // Override the config_path function temporarily by creating the file directly
@@ -161,6 +732,7 @@ mod tests {
     fn test_config_struct_creation() {
         let config = Config {
             api_key: "test_key".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(config.api_key, "test_key");
@@ -0,0 +1,251 @@
+//! # Profiles Module
+//!
+//! This module manages the configuration file, which holds one `Config` per named
+//! profile and remembers which profile is current:
+//!
+//! ```json
+//! {
+//!   "current_profile": "staging",
+//!   "profiles": {
+//!     "default": { "api_key": "re_live_..." },
+//!     "staging": { "api_key": "re_test_...", "base_url": "https://staging.example.com" }
+//!   }
+//! }
+//! ```
+//!
+//! Files written by earlier versions, holding a single configuration, are read as
+//! a file whose only profile is `default`.
+
+use super::Config;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the profile used when none is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Contents of the configuration file
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+    /// Profile used when none is selected with `--profile` or RESEND_PROFILE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>,
+    /// Settings of each profile, by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Config>,
+}
+
+impl ConfigFile {
+    /// Gets the path of the configuration file, ~/.resend-cli/config.json
+    ///
+    /// # Returns
+    ///
+    /// The path to the configuration file, or an error if the home directory
+    /// could not be determined
+    pub fn path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not find home directory")?;
+        Ok(home.join(".resend-cli").join("config.json"))
+    }
+
+    /// Loads the configuration file
+    ///
+    /// # Returns
+    ///
+    /// The profiles read from the file, none if the file does not exist, or an
+    /// error if it cannot be read or parsed
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    /// Loads a configuration file from a specific path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the configuration file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Parses the contents of a configuration file, in either format
+    pub fn parse(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)?;
+        if value.get("profiles").is_some() {
+            return Ok(serde_json::from_value(value)?);
+        }
+        // Single configuration written before profiles existed
+        let config: Config = serde_json::from_value(value)?;
+        Ok(Self {
+            current_profile: None,
+            profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), config)]),
+        })
+    }
+
+    /// Saves the configuration file, creating its directory if needed
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    /// Saves the configuration file to a specific path
+    ///
+    /// On Unix, the file is readable and writable by its owner only (0600), since
+    /// it may hold API keys in plaintext.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the configuration file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        write_private(path, content.as_bytes())
+            .with_context(|| format!("Could not write {}", path.display()))
+    }
+
+    /// Returns the name of the current profile
+    pub fn current_name(&self) -> &str {
+        self.current_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Returns the settings of a profile
+    ///
+    /// # Returns
+    ///
+    /// The profile, or an error listing the available profiles if it does not exist
+    pub fn profile(&self, name: &str) -> Result<&Config> {
+        self.profiles.get(name).ok_or_else(|| {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            anyhow!(
+                "Profile '{}' not found (available profiles: {}). Use 'resend config --profile {} --api-key <KEY>' to create it.",
+                name,
+                if available.is_empty() { "none".to_string() } else { available.join(", ") },
+                name
+            )
+        })
+    }
+
+    /// Makes a profile the current one
+    ///
+    /// # Returns
+    ///
+    /// An error if the profile does not exist
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        self.profile(name)?;
+        self.current_profile = Some(name.to_string());
+        Ok(())
+    }
+}
+
+/// Writes a file only its owner may read, tightening the permissions of an
+/// existing file written by earlier versions
+#[cfg(unix)]
+fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(content)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, content)
+}
+
+/// Masks an API key for display, keeping its prefix and last four characters
+///
+/// # Arguments
+///
+/// * `key` - API key to mask
+pub fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let prefix: String = chars.iter().take(3).collect();
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", prefix, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_legacy_file_becomes_default_profile() {
+        let file =
+            ConfigFile::parse(r#"{"api_key": "re_legacy", "base_url": "http://x"}"#).unwrap();
+        assert_eq!(file.current_name(), DEFAULT_PROFILE);
+        let profile = file.profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(profile.api_key, "re_legacy");
+        assert_eq!(profile.base_url.as_deref(), Some("http://x"));
+    }
+
+    #[test]
+    fn test_profiles_round_trip_and_selection() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("config.json");
+
+        let mut file = ConfigFile::default();
+        file.profiles.insert(
+            "staging".to_string(),
+            Config {
+                api_key: "re_staging".to_string(),
+                from: Some("Staging <dev@acme.com>".to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(file.use_profile("production").is_err());
+        file.use_profile("staging").unwrap();
+        file.save_to(&path).unwrap();
+
+        let loaded = ConfigFile::load_from(&path).unwrap();
+        assert_eq!(loaded, file);
+        assert_eq!(loaded.current_name(), "staging");
+
+        let err = loaded.profile("production").unwrap_err().to_string();
+        assert!(err.contains("available profiles: staging"));
+        assert_eq!(
+            ConfigFile::load_from(&dir.path().join("missing.json")).unwrap(),
+            ConfigFile::default()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"api_key": "re_legacy"}"#).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        ConfigFile::load_from(&path)
+            .unwrap()
+            .save_to(&path)
+            .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key("re_123456789abcd"), "re_...abcd");
+        assert_eq!(mask_key("short"), "*****");
+    }
+}
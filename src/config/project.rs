@@ -0,0 +1,223 @@
+//! # Project Module
+//!
+//! This module reads the project-level `.resend.toml`, found by walking up from the
+//! current directory. It supplies default values for command arguments, so that a
+//! repository can commit its sender, segment and domain once:
+//!
+//! ```toml
+//! from = "Acme <hi@acme.com>"
+//! reply_to = ["support@acme.com"]
+//! segment_id = "78261eea-8f8b-4381-83c6-79fa7120f1cf"
+//! domain_id = "4dd369bc-aa82-4ff3-97de-514ae3000ee0"
+//! ```
+//!
+//! Flags given on the command line take precedence over these defaults. API keys
+//! are deliberately not accepted, since the file is meant to be committed.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file
+pub const PROJECT_FILE: &str = ".resend.toml";
+
+/// Defaults read from a `.resend.toml` file
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Default sender for `emails send`, `emails draft` and `broadcasts create`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Default reply-to addresses for emails and broadcasts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<Vec<String>>,
+    /// Default segment for `broadcasts create` and contact segment commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<String>,
+    /// Default domain for `domains get` and `domains verify`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_id: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Finds the nearest `.resend.toml` in a directory or its ancestors
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Directory to start the search from, usually the current one
+    ///
+    /// # Returns
+    ///
+    /// The path of the file and its defaults, `None` if there is no such file, or
+    /// an error if the file cannot be read or parsed
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        for dir in start.ancestors() {
+            let path = dir.join(PROJECT_FILE);
+            if path.is_file() {
+                let project = Self::load_from(&path)?;
+                return Ok(Some((path, project)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the nearest `.resend.toml` from the current directory
+    ///
+    /// # Returns
+    ///
+    /// The path of the file and its defaults, or `None` if there is no such file
+    pub fn discover_from_current_dir() -> Result<Option<(PathBuf, Self)>> {
+        match std::env::current_dir() {
+            Ok(dir) => Self::discover(&dir),
+            // A deleted working directory has no project file
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Reads a project configuration file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the `.resend.toml` file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid project file {}", path.display()))
+    }
+
+    /// Renders the file written by `resend config init`
+    ///
+    /// Settings left unset are written as commented-out examples.
+    pub fn render(&self) -> String {
+        let mut content = String::from(
+            "# Project defaults for the Resend CLI, found by walking up from the current\n\
+             # directory. Flags given on the command line take precedence.\n\
+             # Do not put API keys here: this file is meant to be committed.\n",
+        );
+        let string = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let entries = [
+            (
+                "Default sender for `emails send`, `emails draft` and `broadcasts create`",
+                "from",
+                self.from.as_deref().map(string),
+                string("Acme <hi@acme.com>"),
+            ),
+            (
+                "Default reply-to addresses",
+                "reply_to",
+                self.reply_to.as_ref().map(|addresses| {
+                    toml::Value::Array(addresses.iter().map(|a| a.as_str().into()).collect())
+                        .to_string()
+                }),
+                r#"["support@acme.com"]"#.to_string(),
+            ),
+            (
+                "Default segment for `broadcasts create` and contact segment commands",
+                "segment_id",
+                self.segment_id.as_deref().map(string),
+                string("<segment-id>"),
+            ),
+            (
+                "Default domain for `domains get` and `domains verify`",
+                "domain_id",
+                self.domain_id.as_deref().map(string),
+                string("<domain-id>"),
+            ),
+        ];
+        for (comment, key, value, example) in entries {
+            content.push_str(&format!("\n# {}\n", comment));
+            match value {
+                Some(value) => content.push_str(&format!("{} = {}\n", key, value)),
+                None => content.push_str(&format!("# {} = {}\n", key, example)),
+            }
+        }
+        content
+    }
+
+    /// Writes a new `.resend.toml` in a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to create the file in
+    /// * `force` - Whether to replace an existing file
+    ///
+    /// # Returns
+    ///
+    /// The path of the created file, or an error if it already exists and
+    /// `force` is not set
+    pub fn init(&self, dir: &Path, force: bool) -> Result<PathBuf> {
+        let path = dir.join(PROJECT_FILE);
+        if path.exists() && !force {
+            bail!(
+                "{} already exists. Pass --force to replace it.",
+                path.display()
+            );
+        }
+        std::fs::write(&path, self.render())
+            .with_context(|| format!("Could not write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_walks_up_to_the_nearest_file() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("app").join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(ProjectConfig::discover(&nested).unwrap().is_none());
+
+        std::fs::write(
+            dir.path().join(PROJECT_FILE),
+            "from = \"Acme <hi@acme.com>\"\nreply_to = [\"support@acme.com\"]\n",
+        )
+        .unwrap();
+        let (path, project) = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(path, dir.path().join(PROJECT_FILE));
+        assert_eq!(project.from.as_deref(), Some("Acme <hi@acme.com>"));
+        assert_eq!(project.reply_to, Some(vec!["support@acme.com".to_string()]));
+
+        std::fs::write(
+            dir.path().join("app").join(PROJECT_FILE),
+            "segment_id = \"seg_1\"\n",
+        )
+        .unwrap();
+        let (_, project) = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(project.segment_id.as_deref(), Some("seg_1"));
+        assert!(project.from.is_none());
+    }
+
+    #[test]
+    fn test_api_keys_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(PROJECT_FILE), "api_key = \"re_secret\"\n").unwrap();
+        let err = ProjectConfig::discover(dir.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown field `api_key`"));
+    }
+
+    #[test]
+    fn test_init_renders_a_loadable_file() {
+        let dir = TempDir::new().unwrap();
+        let project = ProjectConfig {
+            from: Some("Acme <hi@acme.com>".to_string()),
+            reply_to: Some(vec!["support@acme.com".to_string()]),
+            ..Default::default()
+        };
+        let path = project.init(dir.path(), false).unwrap();
+        assert_eq!(ProjectConfig::load_from(&path).unwrap(), project);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("# segment_id = \"<segment-id>\""));
+
+        assert!(ProjectConfig::default().init(dir.path(), false).is_err());
+        ProjectConfig::default().init(dir.path(), true).unwrap();
+        assert_eq!(
+            ProjectConfig::load_from(&path).unwrap(),
+            ProjectConfig::default()
+        );
+    }
+}
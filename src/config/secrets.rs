@@ -0,0 +1,153 @@
+//! # Secrets Module
+//!
+//! This module fetches and stores API keys outside the configuration file: in the
+//! OS keyring (Secret Service on Linux, Keychain on macOS, Credential Manager on
+//! Windows), through an external secret command such as `pass`, `op` or `vault`,
+//! or in a file named by RESEND_API_KEY_FILE.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Service name under which API keys are stored in the OS keyring
+pub const KEYRING_SERVICE: &str = "resend-cli";
+
+/// Runs a secret command and returns the API key it prints
+///
+/// The command is run by the shell (`sh -c`, or `cmd /C` on Windows), and
+/// surrounding whitespace is trimmed from its output.
+///
+/// # Arguments
+///
+/// * `command` - Shell command printing the API key, e.g. `pass show resend/api-key`
+///
+/// # Returns
+///
+/// The API key, or an error if the command fails or prints nothing
+pub fn run_command(command: &str) -> Result<String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .with_context(|| format!("Could not run API key command '{}'", command))?;
+
+    if !output.status.success() {
+        bail!(
+            "API key command '{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let key = String::from_utf8(output.stdout)
+        .with_context(|| format!("API key command '{}' printed invalid UTF-8", command))?;
+    non_empty(key, || {
+        format!("API key command '{}' printed nothing", command)
+    })
+}
+
+/// Reads an API key from a file, such as a mounted Docker or Kubernetes secret
+///
+/// # Arguments
+///
+/// * `path` - Path of the file holding the API key
+pub fn read_key_file(path: &Path) -> Result<String> {
+    let key = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read API key file {}", path.display()))?;
+    non_empty(key, || format!("API key file {} is empty", path.display()))
+}
+
+/// Reads the API key of a profile from the OS keyring
+///
+/// # Arguments
+///
+/// * `profile` - Name of the profile the key belongs to
+pub fn keyring_get(profile: &str) -> Result<String> {
+    outside_runtime(|| keyring::Entry::new(KEYRING_SERVICE, profile)?.get_password()).with_context(
+        || {
+            format!(
+                "Could not read the API key of profile '{}' from the OS keyring",
+                profile
+            )
+        },
+    )
+}
+
+/// Stores the API key of a profile in the OS keyring, replacing any previous one
+///
+/// # Arguments
+///
+/// * `profile` - Name of the profile the key belongs to
+/// * `key` - API key to store
+pub fn keyring_set(profile: &str, key: &str) -> Result<()> {
+    outside_runtime(|| keyring::Entry::new(KEYRING_SERVICE, profile)?.set_password(key))
+        .with_context(|| {
+            format!(
+                "Could not store the API key of profile '{}' in the OS keyring",
+                profile
+            )
+        })
+}
+
+/// Runs a keyring operation on its own thread
+///
+/// The Secret Service backend drives D-Bus with a private Tokio runtime, which
+/// cannot be started from a thread already running the CLI's runtime.
+fn outside_runtime<T: Send>(
+    operation: impl FnOnce() -> keyring::Result<T> + Send,
+) -> keyring::Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(operation)
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Trims a key, failing with `message` if nothing is left
+fn non_empty(key: String, message: impl FnOnce() -> String) -> Result<String> {
+    let key = key.trim();
+    if key.is_empty() {
+        bail!(message());
+    }
+    Ok(key.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_command_trims_output() {
+        assert_eq!(
+            run_command("echo '  re_from_command  '").unwrap(),
+            "re_from_command"
+        );
+    }
+
+    #[test]
+    fn test_run_command_failures() {
+        let err = run_command("echo locked >&2; exit 3")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("failed"));
+        assert!(err.contains("locked"));
+
+        let err = run_command("true").unwrap_err().to_string();
+        assert!(err.contains("printed nothing"));
+    }
+
+    #[test]
+    fn test_read_key_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("api-key");
+        std::fs::write(&path, "re_from_file\n").unwrap();
+        assert_eq!(read_key_file(&path).unwrap(), "re_from_file");
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(read_key_file(&path).is_err());
+        assert!(read_key_file(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! # Resend
+//!
+//! Rust client for the Resend API, and the library behind the `resend` CLI.
+//!
+//! ## Modules
+//!
+//! - [`api`]: `ResendClient`, the `ResendApi` trait and every request/response type
+//! - [`config`]: Loading and saving of CLI configuration
+//! - [`output`]: Table formatting of API responses
+//! - [`commands`]: Subcommands of the `resend` binary
+//! - [`mock_server`]: In-memory local implementation of the Resend API
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use resend_cli::api::emails::SendEmailRequest;
+//! use resend_cli::api::{ResendApi, ResendClient};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = ResendClient::builder("re_123").build()?;
+//! let mut request = SendEmailRequest::new(
+//!     "hi@acme.com",
+//!     vec!["user@example.com".to_string()],
+//!     "Hello!",
+//! );
+//! request.html = Some("<h1>Welcome!</h1>".to_string());
+//! let sent = client.send_email(request, None).await?;
+//! println!("Sent {}", sent.id);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+pub mod commands;
+pub mod config;
+pub mod mock_server;
+pub mod output;
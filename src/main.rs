@@ -14,26 +14,29 @@
 //! - Send and receive broadcast messages
 //! - Manage contact properties
 
-mod api;
-mod commands;
-mod config;
-mod output;
-
-use crate::api::ResendClient;
-use crate::commands::api_keys::ApiKeysCommand;
-use crate::commands::broadcasts::BroadcastsCommand;
-use crate::commands::contact_properties::ContactPropertiesCommand;
-use crate::commands::contacts::ContactsCommand;
-use crate::commands::domains::DomainsCommand;
-use crate::commands::emails::EmailsCommand;
-use crate::commands::receiving::ReceivingCommand;
-use crate::commands::segments::SegmentsCommand;
-use crate::commands::templates::TemplatesCommand;
-use crate::commands::topics::TopicsCommand;
-use crate::commands::webhooks::WebhooksCommand;
-use crate::config::Config;
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use resend_cli::api::{
+    Cassette, CassetteMode, DryRunClient, ResendApi, ResendClient, Tracer, DEFAULT_BASE_URL,
+};
+use resend_cli::commands::api_keys::ApiKeysCommand;
+use resend_cli::commands::broadcasts::BroadcastsCommand;
+use resend_cli::commands::completions::{CompleteIdsCommand, CompletionsCommand, ManCommand};
+use resend_cli::commands::config::ConfigCommand;
+use resend_cli::commands::contact_properties::ContactPropertiesCommand;
+use resend_cli::commands::contacts::ContactsCommand;
+use resend_cli::commands::domains::DomainsCommand;
+use resend_cli::commands::emails::EmailsCommand;
+use resend_cli::commands::mock_server::MockServerCommand;
+use resend_cli::commands::receiving::ReceivingCommand;
+use resend_cli::commands::segments::SegmentsCommand;
+use resend_cli::commands::templates::TemplatesCommand;
+use resend_cli::commands::topics::TopicsCommand;
+use resend_cli::commands::webhooks::WebhooksCommand;
+use resend_cli::commands::whoami::WhoamiCommand;
+use resend_cli::config::{self, Config, ConfigSource};
+use resend_cli::output::{IdCache, Output, OutputFormat, Query};
+use std::path::PathBuf;
 
 /// Command-line interface parser for the Resend CLI
 ///
@@ -42,7 +45,104 @@ use clap::{Parser, Subcommand};
 #[derive(Parser)]
 #[command(name = "resend")]
 #[command(about = "Resend CLI - Manage your emails, domains, and more", long_about = None)]
+#[command(next_help_heading = "Global Options")]
 struct Cli {
+    /// Base URL of the Resend API, e.g. a local mock server or staging gateway
+    #[arg(long, global = true, env = "RESEND_BASE_URL")]
+    base_url: Option<String>,
+
+    /// Maximum number of attempts per request, including the first (1 disables retries)
+    #[arg(long, global = true, env = "RESEND_MAX_ATTEMPTS")]
+    max_attempts: Option<u32>,
+
+    /// Client-side request quota in requests per second (default 2, 0 disables)
+    #[arg(
+        long,
+        global = true,
+        env = "RESEND_RATE_LIMIT",
+        value_parser = config::parse_requests_per_second
+    )]
+    rate_limit: Option<f64>,
+
+    /// Timeout for a whole request in seconds (default 30, 0 disables)
+    #[arg(
+        long,
+        global = true,
+        env = "RESEND_TIMEOUT",
+        value_name = "SECONDS",
+        value_parser = config::parse_seconds
+    )]
+    timeout: Option<f64>,
+
+    /// Timeout for establishing a connection in seconds (default 10, 0 disables)
+    #[arg(
+        long,
+        global = true,
+        env = "RESEND_CONNECT_TIMEOUT",
+        value_name = "SECONDS",
+        value_parser = config::parse_seconds
+    )]
+    connect_timeout: Option<f64>,
+
+    /// Proxy URL to send every request through (defaults to HTTPS_PROXY/HTTP_PROXY)
+    #[arg(long, global = true, env = "RESEND_PROXY", value_name = "URL")]
+    proxy: Option<String>,
+
+    /// PEM file of additional CA certificates to trust
+    #[arg(long, global = true, env = "RESEND_CA_CERT", value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// Log every HTTP request and response to stderr, with credentials redacted
+    #[arg(long, visible_alias = "debug", global = true)]
+    verbose: bool,
+
+    /// Append a JSON line per HTTP request and response to this file
+    #[arg(long, global = true, env = "RESEND_TRACE_FILE", value_name = "PATH")]
+    trace_file: Option<PathBuf>,
+
+    /// Record HTTP exchanges to, or replay them from, this cassette file
+    #[arg(long, global = true, env = "RESEND_CASSETTE", value_name = "PATH")]
+    cassette: Option<PathBuf>,
+
+    /// Whether to record or replay the cassette
+    #[arg(
+        long,
+        global = true,
+        env = "RESEND_CASSETTE_MODE",
+        value_enum,
+        default_value = "replay",
+        requires = "cassette"
+    )]
+    cassette_mode: CassetteMode,
+
+    /// Format of the command output
+    #[arg(
+        long,
+        short = 'o',
+        global = true,
+        env = "RESEND_OUTPUT",
+        value_enum,
+        default_value = "table"
+    )]
+    output: OutputFormat,
+
+    /// Comma-separated columns to show in tables and CSV, in this order
+    #[arg(long, global = true, value_delimiter = ',', value_name = "COLUMNS")]
+    columns: Vec<String>,
+
+    /// JMESPath expression filtering and projecting the output, e.g. "[?status=='verified'].name"
+    /// (a subset of JMESPath: expression references and most functions are not supported)
+    #[arg(long, global = true, value_name = "EXPRESSION")]
+    query: Option<String>,
+
+    /// Print the requests that would be sent, without sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Profile of the configuration file to use (defaults to the current profile)
+    #[arg(long, global = true, env = "RESEND_PROFILE", value_name = "NAME")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -54,14 +154,12 @@ struct Cli {
 /// handling contacts, and more.
 #[derive(Subcommand)]
 enum Commands {
-    /// Configure the Resend CLI with an API key
-    Config {
-        /// API key for authenticating with the Resend API
-        #[arg(long)]
-        api_key: String,
-    },
+    /// Configure the Resend CLI - set the API key and defaults of a profile, list and switch profiles
+    Config(ConfigCommand),
+    /// Show which API key is active, where it comes from and what it can do
+    Whoami(WhoamiCommand),
     /// Manage emails - send, retrieve, list, cancel, and update emails
-    Emails(EmailsCommand),
+    Emails(Box<EmailsCommand>),
     /// Manage API keys - create, list, and delete API keys
     #[command(name = "api-keys")]
     ApiKeys(ApiKeysCommand),
@@ -84,40 +182,168 @@ enum Commands {
     ContactProperties(ContactPropertiesCommand),
     /// Manage received emails - list and retrieve received emails
     Receiving(ReceivingCommand),
+    /// Run a local in-memory implementation of the Resend API for offline testing
+    #[command(name = "mock-server")]
+    MockServer(MockServerCommand),
+    /// Print a shell completion script, completing IDs from recent list results
+    Completions(CompletionsCommand),
+    /// Print the man page, or write a page per command to a directory
+    Man(ManCommand),
+    /// Print cached resource IDs for the word after the given ones, for completion scripts
+    #[command(name = "__complete-ids", hide = true)]
+    CompleteIds(CompleteIdsCommand),
+}
+
+impl Commands {
+    /// Fills in settings left unset on the command line from the configuration,
+    /// such as the default sender of the profile or the project's segment
+    fn apply_defaults(&mut self, config: &Config) {
+        match self {
+            Commands::Emails(cmd) => cmd.apply_defaults(config),
+            Commands::Broadcasts(cmd) => cmd.apply_defaults(config),
+            Commands::Contacts(cmd) => cmd.apply_defaults(config),
+            Commands::Domains(cmd) => cmd.apply_defaults(config),
+            _ => {}
+        }
+    }
 }
 
 /// Main entry point for the Resend CLI application
 ///
+/// Runs the CLI and prints any error as a clean message on stderr, followed by
+/// its root cause when there is one, exiting with a non-zero status code.
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("Error: {}", err);
+        let root = err.root_cause().to_string();
+        if root != err.to_string() {
+            eprintln!("  Caused by: {}", root);
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Runs the Resend CLI
+///
 /// This function handles command-line argument parsing and routes the request
 /// to the appropriate command handler. It manages configuration loading,
 /// client initialization, and command execution.
-#[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
     let cli = Cli::parse();
+    let mut output = Output {
+        format: cli.output,
+        columns: cli.columns,
+        query: cli.query.as_deref().map(Query::parse).transpose()?,
+        id_cache: None,
+    };
+
+    let overrides = Config {
+        api_key: String::new(),
+        api_key_command: None,
+        keyring: false,
+        base_url: cli.base_url.clone(),
+        max_attempts: cli.max_attempts,
+        requests_per_second: cli.rate_limit,
+        timeout: cli.timeout,
+        connect_timeout: cli.connect_timeout,
+        proxy: cli.proxy,
+        ca_cert: cli.ca_cert,
+        from: None,
+        reply_to: None,
+        segment_id: None,
+        domain_id: None,
+    };
+
+    let mut command = match cli.command {
+        Commands::Config(cmd) if cmd.is_local() => {
+            return cmd.execute(cli.profile, overrides, &output)
+        }
+        Commands::MockServer(cmd) => return cmd.execute().await,
+        Commands::Completions(cmd) => return cmd.execute(Cli::command()),
+        Commands::Man(cmd) => return cmd.execute(Cli::command()),
+        Commands::CompleteIds(cmd) => {
+            return cmd.execute(Cli::command(), cli.profile, cli.base_url)
+        }
+        command => command,
+    };
+
+    let cassette = match &cli.cassette {
+        Some(path) if !cli.dry_run => Some(Cassette::open(path, cli.cassette_mode)?),
+        _ => None,
+    };
+    // Dry runs and replayed cassettes never reach the API, so no API key is
+    // needed unless a profile was explicitly selected
+    let offline = cli.dry_run || cassette.as_ref().is_some_and(|c| c.is_replay());
+    let (mut config, source) = match Config::load_profile_with_source(cli.profile.as_deref()) {
+        Err(_) if offline && cli.profile.is_none() => {
+            (Config::project_defaults()?, ConfigSource::None)
+        }
+        loaded => loaded?,
+    };
+    config.merge(overrides);
+    command.apply_defaults(&config);
 
-    if let Commands::Config { api_key } = cli.command {
-        let config = Config { api_key };
-        config.save()?;
-        println!("Configuration saved successfully!");
-        return Ok(());
+    // Synthetic dry-run and replayed results must not end up in completions
+    if !offline {
+        let profile = match &source {
+            ConfigSource::Profile { name, .. } => Some(name.as_str()),
+            _ => None,
+        };
+        let base_url = config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        output.id_cache = IdCache::default_location(profile, base_url);
     }
 
-    let config = Config::load()?;
-    let client = ResendClient::new(config);
-
-    match cli.command {
-        Commands::Emails(cmd) => cmd.execute(client).await?,
-        Commands::ApiKeys(cmd) => cmd.execute(client).await?,
-        Commands::Domains(cmd) => cmd.execute(client).await?,
-        Commands::Segments(cmd) => cmd.execute(client).await?,
-        Commands::Contacts(cmd) => cmd.execute(client).await?,
-        Commands::Templates(cmd) => cmd.execute(client).await?,
-        Commands::Topics(cmd) => cmd.execute(client).await?,
-        Commands::Webhooks(cmd) => cmd.execute(client).await?,
-        Commands::Broadcasts(cmd) => cmd.execute(client).await?,
-        Commands::ContactProperties(cmd) => cmd.execute(client).await?,
-        Commands::Receiving(cmd) => cmd.execute(client).await?,
-        Commands::Config { .. } => unreachable!(),
+    if cli.dry_run {
+        return run_command(command, DryRunClient::new(), &config, &source, &output).await;
+    }
+
+    let tracer = Tracer::new(cli.verbose, cli.trace_file.as_deref())?;
+    let mut builder = ResendClient::builder(config.api_key.clone())
+        .config(&config)?
+        .tracer(tracer);
+    if let Some(cassette) = cassette {
+        builder = builder.cassette(cassette);
+    }
+    let client = builder.build()?;
+
+    run_command(command, client, &config, &source, &output).await
+}
+
+/// Runs an API command against any `ResendApi` implementation
+///
+/// # Arguments
+///
+/// * `command` - Command to run, other than local commands such as `mock-server`
+/// * `client` - Client performing the API calls
+/// * `config` - Loaded configuration
+/// * `source` - Where the API key of the configuration came from
+/// * `output` - Renderer for the command result
+async fn run_command<T: ResendApi + Send + Sync>(
+    command: Commands,
+    client: T,
+    config: &Config,
+    source: &ConfigSource,
+    output: &Output,
+) -> Result<()> {
+    match command {
+        Commands::Emails(cmd) => cmd.execute(client, output).await?,
+        Commands::ApiKeys(cmd) => cmd.execute(client, output).await?,
+        Commands::Domains(cmd) => cmd.execute(client, output).await?,
+        Commands::Segments(cmd) => cmd.execute(client, output).await?,
+        Commands::Contacts(cmd) => cmd.execute(client, output).await?,
+        Commands::Templates(cmd) => cmd.execute(client, output).await?,
+        Commands::Topics(cmd) => cmd.execute(client, output).await?,
+        Commands::Webhooks(cmd) => cmd.execute(client, output).await?,
+        Commands::Broadcasts(cmd) => cmd.execute(client, output).await?,
+        Commands::ContactProperties(cmd) => cmd.execute(client, output).await?,
+        Commands::Receiving(cmd) => cmd.execute(client, output).await?,
+        Commands::Config(cmd) => cmd.inspect(client, config, source, output).await?,
+        Commands::Whoami(cmd) => cmd.execute(client, config, source, output).await?,
+        Commands::MockServer(_)
+        | Commands::Completions(_)
+        | Commands::Man(_)
+        | Commands::CompleteIds(_) => unreachable!(),
     }
 
     Ok(())
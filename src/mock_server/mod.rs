@@ -0,0 +1,411 @@
+//! # Mock Server Module
+//!
+//! This module implements a local, in-memory stand-in for the Resend API. It
+//! serves every endpoint used by `ResendClient`, so the CLI can be developed and
+//! tested end to end with no network by pointing its base URL at the server.
+//!
+//! ## Key Components
+//!
+//! - `MockServer`: HTTP server bound to a local address
+//! - `MockState`: In-memory resources, optionally seeded from a JSON file
+//! - `route`: Dispatch of a request to the matching endpoint
+
+use anyhow::{Context, Result};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::{SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+pub use state::{Collection, MockResponse, MockState};
+
+/// In-memory resources of the mock server
+pub mod state;
+
+/// Request received by the mock server
+#[derive(Debug)]
+pub struct MockRequest<'a> {
+    /// HTTP method
+    pub method: &'a Method,
+    /// URL path, e.g. /emails
+    pub path: &'a str,
+    /// Decoded query parameters
+    pub query: Vec<(String, String)>,
+    /// Value of the `Authorization` header, if any
+    pub authorization: Option<&'a str>,
+    /// Value of the `Idempotency-Key` header, if any
+    pub idempotency_key: Option<&'a str>,
+    /// Raw request body
+    pub body: &'a [u8],
+}
+
+/// Local HTTP server implementing the Resend API in memory
+pub struct MockServer {
+    /// Listener accepting connections
+    listener: TcpListener,
+    /// Resources shared by every connection
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockServer {
+    /// Binds a mock server to a local address
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Address to listen on; port 0 picks a free port
+    /// * `state` - Initial resources of the server
+    pub fn bind(addr: SocketAddr, state: MockState) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("Could not listen on {}", addr))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Returns the address the server listens on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until `shutdown` completes
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let state = self.state;
+        let make_service = make_service_fn(move |_| {
+            let state = state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |request| handle(state.clone(), request))) }
+        });
+        Server::from_tcp(self.listener)?
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Loads the initial resources of a mock server from a JSON seed file
+pub fn load_seed(path: &Path) -> Result<MockState> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read seed file {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid seed file {}", path.display()))
+}
+
+/// Handles an HTTP request against the shared state
+async fn handle(
+    state: Arc<Mutex<MockState>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+    let query = parts
+        .uri
+        .query()
+        .map(|query| {
+            reqwest::Url::parse(&format!("http://localhost/?{}", query))
+                .map(|url| url.query_pairs().into_owned().collect())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    let request = MockRequest {
+        method: &parts.method,
+        path: parts.uri.path(),
+        query,
+        authorization: header(AUTHORIZATION.as_str()),
+        idempotency_key: header("idempotency-key"),
+        body: &body,
+    };
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        route(&mut state, &request)
+    };
+    eprintln!(
+        "{} {} -> {}",
+        parts.method,
+        parts.uri,
+        response.status.as_u16()
+    );
+
+    let response = Response::builder()
+        .status(response.status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(response.body.to_string()))
+        .unwrap_or_default();
+    Ok(response)
+}
+
+/// Dispatches a request to the matching endpoint
+///
+/// Requests must carry a bearer token, but any token is accepted. POST requests
+/// with an `Idempotency-Key` header get the response of the first request sent
+/// with the same key.
+pub fn route(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let authorized = request
+        .authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| !token.trim().is_empty());
+    if !authorized {
+        return MockResponse::error(
+            StatusCode::UNAUTHORIZED,
+            "missing_api_key",
+            "Missing API key in the authorization header.",
+        );
+    }
+
+    let idempotency_key = request
+        .idempotency_key
+        .filter(|_| request.method == Method::POST)
+        .map(|key| format!("{} {}", request.path, key));
+    if let Some(response) = idempotency_key
+        .as_deref()
+        .and_then(|key| state.idempotent_response(key))
+    {
+        return response;
+    }
+
+    let body = if request.body.is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_slice(request.body) {
+            Ok(body) => body,
+            Err(e) => return MockResponse::validation(format!("Invalid JSON body: {}", e)),
+        }
+    };
+
+    let response = dispatch(state, request, body);
+    if let Some(key) = idempotency_key {
+        if response.status.is_success() {
+            state.remember_response(key, &response);
+        }
+    }
+    response
+}
+
+/// Dispatches an authorized request to the matching endpoint
+fn dispatch(state: &mut MockState, request: &MockRequest, body: Value) -> MockResponse {
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let method = request.method;
+    let received = Collection::ReceivedEmails;
+
+    match segments.as_slice() {
+        ["emails", "batch"] if method == Method::POST => state.create_email_batch(body),
+        ["emails", "receiving"] if method == Method::GET => state.list(received, &request.query),
+        ["emails", "receiving", id] if method == Method::GET => state.get(received, id),
+        ["emails", "receiving", id, "attachments"] if method == Method::GET => {
+            state.attachments(received, id)
+        }
+        ["emails", id, "cancel"] if method == Method::POST => state.transition(
+            Collection::Emails,
+            id,
+            "last_event",
+            &["scheduled"],
+            "canceled",
+        ),
+        ["emails", id, "attachments"] if method == Method::GET => {
+            state.attachments(Collection::Emails, id)
+        }
+        ["domains", id, "verify"] if method == Method::POST => {
+            state.transition(Collection::Domains, id, "status", &[], "verified")
+        }
+        ["broadcasts", id, "send"] if method == Method::POST => {
+            state.transition(Collection::Broadcasts, id, "status", &["draft"], "sent")
+        }
+        ["contacts", contact, "segments", segment] if method == Method::POST => {
+            state.set_contact_segment(contact, segment, true)
+        }
+        ["contacts", contact, "segments", segment] if method == Method::DELETE => {
+            state.set_contact_segment(contact, segment, false)
+        }
+        [name] => match Collection::from_path(name) {
+            Some(collection) if method == Method::GET => state.list(collection, &request.query),
+            Some(collection) if method == Method::POST => state.create(collection, body),
+            Some(_) => method_not_allowed(method),
+            None => endpoint_not_found(),
+        },
+        [name, id] => match Collection::from_path(name) {
+            Some(collection) if method == Method::GET => state.get(collection, id),
+            Some(collection) if method == Method::PATCH => state.update(collection, id, body),
+            Some(Collection::Emails) => method_not_allowed(method),
+            Some(collection) if method == Method::DELETE => state.delete(collection, id),
+            Some(_) => method_not_allowed(method),
+            None => endpoint_not_found(),
+        },
+        _ => endpoint_not_found(),
+    }
+}
+
+/// Response for a path that matches no endpoint
+fn endpoint_not_found() -> MockResponse {
+    MockResponse::error(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        "The requested endpoint does not exist.",
+    )
+}
+
+/// Response for an endpoint that does not support the request method
+fn method_not_allowed(method: &Method) -> MockResponse {
+    MockResponse::error(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+        format!("Method {} is not allowed for this endpoint.", method),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{emails, segments, ListOptions, PaginationOptions, ResendApi, ResendClient};
+    use crate::config::Config;
+    use serde_json::json;
+
+    /// Starts a mock server on a free port and returns a client pointed at it
+    async fn start(state: MockState) -> ResendClient {
+        let server = MockServer::bind("127.0.0.1:0".parse().unwrap(), state).unwrap();
+        let base_url = format!("http://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run_until(std::future::pending()));
+        ResendClient::new(Config {
+            api_key: "re_test".to_string(),
+            base_url: Some(base_url),
+            requests_per_second: Some(0.0),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn request<'a>(method: &'a Method, path: &'a str, body: &'a [u8]) -> MockRequest<'a> {
+        MockRequest {
+            method,
+            path,
+            query: Vec::new(),
+            authorization: Some("Bearer re_test"),
+            idempotency_key: None,
+            body,
+        }
+    }
+
+    #[test]
+    fn test_requests_without_api_key_are_rejected() {
+        let mut state = MockState::default();
+        let request = MockRequest {
+            authorization: None,
+            ..request(&Method::GET, "/domains", b"")
+        };
+        let response = route(&mut state, &request);
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(response.body["name"], "missing_api_key");
+    }
+
+    #[test]
+    fn test_unknown_endpoints_and_methods() {
+        let mut state = MockState::default();
+        let unknown = route(&mut state, &request(&Method::GET, "/unknown", b""));
+        assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+        let delete_email = route(&mut state, &request(&Method::DELETE, "/emails/e1", b""));
+        assert_eq!(delete_email.status, StatusCode::METHOD_NOT_ALLOWED);
+        let invalid = route(&mut state, &request(&Method::POST, "/segments", b"{"));
+        assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_idempotency_key_replays_first_response() {
+        let mut state = MockState::default();
+        let body = br#"{"from":"a@example.com","to":"b@example.com","subject":"Hi"}"#;
+        let keyed = MockRequest {
+            idempotency_key: Some("order-1"),
+            ..request(&Method::POST, "/emails", body)
+        };
+        let first = route(&mut state, &keyed);
+        let second = route(&mut state, &keyed);
+        assert_eq!(first, second);
+        assert_eq!(state.emails.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_round_trip() {
+        let client = start(MockState::default()).await;
+
+        let sent = client
+            .send_email(
+                emails::SendEmailRequest {
+                    html: Some("<p>Hi</p>".to_string()),
+                    ..emails::SendEmailRequest::new(
+                        "hi@acme.com",
+                        vec!["user@example.com".to_string()],
+                        "Hello",
+                    )
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let email = client.get_email(&sent.id).await.unwrap();
+        assert_eq!(email.subject, "Hello");
+        assert_eq!(email.last_event, "delivered");
+
+        let domain = client
+            .create_domain(crate::api::domains::CreateDomainRequest {
+                name: "acme.com".to_string(),
+                region: None,
+            })
+            .await
+            .unwrap();
+        client.verify_domain(&domain.id).await.unwrap();
+        assert_eq!(
+            client.get_domain(&domain.id).await.unwrap().status,
+            "verified"
+        );
+        client.delete_domain(&domain.id).await.unwrap();
+        assert!(client.get_domain(&domain.id).await.is_err());
+
+        let key = client
+            .create_api_key(crate::api::api_keys::CreateApiKeyRequest {
+                name: "CI".to_string(),
+                permission: None,
+                domain_id: None,
+            })
+            .await
+            .unwrap();
+        assert!(key.token.unwrap().starts_with("re_"));
+        assert!(client
+            .list_api_keys(PaginationOptions::default())
+            .await
+            .unwrap()
+            .data[0]
+            .token
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_seeded_state_is_paginated() {
+        let segments: Vec<Value> = (1..=7)
+            .map(|i| json!({ "id": format!("s{}", i), "name": format!("Segment {}", i), "created_at": "2023-01-01" }))
+            .collect();
+        let state: MockState = serde_json::from_value(json!({ "segments": segments })).unwrap();
+        let client = start(state).await;
+
+        let options = ListOptions {
+            pagination: PaginationOptions {
+                limit: Some(3),
+                ..Default::default()
+            },
+            all: true,
+            max_items: None,
+        };
+        let segments: Vec<segments::Segment> =
+            options.collect(|p| client.list_segments(p)).await.unwrap();
+        assert_eq!(segments.len(), 7);
+        assert_eq!(segments[6].id, "s7");
+    }
+}
@@ -0,0 +1,693 @@
+//! # Mock Server State
+//!
+//! In-memory resources served by the mock server. Every collection is a list of
+//! JSON objects kept newest first, like the Resend API's list endpoints. The
+//! state can be seeded from a JSON file using the same field names as
+//! [`MockState`].
+
+use rand::Rng;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Default number of items returned by list endpoints
+const DEFAULT_LIMIT: usize = 20;
+
+/// Maximum number of items returned by list endpoints
+const MAX_LIMIT: usize = 100;
+
+/// Resources held by the mock server
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockState {
+    /// Sent emails
+    pub emails: Vec<Value>,
+    /// API keys, without their tokens
+    pub api_keys: Vec<Value>,
+    /// Sending domains
+    pub domains: Vec<Value>,
+    /// Contact segments
+    pub segments: Vec<Value>,
+    /// Contacts
+    pub contacts: Vec<Value>,
+    /// Email templates
+    pub templates: Vec<Value>,
+    /// Subscription topics
+    pub topics: Vec<Value>,
+    /// Webhook endpoints
+    pub webhooks: Vec<Value>,
+    /// Broadcasts
+    pub broadcasts: Vec<Value>,
+    /// Custom contact properties
+    pub contact_properties: Vec<Value>,
+    /// Received (inbound) emails
+    pub received_emails: Vec<Value>,
+    /// Attachments of sent emails, by email ID
+    pub email_attachments: BTreeMap<String, Vec<Value>>,
+    /// Attachments of received emails, by received email ID
+    pub received_attachments: BTreeMap<String, Vec<Value>>,
+    /// Segment IDs of each contact, by contact ID
+    pub contact_segments: BTreeMap<String, Vec<String>>,
+    /// Responses already sent for each idempotency key
+    #[serde(skip)]
+    idempotent_responses: BTreeMap<String, MockResponse>,
+}
+
+/// Response produced by the mock server
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    /// HTTP status code
+    pub status: StatusCode,
+    /// JSON body
+    pub body: Value,
+}
+
+impl MockResponse {
+    /// Creates a successful response
+    pub fn ok(body: Value) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body,
+        }
+    }
+
+    /// Creates an error response using Resend's error envelope
+    pub fn error(status: StatusCode, name: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({
+                "statusCode": status.as_u16(),
+                "name": name,
+                "message": message.into(),
+            }),
+        }
+    }
+
+    /// Creates a 404 response for a missing resource
+    pub fn not_found(object: &str) -> Self {
+        Self::error(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            format!("The requested {} was not found.", object.replace('_', " ")),
+        )
+    }
+
+    /// Creates a 422 validation error response
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "validation_error",
+            message,
+        )
+    }
+}
+
+/// A collection of resources with generic create, get, list, update and delete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collection {
+    Emails,
+    ApiKeys,
+    Domains,
+    Segments,
+    Contacts,
+    Templates,
+    Topics,
+    Webhooks,
+    Broadcasts,
+    ContactProperties,
+    ReceivedEmails,
+}
+
+impl Collection {
+    /// Returns the collection served under an API path segment
+    pub fn from_path(segment: &str) -> Option<Self> {
+        Some(match segment {
+            "emails" => Self::Emails,
+            "api-keys" => Self::ApiKeys,
+            "domains" => Self::Domains,
+            "segments" => Self::Segments,
+            "contacts" => Self::Contacts,
+            "templates" => Self::Templates,
+            "topics" => Self::Topics,
+            "webhooks" => Self::Webhooks,
+            "broadcasts" => Self::Broadcasts,
+            "contact-properties" => Self::ContactProperties,
+            _ => return None,
+        })
+    }
+
+    /// Returns the value of the `object` field of the collection's resources
+    pub fn object(self) -> &'static str {
+        match self {
+            Self::Emails => "email",
+            Self::ApiKeys => "api_key",
+            Self::Domains => "domain",
+            Self::Segments => "segment",
+            Self::Contacts => "contact",
+            Self::Templates => "template",
+            Self::Topics => "topic",
+            Self::Webhooks => "webhook",
+            Self::Broadcasts => "broadcast",
+            Self::ContactProperties => "contact_property",
+            Self::ReceivedEmails => "email",
+        }
+    }
+
+    /// Returns the fields a create request must contain
+    fn required_fields(self) -> &'static [&'static str] {
+        match self {
+            Self::Emails => &["from", "to", "subject"],
+            Self::ApiKeys | Self::Domains | Self::Segments => &["name"],
+            Self::Contacts => &["email"],
+            Self::Templates => &["name", "html"],
+            Self::Topics => &["name", "default_subscription"],
+            Self::Webhooks => &["endpoint", "events"],
+            Self::Broadcasts => &["segment_id", "from", "subject"],
+            Self::ContactProperties => &["key", "type"],
+            Self::ReceivedEmails => &[],
+        }
+    }
+
+    /// Returns the fields set on new resources when missing from the request
+    fn defaults(self) -> Value {
+        match self {
+            Self::ApiKeys => json!({ "permission": "full_access" }),
+            Self::Domains => json!({
+                "status": "not_started",
+                "region": "us-east-1",
+                "records": [],
+            }),
+            Self::Contacts => json!({
+                "first_name": null,
+                "last_name": null,
+                "unsubscribed": false,
+            }),
+            Self::Webhooks => json!({
+                "status": "enabled",
+                "signing_secret": format!("whsec_{}", random_hex(16)),
+            }),
+            Self::Broadcasts => json!({ "name": null, "status": "draft" }),
+            Self::ContactProperties => json!({ "fallback_value": null }),
+            _ => json!({}),
+        }
+    }
+}
+
+impl MockState {
+    /// Returns the resources of a collection
+    fn collection(&self, collection: Collection) -> &Vec<Value> {
+        match collection {
+            Collection::Emails => &self.emails,
+            Collection::ApiKeys => &self.api_keys,
+            Collection::Domains => &self.domains,
+            Collection::Segments => &self.segments,
+            Collection::Contacts => &self.contacts,
+            Collection::Templates => &self.templates,
+            Collection::Topics => &self.topics,
+            Collection::Webhooks => &self.webhooks,
+            Collection::Broadcasts => &self.broadcasts,
+            Collection::ContactProperties => &self.contact_properties,
+            Collection::ReceivedEmails => &self.received_emails,
+        }
+    }
+
+    /// Returns the resources of a collection for modification
+    fn collection_mut(&mut self, collection: Collection) -> &mut Vec<Value> {
+        match collection {
+            Collection::Emails => &mut self.emails,
+            Collection::ApiKeys => &mut self.api_keys,
+            Collection::Domains => &mut self.domains,
+            Collection::Segments => &mut self.segments,
+            Collection::Contacts => &mut self.contacts,
+            Collection::Templates => &mut self.templates,
+            Collection::Topics => &mut self.topics,
+            Collection::Webhooks => &mut self.webhooks,
+            Collection::Broadcasts => &mut self.broadcasts,
+            Collection::ContactProperties => &mut self.contact_properties,
+            Collection::ReceivedEmails => &mut self.received_emails,
+        }
+    }
+
+    /// Returns the position of a resource, looked up by ID (or email for contacts)
+    fn position(&self, collection: Collection, id: &str) -> Option<usize> {
+        self.collection(collection).iter().position(|item| {
+            item["id"] == id || (collection == Collection::Contacts && item["email"] == id)
+        })
+    }
+
+    /// Returns the response previously sent for an idempotency key, if any
+    pub fn idempotent_response(&self, key: &str) -> Option<MockResponse> {
+        self.idempotent_responses.get(key).cloned()
+    }
+
+    /// Remembers the response sent for an idempotency key
+    pub fn remember_response(&mut self, key: String, response: &MockResponse) {
+        self.idempotent_responses.insert(key, response.clone());
+    }
+
+    /// Lists a page of a collection
+    pub fn list(&self, collection: Collection, query: &[(String, String)]) -> MockResponse {
+        paginate(self.collection(collection), query)
+    }
+
+    /// Gets a single resource
+    pub fn get(&self, collection: Collection, id: &str) -> MockResponse {
+        match self.position(collection, id) {
+            Some(index) => MockResponse::ok(self.collection(collection)[index].clone()),
+            None => MockResponse::not_found(collection.object()),
+        }
+    }
+
+    /// Creates a resource from a request body
+    pub fn create(&mut self, collection: Collection, body: Value) -> MockResponse {
+        let resource = match new_resource(collection, body) {
+            Ok(resource) => resource,
+            Err(response) => return response,
+        };
+        let mut response = resource.clone();
+
+        match collection {
+            Collection::ApiKeys => {
+                response["token"] = json!(format!("re_{}", random_hex(16)));
+            }
+            Collection::Emails => response = json!({ "id": resource["id"] }),
+            _ => {}
+        }
+        self.collection_mut(collection).insert(0, resource);
+        MockResponse::ok(response)
+    }
+
+    /// Creates a batch of emails from an array of request bodies
+    ///
+    /// Nothing is sent when any email of the batch is invalid.
+    pub fn create_email_batch(&mut self, body: Value) -> MockResponse {
+        let Value::Array(requests) = body else {
+            return MockResponse::validation("The request body must be an array of emails.");
+        };
+        let mut emails = Vec::with_capacity(requests.len());
+        for request in requests {
+            match new_resource(Collection::Emails, request) {
+                Ok(email) => emails.push(email),
+                Err(response) => return response,
+            }
+        }
+        let data: Vec<Value> = emails.iter().map(|e| json!({ "id": e["id"] })).collect();
+        for email in emails {
+            self.emails.insert(0, email);
+        }
+        MockResponse::ok(json!({ "data": data }))
+    }
+
+    /// Updates a resource with the non-null fields of a request body
+    pub fn update(&mut self, collection: Collection, id: &str, body: Value) -> MockResponse {
+        let Value::Object(fields) = body else {
+            return MockResponse::validation("The request body must be a JSON object.");
+        };
+        let Some(index) = self.position(collection, id) else {
+            return MockResponse::not_found(collection.object());
+        };
+        let resource = &mut self.collection_mut(collection)[index];
+        for (key, value) in fields {
+            if !value.is_null() && key != "id" {
+                resource[key] = value;
+            }
+        }
+        if collection == Collection::Emails {
+            if resource["scheduled_at"].is_string() {
+                resource["last_event"] = json!("scheduled");
+            }
+            return MockResponse::ok(json!({ "object": "email", "id": resource["id"] }));
+        }
+        MockResponse::ok(resource.clone())
+    }
+
+    /// Deletes a resource
+    pub fn delete(&mut self, collection: Collection, id: &str) -> MockResponse {
+        let Some(index) = self.position(collection, id) else {
+            return MockResponse::not_found(collection.object());
+        };
+        let resource = self.collection_mut(collection).remove(index);
+        let id = resource["id"].as_str().unwrap_or(id).to_string();
+        match collection {
+            Collection::Contacts => {
+                self.contact_segments.remove(&id);
+            }
+            Collection::Segments => {
+                for segments in self.contact_segments.values_mut() {
+                    segments.retain(|segment| *segment != id);
+                }
+            }
+            _ => {}
+        }
+        MockResponse::ok(json!({
+            "object": collection.object(),
+            "id": id,
+            "deleted": true,
+        }))
+    }
+
+    /// Sets a field of a resource, provided its current value is one of `from`
+    ///
+    /// Used for state transitions such as verifying a domain, cancelling a
+    /// scheduled email or sending a draft broadcast.
+    pub fn transition(
+        &mut self,
+        collection: Collection,
+        id: &str,
+        field: &str,
+        from: &[&str],
+        to: &str,
+    ) -> MockResponse {
+        let Some(index) = self.position(collection, id) else {
+            return MockResponse::not_found(collection.object());
+        };
+        let resource = &mut self.collection_mut(collection)[index];
+        let current = resource[field].as_str().unwrap_or_default().to_string();
+        if !from.is_empty() && !from.contains(&current.as_str()) {
+            return MockResponse::validation(format!(
+                "The {} cannot change from {} to {}.",
+                collection.object().replace('_', " "),
+                current,
+                to
+            ));
+        }
+        resource[field] = json!(to);
+        MockResponse::ok(json!({ "object": collection.object(), "id": resource["id"] }))
+    }
+
+    /// Adds a contact to a segment, or removes it from the segment
+    pub fn set_contact_segment(
+        &mut self,
+        contact: &str,
+        segment_id: &str,
+        member: bool,
+    ) -> MockResponse {
+        let Some(index) = self.position(Collection::Contacts, contact) else {
+            return MockResponse::not_found("contact");
+        };
+        if self.position(Collection::Segments, segment_id).is_none() {
+            return MockResponse::not_found("segment");
+        }
+        let contact_id = self.contacts[index]["id"]
+            .as_str()
+            .unwrap_or(contact)
+            .to_string();
+        let segments = self.contact_segments.entry(contact_id).or_default();
+        segments.retain(|segment| segment != segment_id);
+        if member {
+            segments.push(segment_id.to_string());
+            MockResponse::ok(json!({ "id": segment_id }))
+        } else {
+            MockResponse::ok(json!({ "id": segment_id, "deleted": true }))
+        }
+    }
+
+    /// Lists the attachments of a sent or received email
+    pub fn attachments(&self, collection: Collection, id: &str) -> MockResponse {
+        let Some(index) = self.position(collection, id) else {
+            return MockResponse::not_found(collection.object());
+        };
+        let id = self.collection(collection)[index]["id"]
+            .as_str()
+            .unwrap_or(id);
+        let attachments = match collection {
+            Collection::ReceivedEmails => self.received_attachments.get(id),
+            _ => self.email_attachments.get(id),
+        };
+        paginate(attachments.map_or(&[][..], Vec::as_slice), &[])
+    }
+}
+
+/// Builds a new resource from a create request body
+fn new_resource(collection: Collection, body: Value) -> Result<Value, MockResponse> {
+    let Value::Object(fields) = body else {
+        return Err(MockResponse::validation(
+            "The request body must be a JSON object.",
+        ));
+    };
+    for field in collection.required_fields() {
+        if fields.get(*field).is_none_or(Value::is_null) {
+            return Err(MockResponse::validation(format!(
+                "Missing `{}` field.",
+                field
+            )));
+        }
+    }
+
+    let mut resource = Map::new();
+    resource.insert("object".to_string(), json!(collection.object()));
+    resource.insert("id".to_string(), json!(new_id()));
+    if let Value::Object(defaults) = collection.defaults() {
+        resource.extend(defaults);
+    }
+    resource.extend(fields);
+    resource.insert("created_at".to_string(), json!(timestamp()));
+
+    if collection == Collection::Emails {
+        for field in ["to", "cc", "bcc", "reply_to"] {
+            if let Some(Value::String(address)) = resource.get(field) {
+                let addresses = json!([address]);
+                resource.insert(field.to_string(), addresses);
+            }
+        }
+        let scheduled = resource.get("scheduled_at").is_some_and(Value::is_string);
+        let last_event = if scheduled { "scheduled" } else { "delivered" };
+        resource.insert("last_event".to_string(), json!(last_event));
+    }
+    Ok(Value::Object(resource))
+}
+
+/// Returns a page of items following the `limit`, `after` and `before` parameters
+fn paginate(items: &[Value], query: &[(String, String)]) -> MockResponse {
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let limit = match param("limit").map(str::parse::<usize>) {
+        None => DEFAULT_LIMIT,
+        Some(Ok(limit)) if (1..=MAX_LIMIT).contains(&limit) => limit,
+        Some(_) => {
+            return MockResponse::validation(format!(
+                "The `limit` parameter must be between 1 and {}.",
+                MAX_LIMIT
+            ))
+        }
+    };
+    let position = |cursor: &str| items.iter().position(|item| item["id"] == cursor);
+
+    let (start, end, has_more) = match (param("after"), param("before")) {
+        (Some(_), Some(_)) => {
+            return MockResponse::validation(
+                "The `after` and `before` parameters cannot be used together.",
+            )
+        }
+        (Some(cursor), None) => {
+            let Some(index) = position(cursor) else {
+                return MockResponse::validation(format!("Invalid cursor `{}`.", cursor));
+            };
+            let end = (index + 1 + limit).min(items.len());
+            (index + 1, end, end < items.len())
+        }
+        (None, Some(cursor)) => {
+            let Some(index) = position(cursor) else {
+                return MockResponse::validation(format!("Invalid cursor `{}`.", cursor));
+            };
+            let start = index.saturating_sub(limit);
+            (start, index, start > 0)
+        }
+        (None, None) => {
+            let end = limit.min(items.len());
+            (0, end, end < items.len())
+        }
+    };
+
+    MockResponse::ok(json!({
+        "object": "list",
+        "has_more": has_more,
+        "data": &items[start..end],
+    }))
+}
+
+/// Generates a random UUID (version 4), the format of Resend IDs
+fn new_id() -> String {
+    let hex = random_hex(16);
+    format!(
+        "{}-{}-4{}-{:x}{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[13..16],
+        rand::thread_rng().gen_range(8..12),
+        &hex[17..20],
+        &hex[20..32]
+    )
+}
+
+/// Generates a random hexadecimal string of the given number of bytes
+fn random_hex(bytes: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..bytes)
+        .map(|_| format!("{:02x}", rng.gen::<u8>()))
+        .collect()
+}
+
+/// Returns the current time in the format used by the Resend API
+fn timestamp() -> String {
+    chrono::Utc::now()
+        .format("%Y-%m-%d %H:%M:%S%.6f+00")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(response: &MockResponse) -> Vec<String> {
+        response.body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn seeded_segments(count: usize) -> MockState {
+        MockState {
+            segments: (1..=count)
+                .map(|i| json!({ "id": format!("s{}", i), "name": format!("Segment {}", i) }))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_pagination_cursors() {
+        let state = seeded_segments(5);
+
+        let first = state.list(Collection::Segments, &query(&[("limit", "2")]));
+        assert_eq!(ids(&first), ["s1", "s2"]);
+        assert_eq!(first.body["has_more"], true);
+
+        let last = state.list(
+            Collection::Segments,
+            &query(&[("limit", "2"), ("after", "s4")]),
+        );
+        assert_eq!(ids(&last), ["s5"]);
+        assert_eq!(last.body["has_more"], false);
+
+        let previous = state.list(
+            Collection::Segments,
+            &query(&[("limit", "2"), ("before", "s4")]),
+        );
+        assert_eq!(ids(&previous), ["s2", "s3"]);
+        assert_eq!(previous.body["has_more"], true);
+
+        let invalid = state.list(Collection::Segments, &query(&[("limit", "500")]));
+        assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_create_update_delete() {
+        let mut state = MockState::default();
+
+        let created = state.create(Collection::Contacts, json!({ "email": "a@example.com" }));
+        assert_eq!(created.status, StatusCode::OK);
+        assert_eq!(created.body["unsubscribed"], false);
+        let id = created.body["id"].as_str().unwrap().to_string();
+
+        let updated = state.update(
+            Collection::Contacts,
+            "a@example.com",
+            json!({ "first_name": "Ada", "last_name": null }),
+        );
+        assert_eq!(updated.body["first_name"], "Ada");
+        assert_eq!(updated.body["id"], id.as_str());
+
+        let deleted = state.delete(Collection::Contacts, &id);
+        assert_eq!(deleted.body["deleted"], true);
+        assert_eq!(
+            state.get(Collection::Contacts, &id).status,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_missing_required_field_is_rejected() {
+        let mut state = MockState::default();
+        let response = state.create(Collection::Domains, json!({ "region": "eu-west-1" }));
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.body["name"], "validation_error");
+        assert!(state.domains.is_empty());
+    }
+
+    #[test]
+    fn test_email_batch_is_all_or_nothing() {
+        let mut state = MockState::default();
+        let email = json!({ "from": "a@example.com", "to": "b@example.com", "subject": "Hi" });
+
+        let invalid = state.create_email_batch(json!([email, { "from": "a@example.com" }]));
+        assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(state.emails.is_empty());
+
+        let sent = state.create_email_batch(json!([email, email]));
+        assert_eq!(sent.body["data"].as_array().unwrap().len(), 2);
+        assert_eq!(state.emails[0]["to"], json!(["b@example.com"]));
+        assert_eq!(state.emails[0]["last_event"], "delivered");
+    }
+
+    #[test]
+    fn test_transitions() {
+        let mut state = MockState::default();
+        let id = state
+            .create(
+                Collection::Broadcasts,
+                json!({ "segment_id": "s1", "from": "a@example.com", "subject": "News" }),
+            )
+            .body["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let send = |state: &mut MockState| {
+            state.transition(Collection::Broadcasts, &id, "status", &["draft"], "sent")
+        };
+        assert_eq!(send(&mut state).status, StatusCode::OK);
+        assert_eq!(state.broadcasts[0]["status"], "sent");
+        assert_eq!(send(&mut state).status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_contact_segments() {
+        let mut state = seeded_segments(1);
+        state
+            .contacts
+            .push(json!({ "id": "c1", "email": "a@example.com" }));
+
+        let added = state.set_contact_segment("a@example.com", "s1", true);
+        assert_eq!(added.status, StatusCode::OK);
+        assert_eq!(state.contact_segments["c1"], ["s1"]);
+
+        let missing = state.set_contact_segment("c1", "s2", true);
+        assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+        state.delete(Collection::Segments, "s1");
+        assert!(state.contact_segments["c1"].is_empty());
+    }
+
+    #[test]
+    fn test_new_ids_are_uuids() {
+        let id = new_id();
+        assert_eq!(id.len(), 36);
+        assert_eq!(id.matches('-').count(), 4);
+        assert_eq!(&id[14..15], "4");
+    }
+}
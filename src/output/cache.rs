@@ -0,0 +1,220 @@
+//! # ID Cache Module
+//!
+//! This module keeps the IDs and names of recently listed resources under
+//! ~/.resend-cli/cache, one JSON file per resource type, so that shell completion
+//! can offer real IDs without calling the API. Each profile and base URL has a
+//! cache of its own, so that completion never offers the IDs of another account
+//! or server.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Resource types whose IDs are cached for completion
+pub const CACHED_RESOURCES: &[&str] = &["domains", "segments", "templates", "broadcasts"];
+
+/// Maximum number of IDs kept per resource type
+pub const MAX_CACHED_IDS: usize = 200;
+
+/// ID and name of a listed resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedId {
+    /// ID of the resource
+    pub id: String,
+    /// Name of the resource, shown next to the ID when completing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl CachedId {
+    /// Reads the `id` and `name` fields of a serialized resource
+    ///
+    /// # Returns
+    ///
+    /// The cached ID, or `None` if the resource has no string `id` field
+    pub fn from_value(value: &Value) -> Option<Self> {
+        Some(Self {
+            id: value.get("id")?.as_str()?.to_string(),
+            name: value
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Directory of cached resource IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdCache {
+    dir: PathBuf,
+}
+
+impl IdCache {
+    /// Creates a cache stored in a directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the cache of an account under ~/.resend-cli/cache, or `None`
+    /// without a home directory
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile of the API key, or `None` for a key from the environment
+    /// * `base_url` - Base URL of the API
+    pub fn default_location(profile: Option<&str>, base_url: &str) -> Option<Self> {
+        dirs::home_dir().map(|home| {
+            Self::for_account(&home.join(".resend-cli").join("cache"), profile, base_url)
+        })
+    }
+
+    /// Returns the cache of an account in a directory of caches
+    ///
+    /// The account is named `<profile>@<host>`, e.g. `staging@api.resend.com`, or
+    /// just `<host>` for an API key from the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory holding the cache of each account
+    /// * `profile` - Profile of the API key, or `None` for a key from the environment
+    /// * `base_url` - Base URL of the API
+    pub fn for_account(root: &Path, profile: Option<&str>, base_url: &str) -> Self {
+        let host = base_url
+            .split_once("://")
+            .map_or(base_url, |(_, host)| host)
+            .trim_end_matches('/');
+        let account = match profile {
+            Some(profile) => format!("{}@{}", profile, host),
+            None => host.to_string(),
+        };
+        let name: String = account
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "@.-_".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Self::new(root.join(name))
+    }
+
+    /// Returns the cached IDs of a resource type, most recently listed first
+    ///
+    /// A missing or unreadable cache file yields no IDs.
+    pub fn get(&self, resource: &str) -> Vec<CachedId> {
+        std::fs::read_to_string(self.path(resource))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Adds listed resources to the cache
+    ///
+    /// The given IDs come first, followed by previously cached IDs that were not
+    /// listed again, up to [`MAX_CACHED_IDS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - Resource type, e.g. `broadcasts`
+    /// * `ids` - IDs and names of the listed resources
+    pub fn remember(&self, resource: &str, ids: Vec<CachedId>) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let previous = self.get(resource);
+        let mut merged = ids;
+        for cached in previous {
+            if !merged.iter().any(|id| id.id == cached.id) {
+                merged.push(cached);
+            }
+        }
+        merged.truncate(MAX_CACHED_IDS);
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(resource), serde_json::to_string(&merged)?)?;
+        Ok(())
+    }
+
+    /// Returns the path of the cache file of a resource type
+    fn path(&self, resource: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", resource))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn id(id: &str, name: &str) -> CachedId {
+        CachedId {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_remember_puts_recent_ids_first() {
+        let dir = TempDir::new().unwrap();
+        let cache = IdCache::new(dir.path().join("cache"));
+        assert!(cache.get("broadcasts").is_empty());
+
+        cache
+            .remember("broadcasts", vec![id("b_1", "Launch"), id("b_2", "Digest")])
+            .unwrap();
+        cache
+            .remember(
+                "broadcasts",
+                vec![id("b_3", "Promo"), id("b_1", "Launch v2")],
+            )
+            .unwrap();
+        assert_eq!(
+            cache.get("broadcasts"),
+            vec![
+                id("b_3", "Promo"),
+                id("b_1", "Launch v2"),
+                id("b_2", "Digest")
+            ]
+        );
+        assert!(cache.get("domains").is_empty());
+    }
+
+    #[test]
+    fn test_each_account_has_its_own_cache() {
+        let root = Path::new("/cache");
+        let cache = |profile, base_url| IdCache::for_account(root, profile, base_url).dir;
+        assert_eq!(
+            cache(Some("staging"), "https://api.resend.com/"),
+            root.join("staging@api.resend.com")
+        );
+        assert_eq!(
+            cache(None, "http://127.0.0.1:4010"),
+            root.join("127.0.0.1_4010")
+        );
+        assert_eq!(cache(Some("../x"), "a/b"), root.join(".._x@a_b"));
+        assert_ne!(
+            cache(Some("default"), "https://api.resend.com"),
+            cache(Some("default"), "http://localhost:4010")
+        );
+    }
+
+    #[test]
+    fn test_cached_id_from_value() {
+        assert_eq!(
+            CachedId::from_value(&json!({"id": "dom_1", "name": "acme.com", "status": "verified"})),
+            Some(id("dom_1", "acme.com"))
+        );
+        assert_eq!(
+            CachedId::from_value(&json!({"id": "b_1", "name": null})),
+            Some(CachedId {
+                id: "b_1".to_string(),
+                name: None
+            })
+        );
+        assert_eq!(CachedId::from_value(&json!({"name": "x"})), None);
+    }
+}
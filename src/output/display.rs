@@ -0,0 +1,82 @@
+//! # Table Cell Formatting
+//!
+//! Helpers used with `#[tabled(display_with = "...")]` to render fields that do
+//! not implement `Display`, such as optional values, lists and free-form JSON.
+
+use serde_json::Value;
+use std::fmt::Display;
+
+/// Placeholder shown for missing values
+pub const NONE: &str = "-";
+
+/// Formats an optional value, or a placeholder if it is missing
+pub fn option<T: Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => NONE.to_string(),
+    }
+}
+
+/// Formats a list as comma-separated values
+pub fn list(values: &[String]) -> String {
+    values.join(", ")
+}
+
+/// Formats an optional list as comma-separated values
+pub fn option_list(values: &Option<Vec<String>>) -> String {
+    match values {
+        Some(values) if !values.is_empty() => list(values),
+        _ => NONE.to_string(),
+    }
+}
+
+/// Formats an optional list of displayable items as comma-separated values
+pub fn option_items<T: Display>(values: &Option<Vec<T>>) -> String {
+    match values {
+        Some(values) if !values.is_empty() => values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => NONE.to_string(),
+    }
+}
+
+/// Formats a JSON value: strings without quotes, anything else as compact JSON
+pub fn json(value: &Value) -> String {
+    match value {
+        Value::Null => NONE.to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Formats an optional JSON value
+pub fn option_json(value: &Option<Value>) -> String {
+    value.as_ref().map_or_else(|| NONE.to_string(), json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_values_use_a_placeholder() {
+        assert_eq!(option::<String>(&None), "-");
+        assert_eq!(option(&Some(3)), "3");
+        assert_eq!(option_list(&Some(vec![])), "-");
+        assert_eq!(option_json(&None), "-");
+    }
+
+    #[test]
+    fn test_json_values() {
+        assert_eq!(json(&json!("Acme")), "Acme");
+        assert_eq!(json(&json!(42)), "42");
+        assert_eq!(json(&json!({"a": [1, 2]})), r#"{"a":[1,2]}"#);
+        assert_eq!(
+            list(&["a@x.com".to_string(), "b@x.com".to_string()]),
+            "a@x.com, b@x.com"
+        );
+    }
+}
@@ -1,10 +1,533 @@
 //! # Output Formatting Module
 //!
 //! This module provides utilities for formatting and displaying data in the CLI.
-//! It includes functions for printing tabular data using the tabled crate.
+//! Every command prints its result through an `Output`, which renders it either
+//! for humans (tables and messages) or as JSON, NDJSON, YAML or CSV for scripts.
 
+use crate::api::MAX_PAGE_SIZE;
+use anyhow::{anyhow, bail, Result};
+use clap::ValueEnum;
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Write;
+use tabled::builder::Builder;
 use tabled::{Table, Tabled};
 
+/// Cache of recently listed resource IDs, used by shell completion
+pub mod cache;
+/// Cell formatting helpers for `Tabled` derives
+pub mod display;
+/// JMESPath subset used by `--query`
+pub mod query;
+
+pub use cache::IdCache;
+pub use query::Query;
+
+/// Format in which command results are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and messages
+    #[default]
+    Table,
+    /// A single pretty-printed JSON document
+    Json,
+    /// One compact JSON document per line, one line per item of a list
+    Ndjson,
+    /// A YAML document
+    Yaml,
+    /// Comma-separated values with a header row, nested values as JSON
+    Csv,
+}
+
+/// Renderer for command results
+///
+/// In the `table` format, lists are printed as tables, single resources as a
+/// key/value table and mutations as a confirmation message. Every
+/// other format serializes the underlying data, so scripts never need to parse
+/// messages meant for humans.
+///
+/// A query replaces the data with its result before rendering; in the `table`
+/// format, the result is then printed as a table, a key/value table or one
+/// scalar per line, depending on its shape.
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    /// Format in which results are printed
+    pub format: OutputFormat,
+    /// Columns kept in tables and CSV, in this order (all columns if empty)
+    pub columns: Vec<String>,
+    /// Query filtering and projecting the data before it is rendered
+    pub query: Option<Query>,
+    /// Cache recording listed resource IDs for shell completion, if enabled
+    pub id_cache: Option<IdCache>,
+}
+
+impl Output {
+    /// Creates a renderer for the given format
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether results are printed for humans rather than as data
+    fn is_human(&self) -> bool {
+        self.format == OutputFormat::Table && self.query.is_none()
+    }
+
+    /// Prints a list of resources
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Resources to print, as a table in the `table` format
+    pub fn list<T: Serialize + Tabled>(&self, items: Vec<T>) -> Result<()> {
+        if !self.is_human() {
+            return self.print_data(&serde_json::to_value(&items)?);
+        }
+        if items.is_empty() {
+            println!("No items found.");
+            return Ok(());
+        }
+        let headers = T::headers().into_iter().map(|h| h.into_owned()).collect();
+        let rows = items
+            .iter()
+            .map(|item| item.fields().into_iter().map(|f| f.into_owned()).collect())
+            .collect();
+        let (headers, rows) = select_columns(headers, rows, &self.columns)?;
+        println!("{}", table(headers, rows));
+        Ok(())
+    }
+
+    /// Prints a list of resources as they are fetched
+    ///
+    /// In the `ndjson` and `csv` formats without a query, each page is printed as
+    /// soon as it arrives, so that `--all` starts printing right away and never
+    /// holds the whole list in memory. Items printed before an error stay printed.
+    /// CSV columns are then those of the first page: a field first appearing in a
+    /// later page is an error, unless `--columns` leaves it out. Every other
+    /// format needs the whole list and collects it first.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Resources to print, e.g. from [`crate::api::ListOptions::stream`]
+    /// * `resource` - Resource type whose IDs are remembered for completion, if any
+    pub async fn list_stream<T, S>(&self, items: S, resource: Option<&str>) -> Result<()>
+    where
+        T: Serialize + Tabled,
+        S: Stream<Item = Result<T>> + Unpin,
+    {
+        let streamed = matches!(self.format, OutputFormat::Ndjson | OutputFormat::Csv);
+        if !streamed || self.query.is_some() {
+            let items: Vec<T> = items.try_collect().await?;
+            if let Some(resource) = resource {
+                self.remember(resource, &items);
+            }
+            return self.list(items);
+        }
+        self.write_stream(items, resource, &mut std::io::stdout())
+            .await
+    }
+
+    /// Writes a list in the `ndjson` or `csv` format a page at a time
+    async fn write_stream<T, S>(
+        &self,
+        items: S,
+        resource: Option<&str>,
+        out: &mut (dyn Write + Send),
+    ) -> Result<()>
+    where
+        T: Serialize,
+        S: Stream<Item = Result<T>> + Unpin,
+    {
+        // Items of a page are ready together, and are printed without waiting
+        // for the next page
+        let mut pages = items.ready_chunks(MAX_PAGE_SIZE as usize);
+        let mut headers = None;
+        let mut listed = Vec::new();
+        let mut result = Ok(());
+        while let Some(page) = pages.next().await {
+            let mut values = Vec::with_capacity(page.len());
+            for item in page {
+                match item.and_then(|item| Ok(serde_json::to_value(item)?)) {
+                    Ok(value) => values.push(value),
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                }
+            }
+            if !values.is_empty() {
+                let rendered = self.render_page(&values, &mut headers)?;
+                out.write_all(rendered.as_bytes())?;
+                out.flush()?;
+            }
+            if resource.is_some() {
+                let room = cache::MAX_CACHED_IDS.saturating_sub(listed.len());
+                listed.extend(values.into_iter().take(room));
+            }
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Some(resource) = resource {
+            self.remember(resource, &listed);
+        }
+        result
+    }
+
+    /// Renders one page of a streamed list in the `ndjson` or `csv` format
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Serialized items of the page
+    /// * `headers` - CSV columns, set from the first page, whose header row is
+    ///   only written then
+    ///
+    /// # Returns
+    ///
+    /// The rendered page, or an error if a CSV page has a field missing from the
+    /// columns, since its values would be dropped
+    fn render_page(&self, values: &[Value], headers: &mut Option<Vec<String>>) -> Result<String> {
+        let items: Vec<&Value> = values.iter().collect();
+        if self.format == OutputFormat::Ndjson {
+            let mut lines = String::new();
+            for item in items {
+                lines.push_str(&serde_json::to_string(item)?);
+                lines.push('\n');
+            }
+            return Ok(lines);
+        }
+
+        let first = headers.is_none();
+        let headers = headers.get_or_insert_with(|| column_headers(&items));
+        if self.columns.is_empty() {
+            if let Some(field) = column_headers(&items)
+                .into_iter()
+                .find(|field| !headers.contains(field))
+            {
+                bail!(
+                    "Column {} first appears after the CSV header was written: pass --columns to choose the columns",
+                    field
+                );
+            }
+        }
+        let rows = column_rows(&items, headers)
+            .into_iter()
+            .map(|row| row.into_iter().map(cell).collect())
+            .collect();
+        let (selected, rows) = select_columns(headers.clone(), rows, &self.columns)?;
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        if first {
+            writer.write_record(&selected)?;
+        }
+        for row in rows {
+            writer.write_record(row)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Records the IDs and names of listed resources for shell completion
+    ///
+    /// Does nothing unless an ID cache is set. Failing to write the cache is not
+    /// an error, since it only makes completion more helpful.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - Resource type, e.g. `broadcasts`
+    /// * `items` - Listed resources, whose `id` and `name` fields are cached
+    pub fn remember<T: Serialize>(&self, resource: &str, items: &[T]) {
+        if let Some(cache) = &self.id_cache {
+            let ids = items
+                .iter()
+                .filter_map(|item| cache::CachedId::from_value(&serde_json::to_value(item).ok()?))
+                .collect();
+            let _ = cache.remember(resource, ids);
+        }
+    }
+
+    /// Prints a single resource
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Resource to print, as a key/value table in the `table` format
+    pub fn item<T: Serialize + Tabled>(&self, item: &T) -> Result<()> {
+        if !self.is_human() {
+            return self.print_data(&serde_json::to_value(item)?);
+        }
+        let keys = T::headers().into_iter().map(|h| h.into_owned()).collect();
+        let values = item.fields().into_iter().map(|f| f.into_owned()).collect();
+        println!("{}", key_value_table(keys, values, &self.columns)?);
+        Ok(())
+    }
+
+    /// Prints a free-form JSON resource
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Resource to print, as a key/value table of its top-level
+    ///   fields in the `table` format
+    pub fn value(&self, value: &Value) -> Result<()> {
+        self.print_data(value)
+    }
+
+    /// Prints the result of a mutation
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Confirmation printed in the `table` format
+    /// * `result` - Data printed in every other format
+    pub fn message<T: Serialize>(&self, message: &str, result: &T) -> Result<()> {
+        if self.is_human() {
+            println!("{}", message);
+            return Ok(());
+        }
+        self.print_data(&serde_json::to_value(result)?)
+    }
+
+    /// Prints the result of deleting a resource
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Kind of resource deleted, e.g. "Domain"
+    /// * `id` - ID of the deleted resource
+    pub fn deleted(&self, kind: &str, id: &str) -> Result<()> {
+        self.message(
+            &format!("{} {} deleted successfully!", kind, id),
+            &json!({ "id": id, "deleted": true }),
+        )
+    }
+
+    /// Prints a resource created or updated by a mutation
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Confirmation printed before the resource in the `table` format
+    /// * `item` - Resource returned by the API
+    pub fn mutated<T: Serialize + Tabled>(&self, message: &str, item: &T) -> Result<()> {
+        if self.is_human() {
+            println!("{}", message);
+        }
+        self.item(item)
+    }
+
+    /// Prints serialized data in the selected format
+    fn print_data(&self, value: &Value) -> Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(self.render(value)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Renders serialized data in the selected format, after applying the query
+    ///
+    /// # Returns
+    ///
+    /// The rendered text, ending with a newline unless it is empty, or an error
+    /// if the query fails or a selected column does not exist
+    pub fn render(&self, value: &Value) -> Result<String> {
+        let queried;
+        let value = match &self.query {
+            Some(query) => {
+                queried = query.apply(value)?;
+                &queried
+            }
+            None => value,
+        };
+        let rendered = match self.format {
+            OutputFormat::Table => render_table(value, &self.columns)?,
+            OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(value)?),
+            OutputFormat::Ndjson => {
+                let mut lines = String::new();
+                for item in items(value) {
+                    lines.push_str(&serde_json::to_string(item)?);
+                    lines.push('\n');
+                }
+                lines
+            }
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            OutputFormat::Csv => render_csv(value, &self.columns)?,
+        };
+        Ok(rendered)
+    }
+}
+
+/// Keeps only the selected columns of a table, in the order they were selected
+///
+/// # Returns
+///
+/// The selected headers and rows, or an error naming the available columns if
+/// a selected column does not exist
+fn select_columns(
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    columns: &[String],
+) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    if columns.is_empty() {
+        return Ok((headers, rows));
+    }
+    let indexes = columns
+        .iter()
+        .map(|column| {
+            headers.iter().position(|h| h == column).ok_or_else(|| {
+                anyhow!(
+                    "Unknown column {} (available columns: {})",
+                    column,
+                    headers.join(", ")
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let rows = rows
+        .into_iter()
+        .map(|row| indexes.iter().map(|&i| row[i].clone()).collect())
+        .collect();
+    Ok((columns.to_vec(), rows))
+}
+
+/// Builds a table from headers and rows
+fn table(headers: Vec<String>, rows: Vec<Vec<String>>) -> Table {
+    let mut builder = Builder::default();
+    builder.set_header(headers);
+    for row in rows {
+        builder.push_record(row);
+    }
+    builder.build()
+}
+
+/// Builds a two-column table with one row per selected field
+fn key_value_table(keys: Vec<String>, values: Vec<String>, columns: &[String]) -> Result<Table> {
+    let (keys, rows) = select_columns(keys, vec![values], columns)?;
+    let values = rows.into_iter().next().unwrap_or_default();
+    let rows = keys.into_iter().zip(values).map(|(k, v)| vec![k, v]);
+    Ok(table(
+        vec!["field".to_string(), "value".to_string()],
+        rows.collect(),
+    ))
+}
+
+/// Returns the items of a list, or the value itself if it is not a list
+fn items(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        item => vec![item],
+    }
+}
+
+/// Splits data into a header row and one row per item
+///
+/// Columns appear in the order fields are first seen. Items that are not
+/// objects are placed in a `value` column.
+fn tabulate(value: &Value) -> (Vec<String>, Vec<Vec<Option<&Value>>>) {
+    let items = items(value);
+    let headers = column_headers(&items);
+    let rows = column_rows(&items, &headers);
+    (headers, rows)
+}
+
+/// Returns the fields of the items, in the order they are first seen
+fn column_headers(items: &[&Value]) -> Vec<String> {
+    let mut headers: Vec<String> = Vec::new();
+    for item in items {
+        let keys: Vec<&String> = match item {
+            Value::Object(fields) => fields.keys().collect(),
+            _ => Vec::new(),
+        };
+        for key in keys {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    if items.iter().any(|item| !item.is_object()) {
+        headers.push("value".to_string());
+    }
+    headers
+}
+
+/// Returns one row per item, with the item's value for each header
+fn column_rows<'a>(items: &[&'a Value], headers: &[String]) -> Vec<Vec<Option<&'a Value>>> {
+    items
+        .iter()
+        .map(|item| {
+            headers
+                .iter()
+                .map(|header| match item {
+                    Value::Object(fields) => fields.get(header),
+                    scalar if header == "value" => Some(*scalar),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Renders free-form data for humans
+///
+/// Lists of objects become tables, objects become key/value tables, and
+/// scalars, or lists of scalars, are printed one per line.
+fn render_table(value: &Value, columns: &[String]) -> Result<String> {
+    let rendered = match value {
+        Value::Null => String::new(),
+        Value::Array(items) if items.is_empty() => "No items found.\n".to_string(),
+        Value::Array(items) if !items.iter().any(Value::is_object) => items
+            .iter()
+            .map(|item| format!("{}\n", display::json(item)))
+            .collect(),
+        Value::Array(_) => {
+            let (headers, rows) = tabulate(value);
+            let rows = rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|v| display::json(v.unwrap_or(&Value::Null)))
+                        .collect()
+                })
+                .collect();
+            let (headers, rows) = select_columns(headers, rows, columns)?;
+            format!("{}\n", table(headers, rows))
+        }
+        Value::Object(fields) => {
+            let keys = fields.keys().cloned().collect();
+            let values = fields.values().map(display::json).collect();
+            format!("{}\n", key_value_table(keys, values, columns)?)
+        }
+        scalar => format!("{}\n", display::json(scalar)),
+    };
+    Ok(rendered)
+}
+
+/// Renders data as CSV, with one row per item and one column per field
+///
+/// Nested objects and arrays are written as compact JSON, and null values as
+/// empty cells.
+fn render_csv(value: &Value, columns: &[String]) -> Result<String> {
+    if matches!(value, Value::Array(items) if items.is_empty()) {
+        return Ok(String::new());
+    }
+    let (headers, rows) = tabulate(value);
+    let rows = rows
+        .into_iter()
+        .map(|row| row.into_iter().map(cell).collect())
+        .collect();
+    let (headers, rows) = select_columns(headers, rows, columns)?;
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&headers)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Formats a field as a single CSV cell
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
 /// Prints a vector of items as a formatted table
 ///
 /// This function takes a vector of items that implement the Tabled trait and
@@ -59,7 +582,6 @@ mod tests {
         // Capture stdout would be complex, so we just ensure the function runs without error
         // by calling it with a sample of items that implement Tabled
         print_table(items);
-        assert!(true); // Basic assertion to satisfy test
     }
 
     #[test]
@@ -68,6 +590,207 @@ mod tests {
 
         // Capture stdout would be complex, so we just ensure the function runs without error
         print_table(items);
-        assert!(true); // Basic assertion to satisfy test
+    }
+
+    /// Returns the content rows of a rendered table
+    fn table_rows(table: &str) -> Vec<&str> {
+        table.lines().filter(|l| l.starts_with('|')).collect()
+    }
+
+    #[test]
+    fn test_key_value_table() {
+        let table = key_value_table(
+            vec!["id".to_string(), "name".to_string()],
+            vec!["seg_1".to_string(), "Newsletter".to_string()],
+            &[],
+        )
+        .unwrap()
+        .to_string();
+        assert_eq!(
+            table_rows(&table),
+            [
+                "| field | value      |",
+                "| id    | seg_1      |",
+                "| name  | Newsletter |"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columns_select_and_reorder() {
+        let output = Output {
+            columns: vec!["status".to_string(), "id".to_string()],
+            ..Default::default()
+        };
+        let value = json!([
+            {"id": "d1", "name": "a.com", "status": "verified"},
+            {"id": "d2", "name": "b.com", "status": "not_started"}
+        ]);
+        assert_eq!(
+            table_rows(&output.render(&value).unwrap()),
+            [
+                "| status      | id |",
+                "| verified    | d1 |",
+                "| not_started | d2 |"
+            ]
+        );
+
+        let csv = Output {
+            format: OutputFormat::Csv,
+            ..output.clone()
+        };
+        assert_eq!(
+            csv.render(&value).unwrap(),
+            "status,id\nverified,d1\nnot_started,d2\n"
+        );
+
+        let unknown = Output {
+            columns: vec!["region".to_string()],
+            ..Default::default()
+        };
+        let err = unknown.render(&value).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Unknown column region (available columns: id, name, status)"
+        );
+    }
+
+    #[test]
+    fn test_streamed_pages_share_one_csv_header() {
+        let output = Output::new(OutputFormat::Csv);
+        let mut headers = None;
+        let first = output
+            .render_page(
+                &[json!({"id": "d1", "name": "a.com"}), json!({"id": "d2"})],
+                &mut headers,
+            )
+            .unwrap();
+        let second = output
+            .render_page(&[json!({"name": "c.com", "id": "d3"})], &mut headers)
+            .unwrap();
+        assert_eq!(first, "id,name\nd1,a.com\nd2,\n");
+        assert_eq!(second, "d3,c.com\n");
+
+        // A field missing from the header row is not silently dropped
+        let err = output
+            .render_page(&[json!({"id": "d4", "status": "verified"})], &mut headers)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Column status first appears"));
+        let output = Output {
+            format: OutputFormat::Csv,
+            columns: vec!["id".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            output
+                .render_page(&[json!({"id": "d4", "status": "verified"})], &mut headers)
+                .unwrap(),
+            "d4\n"
+        );
+
+        let ndjson = Output::new(OutputFormat::Ndjson);
+        assert_eq!(
+            ndjson
+                .render_page(&[json!({"id": "d1"}), json!({"id": "d2"})], &mut None)
+                .unwrap(),
+            "{\"id\":\"d1\"}\n{\"id\":\"d2\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streamed_items_are_written_without_waiting_for_more() {
+        let output = Output::new(OutputFormat::Ndjson);
+        let page = || futures::stream::iter([json!({"id": "d1"}), json!({"id": "d2"})]).map(Ok);
+
+        // The next page never arrives, yet the first one is written
+        let mut out = Vec::new();
+        let items = page().chain(futures::stream::pending());
+        let pending = output.write_stream(items, None, &mut out);
+        let timeout = tokio::time::timeout(std::time::Duration::from_millis(50), pending);
+        assert!(timeout.await.is_err());
+        assert_eq!(out, b"{\"id\":\"d1\"}\n{\"id\":\"d2\"}\n");
+
+        // Items received before an error are written before it is returned
+        let mut out = Vec::new();
+        let items = page().chain(futures::stream::iter([Err(anyhow!("page 2 failed"))]));
+        let err = output
+            .write_stream(items, None, &mut out)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "page 2 failed");
+        assert_eq!(out, b"{\"id\":\"d1\"}\n{\"id\":\"d2\"}\n");
+    }
+
+    #[test]
+    fn test_query_applies_before_rendering() {
+        let value = json!([
+            {"id": "d1", "name": "a.com", "status": "verified"},
+            {"id": "d2", "name": "b.com", "status": "not_started"}
+        ]);
+        let output = |format| Output {
+            format,
+            query: Some(Query::parse("[?status=='not_started'].name").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            output(OutputFormat::Table).render(&value).unwrap(),
+            "b.com\n"
+        );
+        assert_eq!(
+            output(OutputFormat::Ndjson).render(&value).unwrap(),
+            "\"b.com\"\n"
+        );
+        let output = Output {
+            query: Some(Query::parse("[0]").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            table_rows(&output.render(&value).unwrap())[1],
+            "| id     | d1       |"
+        );
+    }
+
+    fn render(format: OutputFormat, value: Value) -> String {
+        Output::new(format).render(&value).unwrap()
+    }
+
+    #[test]
+    fn test_render_json_and_ndjson() {
+        let value = json!([{"id": "a", "to": ["x@y.z"]}, {"id": "b", "to": []}]);
+        assert_eq!(
+            serde_json::from_str::<Value>(&render(OutputFormat::Json, value.clone())).unwrap(),
+            value
+        );
+        assert_eq!(
+            render(OutputFormat::Ndjson, value),
+            "{\"id\":\"a\",\"to\":[\"x@y.z\"]}\n{\"id\":\"b\",\"to\":[]}\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Ndjson, json!({"id": "a"})),
+            "{\"id\":\"a\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let yaml = render(OutputFormat::Yaml, json!({"id": "a", "deleted": true}));
+        assert_eq!(yaml, "id: a\ndeleted: true\n");
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let csv = render(
+            OutputFormat::Csv,
+            json!([
+                {"id": "a", "name": "Hello, world", "to": ["x@y.z"]},
+                {"id": "b", "name": null, "extra": 3}
+            ]),
+        );
+        assert_eq!(
+            csv,
+            "id,name,to,extra\na,\"Hello, world\",\"[\"\"x@y.z\"\"]\",\nb,,,3\n"
+        );
+        assert_eq!(render(OutputFormat::Csv, json!([])), "");
+        assert_eq!(render(OutputFormat::Csv, json!("ok")), "value\nok\n");
     }
 }
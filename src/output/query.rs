@@ -0,0 +1,952 @@
+//! # Query Module
+//!
+//! This module implements the subset of [JMESPath](https://jmespath.org) used by
+//! `--query` to filter and project command results client-side:
+//!
+//! - fields, sub-expressions and the current node: `data.name`, `"quoted key"`, `@`
+//! - indexes and slices: `[0]`, `[-1]`, `[1:3]`, `[::2]`
+//! - projections: `[*].id`, `*.status`, `[].to`, `[?status=='verified'].name`
+//! - comparisons and logic: `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&`, `||`, `!`
+//! - literals: `'raw string'` and `` `JSON` ``
+//! - multi-selects: `[id, name]`, `{id: id, domain: name}`
+//! - pipes: `[?region=='eu-west-1'] | [0]`
+//! - functions: `length`, `contains`, `starts_with`, `ends_with`, `join`,
+//!   `keys`, `values`, `sort`, `to_string`, `to_number`
+//!
+//! Other JMESPath features are not supported and are rejected with an error:
+//! expression references (`&name`) and the functions taking them (`sort_by`,
+//! `max_by`, `min_by`, `map`), the remaining built-in functions (`abs`, `avg`,
+//! `ceil`, `floor`, `max`, `min`, `sum`, `merge`, `not_null`, `reverse`,
+//! `to_array`, `type`), and a slice step of `0`.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+/// Parsed `--query` expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// Source of the expression, for error messages
+    source: String,
+    /// Parsed expression
+    expr: Expr,
+}
+
+impl Query {
+    /// Parses a query expression
+    ///
+    /// # Returns
+    ///
+    /// The parsed query, or an error describing the first syntax error
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source).map_err(|e| anyhow!("Invalid query {:?}: {}", source, e))?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser
+            .expression(0)
+            .and_then(|expr| match parser.peek() {
+                Token::Eof => Ok(expr),
+                token => Err(format!("unexpected {:?}", token)),
+            })
+            .map_err(|e| anyhow!("Invalid query {:?}: {}", source, e))?;
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Evaluates the query against a JSON document
+    ///
+    /// # Returns
+    ///
+    /// The selected data, `null` if nothing matches, or an error if a function
+    /// is called with invalid arguments
+    pub fn apply(&self, value: &Value) -> Result<Value> {
+        eval(&self.expr, value).map_err(|e| anyhow!("Query {:?} failed: {}", self.source, e))
+    }
+}
+
+/// Lexical token of a query
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    QuotedIdentifier(String),
+    Number(i64),
+    Literal(Value),
+    Dot,
+    Star,
+    Flatten,
+    Filter,
+    Lbracket,
+    Rbracket,
+    Lbrace,
+    Rbrace,
+    Lparen,
+    Rparen,
+    Comma,
+    Colon,
+    Pipe,
+    Or,
+    And,
+    Not,
+    At,
+    Comparator(Comparator),
+    Eof,
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Token {
+    /// Left binding power of the token, as defined by the JMESPath grammar
+    fn lbp(&self) -> u8 {
+        match self {
+            Token::Pipe => 1,
+            Token::Or => 2,
+            Token::And => 3,
+            Token::Comparator(_) => 5,
+            Token::Flatten => 9,
+            Token::Star => 20,
+            Token::Filter => 21,
+            Token::Dot => 40,
+            Token::Not => 45,
+            Token::Lbrace => 50,
+            Token::Lbracket => 55,
+            Token::Lparen => 60,
+            _ => 0,
+        }
+    }
+}
+
+/// Splits a query into tokens
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        i += 1;
+        let token = match c {
+            ' ' | '\t' | '\n' | '\r' => continue,
+            '.' => Token::Dot,
+            '*' => Token::Star,
+            ']' => Token::Rbracket,
+            '{' => Token::Lbrace,
+            '}' => Token::Rbrace,
+            '(' => Token::Lparen,
+            ')' => Token::Rparen,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '@' => Token::At,
+            '[' => match next {
+                Some(']') => {
+                    i += 1;
+                    Token::Flatten
+                }
+                Some('?') => {
+                    i += 1;
+                    Token::Filter
+                }
+                _ => Token::Lbracket,
+            },
+            '|' if next == Some('|') => {
+                i += 1;
+                Token::Or
+            }
+            '|' => Token::Pipe,
+            '&' if next == Some('&') => {
+                i += 1;
+                Token::And
+            }
+            '&' => return Err("expression references (&) are not supported".to_string()),
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::Comparator(Comparator::Ne)
+            }
+            '!' => Token::Not,
+            '=' if next == Some('=') => {
+                i += 1;
+                Token::Comparator(Comparator::Eq)
+            }
+            '<' | '>' => {
+                let or_equal = next == Some('=');
+                if or_equal {
+                    i += 1;
+                }
+                Token::Comparator(match (c, or_equal) {
+                    ('<', false) => Comparator::Lt,
+                    ('<', true) => Comparator::Le,
+                    ('>', false) => Comparator::Gt,
+                    _ => Comparator::Ge,
+                })
+            }
+            '\'' => {
+                let (text, end) = delimited(&chars, i, '\'')?;
+                i = end;
+                Token::Literal(Value::String(text.replace("\\'", "'")))
+            }
+            '`' => {
+                let (text, end) = delimited(&chars, i, '`')?;
+                i = end;
+                let value = serde_json::from_str(text.replace("\\`", "`").trim())
+                    .map_err(|e| format!("invalid JSON literal `{}`: {}", text, e))?;
+                Token::Literal(value)
+            }
+            '"' => {
+                let (text, end) = delimited(&chars, i, '"')?;
+                i = end;
+                let name: String = serde_json::from_str(&format!("\"{}\"", text))
+                    .map_err(|e| format!("invalid quoted identifier \"{}\": {}", text, e))?;
+                Token::QuotedIdentifier(name)
+            }
+            '-' | '0'..='9' => {
+                let start = i - 1;
+                while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                Token::Number(
+                    text.parse()
+                        .map_err(|_| format!("invalid number {}", text))?,
+                )
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i - 1;
+                while chars
+                    .get(i)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    i += 1;
+                }
+                Token::Identifier(chars[start..i].iter().collect())
+            }
+            c => return Err(format!("unexpected character {:?}", c)),
+        };
+        tokens.push(token);
+    }
+    tokens.push(Token::Eof);
+    Ok(tokens)
+}
+
+/// Reads text up to an unescaped closing delimiter
+///
+/// # Returns
+///
+/// The raw text, still containing escapes, and the position after the delimiter
+fn delimited(
+    chars: &[char],
+    start: usize,
+    delimiter: char,
+) -> std::result::Result<(String, usize), String> {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == delimiter => return Ok((chars[start..i].iter().collect(), i + 1)),
+            _ => i += 1,
+        }
+    }
+    Err(format!("unterminated {}", delimiter))
+}
+
+/// Node of a parsed query
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Current,
+    Field(String),
+    Literal(Value),
+    Subexpr(Box<Expr>, Box<Expr>),
+    Index(Box<Expr>, i64),
+    Slice(Box<Expr>, [Option<i64>; 3]),
+    /// Applies the right side to every element of the left side, dropping nulls
+    Projection(Box<Expr>, Box<Expr>),
+    ObjectValues(Box<Expr>),
+    Flatten(Box<Expr>),
+    /// Evaluates to the right side if the condition holds, to null otherwise
+    Condition(Box<Expr>, Box<Expr>),
+    MultiList(Vec<Expr>),
+    MultiHash(Vec<(String, Expr)>),
+    Compare(Comparator, Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Function(String, Vec<Expr>),
+}
+
+/// Binding power below which a token ends a projection
+const PROJECTION_STOP: u8 = 10;
+
+/// Top-down operator precedence parser
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+type ParseResult = std::result::Result<Expr, String>;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        self.tokens.get(self.pos + offset).unwrap_or(&Token::Eof)
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> std::result::Result<(), String> {
+        match self.advance() {
+            token if token == expected => Ok(()),
+            token => Err(format!("expected {:?}, found {:?}", expected, token)),
+        }
+    }
+
+    fn expression(&mut self, rbp: u8) -> ParseResult {
+        let mut left = self.nud()?;
+        while rbp < self.peek().lbp() {
+            left = self.led(left)?;
+        }
+        Ok(left)
+    }
+
+    /// Parses a token at the start of an expression
+    fn nud(&mut self) -> ParseResult {
+        match self.advance() {
+            Token::At => Ok(Expr::Current),
+            Token::Identifier(name) => Ok(Expr::Field(name)),
+            Token::QuotedIdentifier(name) => {
+                if *self.peek() == Token::Lparen {
+                    return Err("quoted identifiers cannot be called".to_string());
+                }
+                Ok(Expr::Field(name))
+            }
+            Token::Literal(value) => Ok(Expr::Literal(value)),
+            Token::Star => self.object_values(Expr::Current),
+            Token::Flatten => self.flatten(Expr::Current),
+            Token::Filter => self.filter(Expr::Current),
+            Token::Lbracket => match self.peek() {
+                Token::Number(_) | Token::Colon => self.index(Expr::Current),
+                Token::Star if *self.peek_at(1) == Token::Rbracket => {
+                    self.advance();
+                    self.advance();
+                    self.wildcard_index(Expr::Current)
+                }
+                _ => self.multi_list(),
+            },
+            Token::Lbrace => self.multi_hash(),
+            Token::Not => Ok(Expr::Not(Box::new(self.expression(Token::Not.lbp())?))),
+            Token::Lparen => {
+                let expr = self.expression(0)?;
+                self.expect(Token::Rparen)?;
+                Ok(expr)
+            }
+            token => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Parses a token following a complete expression
+    fn led(&mut self, left: Expr) -> ParseResult {
+        let token = self.advance();
+        match token {
+            Token::Dot => {
+                if *self.peek() == Token::Star {
+                    self.advance();
+                    return self.object_values(left);
+                }
+                let right = self.dot_rhs(Token::Dot.lbp())?;
+                Ok(Expr::Subexpr(Box::new(left), Box::new(right)))
+            }
+            Token::Lbracket => match self.peek() {
+                Token::Number(_) | Token::Colon => self.index(left),
+                Token::Star => {
+                    self.advance();
+                    self.expect(Token::Rbracket)?;
+                    self.wildcard_index(left)
+                }
+                token => Err(format!("unexpected {:?} after [", token)),
+            },
+            Token::Flatten => self.flatten(left),
+            Token::Filter => self.filter(left),
+            Token::Pipe => {
+                let right = self.expression(token.lbp())?;
+                Ok(Expr::Pipe(Box::new(left), Box::new(right)))
+            }
+            Token::Or => {
+                let right = self.expression(token.lbp())?;
+                Ok(Expr::Or(Box::new(left), Box::new(right)))
+            }
+            Token::And => {
+                let right = self.expression(token.lbp())?;
+                Ok(Expr::And(Box::new(left), Box::new(right)))
+            }
+            Token::Comparator(comparator) => {
+                let right = self.expression(token.lbp())?;
+                Ok(Expr::Compare(comparator, Box::new(left), Box::new(right)))
+            }
+            Token::Lparen => match left {
+                Expr::Field(name) => self.function(name),
+                _ => Err("only functions can be called".to_string()),
+            },
+            token => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Parses what follows a dot: a field, a multi-select or a function call
+    fn dot_rhs(&mut self, lbp: u8) -> ParseResult {
+        match self.peek() {
+            Token::Lbracket => {
+                self.advance();
+                self.multi_list()
+            }
+            Token::Lbrace => {
+                self.advance();
+                self.multi_hash()
+            }
+            Token::Identifier(_) | Token::QuotedIdentifier(_) | Token::At => self.expression(lbp),
+            token => Err(format!("unexpected {:?} after .", token)),
+        }
+    }
+
+    /// Parses the expression applied to each element of a projection
+    fn projection_rhs(&mut self, lbp: u8) -> ParseResult {
+        match self.peek() {
+            Token::Dot => {
+                self.advance();
+                self.dot_rhs(lbp)
+            }
+            Token::Lbracket | Token::Filter | Token::Flatten => self.expression(lbp),
+            token if token.lbp() < PROJECTION_STOP => Ok(Expr::Current),
+            token => Err(format!("unexpected {:?} after projection", token)),
+        }
+    }
+
+    fn index(&mut self, left: Expr) -> ParseResult {
+        let mut parts = [None; 3];
+        let mut part = 0;
+        loop {
+            match self.advance() {
+                Token::Number(n) if parts[part].is_none() => parts[part] = Some(n),
+                Token::Colon if part < 2 => part += 1,
+                Token::Rbracket => break,
+                token => return Err(format!("unexpected {:?} in index", token)),
+            }
+        }
+        if part == 0 {
+            let index = parts[0].ok_or("empty index")?;
+            return Ok(Expr::Index(Box::new(left), index));
+        }
+        if parts[2] == Some(0) {
+            return Err("slice step cannot be 0".to_string());
+        }
+        let rhs = self.projection_rhs(Token::Star.lbp())?;
+        Ok(Expr::Projection(
+            Box::new(Expr::Slice(Box::new(left), parts)),
+            Box::new(rhs),
+        ))
+    }
+
+    fn wildcard_index(&mut self, left: Expr) -> ParseResult {
+        let rhs = self.projection_rhs(Token::Star.lbp())?;
+        Ok(Expr::Projection(Box::new(left), Box::new(rhs)))
+    }
+
+    fn object_values(&mut self, left: Expr) -> ParseResult {
+        let rhs = self.projection_rhs(Token::Star.lbp())?;
+        Ok(Expr::Projection(
+            Box::new(Expr::ObjectValues(Box::new(left))),
+            Box::new(rhs),
+        ))
+    }
+
+    fn flatten(&mut self, left: Expr) -> ParseResult {
+        let rhs = self.projection_rhs(Token::Flatten.lbp())?;
+        Ok(Expr::Projection(
+            Box::new(Expr::Flatten(Box::new(left))),
+            Box::new(rhs),
+        ))
+    }
+
+    fn filter(&mut self, left: Expr) -> ParseResult {
+        let condition = self.expression(0)?;
+        self.expect(Token::Rbracket)?;
+        let rhs = self.projection_rhs(Token::Filter.lbp())?;
+        Ok(Expr::Projection(
+            Box::new(left),
+            Box::new(Expr::Condition(Box::new(condition), Box::new(rhs))),
+        ))
+    }
+
+    fn multi_list(&mut self) -> ParseResult {
+        let mut items = Vec::new();
+        loop {
+            items.push(self.expression(0)?);
+            match self.advance() {
+                Token::Comma => continue,
+                Token::Rbracket => return Ok(Expr::MultiList(items)),
+                token => return Err(format!("unexpected {:?} in list", token)),
+            }
+        }
+    }
+
+    fn multi_hash(&mut self) -> ParseResult {
+        let mut items = Vec::new();
+        loop {
+            let key = match self.advance() {
+                Token::Identifier(key) | Token::QuotedIdentifier(key) => key,
+                token => return Err(format!("expected a key, found {:?}", token)),
+            };
+            self.expect(Token::Colon)?;
+            items.push((key, self.expression(0)?));
+            match self.advance() {
+                Token::Comma => continue,
+                Token::Rbrace => return Ok(Expr::MultiHash(items)),
+                token => return Err(format!("unexpected {:?} in object", token)),
+            }
+        }
+    }
+
+    fn function(&mut self, name: String) -> ParseResult {
+        let mut args = Vec::new();
+        if *self.peek() == Token::Rparen {
+            self.advance();
+            return Ok(Expr::Function(name, args));
+        }
+        loop {
+            args.push(self.expression(0)?);
+            match self.advance() {
+                Token::Comma => continue,
+                Token::Rparen => return Ok(Expr::Function(name, args)),
+                token => return Err(format!("unexpected {:?} in arguments", token)),
+            }
+        }
+    }
+}
+
+/// Returns whether a value is truthy in the JMESPath sense
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+        _ => true,
+    }
+}
+
+/// Resolves a possibly negative index into a list of `len` elements
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if index < 0 { len + index } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Selects the elements of a slice, following Python semantics
+fn slice(items: &[Value], [start, stop, step]: [Option<i64>; 3]) -> Result<Vec<Value>> {
+    let len = items.len() as i64;
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        bail!("slice step cannot be 0");
+    }
+    let clamp = |bound: i64| {
+        let bound = if bound < 0 { bound + len } else { bound };
+        if step > 0 {
+            bound.clamp(0, len)
+        } else {
+            bound.clamp(-1, len - 1)
+        }
+    };
+    let (mut i, stop) = if step > 0 {
+        (start.map_or(0, clamp), stop.map_or(len, clamp))
+    } else {
+        (start.map_or(len - 1, clamp), stop.map_or(-1, clamp))
+    };
+    let mut selected = Vec::new();
+    while (step > 0 && i < stop) || (step < 0 && i > stop) {
+        selected.push(items[i as usize].clone());
+        // A step past the end of the index range ends the slice
+        let Some(next) = i.checked_add(step) else {
+            break;
+        };
+        i = next;
+    }
+    Ok(selected)
+}
+
+/// Compares two values, if they are both numbers or both strings
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+/// Evaluates an expression against a JSON value
+fn eval(expr: &Expr, value: &Value) -> Result<Value> {
+    Ok(match expr {
+        Expr::Current => value.clone(),
+        Expr::Field(name) => value.get(name).cloned().unwrap_or(Value::Null),
+        Expr::Literal(literal) => literal.clone(),
+        Expr::Subexpr(left, right) | Expr::Pipe(left, right) => eval(right, &eval(left, value)?)?,
+        Expr::Index(left, index) => match eval(left, value)? {
+            Value::Array(items) => resolve_index(*index, items.len())
+                .map(|i| items[i].clone())
+                .unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Expr::Slice(left, parts) => match eval(left, value)? {
+            Value::Array(items) => Value::Array(slice(&items, *parts)?),
+            _ => Value::Null,
+        },
+        Expr::Projection(left, right) => match eval(left, value)? {
+            Value::Array(items) => {
+                let mut projected = Vec::new();
+                for item in &items {
+                    let result = eval(right, item)?;
+                    if !result.is_null() {
+                        projected.push(result);
+                    }
+                }
+                Value::Array(projected)
+            }
+            _ => Value::Null,
+        },
+        Expr::ObjectValues(left) => match eval(left, value)? {
+            Value::Object(fields) => Value::Array(fields.into_iter().map(|(_, v)| v).collect()),
+            _ => Value::Null,
+        },
+        Expr::Flatten(left) => match eval(left, value)? {
+            Value::Array(items) => {
+                let mut flattened = Vec::new();
+                for item in items {
+                    match item {
+                        Value::Array(inner) => flattened.extend(inner),
+                        item => flattened.push(item),
+                    }
+                }
+                Value::Array(flattened)
+            }
+            _ => Value::Null,
+        },
+        Expr::Condition(condition, right) => {
+            if is_truthy(&eval(condition, value)?) {
+                eval(right, value)?
+            } else {
+                Value::Null
+            }
+        }
+        Expr::MultiList(items) => {
+            if value.is_null() {
+                return Ok(Value::Null);
+            }
+            Value::Array(
+                items
+                    .iter()
+                    .map(|item| eval(item, value))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        Expr::MultiHash(items) => {
+            if value.is_null() {
+                return Ok(Value::Null);
+            }
+            let mut fields = Map::new();
+            for (key, item) in items {
+                fields.insert(key.clone(), eval(item, value)?);
+            }
+            Value::Object(fields)
+        }
+        Expr::Compare(comparator, left, right) => {
+            let (left, right) = (eval(left, value)?, eval(right, value)?);
+            match comparator {
+                Comparator::Eq => Value::Bool(left == right),
+                Comparator::Ne => Value::Bool(left != right),
+                ordering => match compare(&left, &right) {
+                    Some(order) => Value::Bool(match ordering {
+                        Comparator::Lt => order.is_lt(),
+                        Comparator::Le => order.is_le(),
+                        Comparator::Gt => order.is_gt(),
+                        _ => order.is_ge(),
+                    }),
+                    None => Value::Null,
+                },
+            }
+        }
+        Expr::Or(left, right) => {
+            let left = eval(left, value)?;
+            if is_truthy(&left) {
+                left
+            } else {
+                eval(right, value)?
+            }
+        }
+        Expr::And(left, right) => {
+            let left = eval(left, value)?;
+            if is_truthy(&left) {
+                eval(right, value)?
+            } else {
+                left
+            }
+        }
+        Expr::Not(expr) => Value::Bool(!is_truthy(&eval(expr, value)?)),
+        Expr::Function(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, value))
+                .collect::<Result<Vec<_>>>()?;
+            call(name, &args)?
+        }
+    })
+}
+
+/// Names of the supported built-in functions
+const FUNCTIONS: &[&str] = &[
+    "length",
+    "contains",
+    "starts_with",
+    "ends_with",
+    "join",
+    "keys",
+    "values",
+    "sort",
+    "to_string",
+    "to_number",
+];
+
+/// Calls a built-in function
+fn call(name: &str, args: &[Value]) -> Result<Value> {
+    let arity = |expected: usize| {
+        if args.len() == expected {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{}() takes {} argument(s), {} given",
+                name,
+                expected,
+                args.len()
+            ))
+        }
+    };
+    let string = |value: &Value| match value {
+        Value::String(s) => Ok(s.clone()),
+        other => Err(anyhow!("{}() expects a string, got {}", name, other)),
+    };
+
+    Ok(match name {
+        "length" => {
+            arity(1)?;
+            let length = match &args[0] {
+                Value::String(s) => s.chars().count(),
+                Value::Array(items) => items.len(),
+                Value::Object(fields) => fields.len(),
+                other => bail!("length() expects a string, array or object, got {}", other),
+            };
+            Value::from(length)
+        }
+        "contains" => {
+            arity(2)?;
+            let found = match &args[0] {
+                Value::Array(items) => items.contains(&args[1]),
+                Value::String(s) => s.contains(&string(&args[1])?),
+                other => bail!("contains() expects a string or array, got {}", other),
+            };
+            Value::Bool(found)
+        }
+        "starts_with" => {
+            arity(2)?;
+            Value::Bool(string(&args[0])?.starts_with(&string(&args[1])?))
+        }
+        "ends_with" => {
+            arity(2)?;
+            Value::Bool(string(&args[0])?.ends_with(&string(&args[1])?))
+        }
+        "join" => {
+            arity(2)?;
+            let separator = string(&args[0])?;
+            let items = match &args[1] {
+                Value::Array(items) => items.iter().map(string).collect::<Result<Vec<_>>>()?,
+                other => bail!("join() expects an array of strings, got {}", other),
+            };
+            Value::String(items.join(&separator))
+        }
+        "keys" | "values" => {
+            arity(1)?;
+            match &args[0] {
+                Value::Object(fields) if name == "keys" => {
+                    fields.keys().cloned().map(Value::String).collect()
+                }
+                Value::Object(fields) => fields.values().cloned().collect(),
+                other => bail!("{}() expects an object, got {}", name, other),
+            }
+        }
+        "sort" => {
+            arity(1)?;
+            match &args[0] {
+                Value::Array(items) => {
+                    let mut items = items.clone();
+                    items.sort_by(|a, b| compare(a, b).unwrap_or(Ordering::Equal));
+                    Value::Array(items)
+                }
+                other => bail!("sort() expects an array, got {}", other),
+            }
+        }
+        "to_string" => {
+            arity(1)?;
+            match &args[0] {
+                Value::String(s) => Value::String(s.clone()),
+                other => Value::String(other.to_string()),
+            }
+        }
+        "to_number" => {
+            arity(1)?;
+            match &args[0] {
+                Value::Number(n) => Value::Number(n.clone()),
+                Value::String(s) => serde_json::from_str::<serde_json::Number>(s)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+                _ => Value::Null,
+            }
+        }
+        _ => bail!(
+            "unknown function {}(): only {} are supported",
+            name,
+            FUNCTIONS.join(", ")
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(source: &str, value: Value) -> Value {
+        Query::parse(source).unwrap().apply(&value).unwrap()
+    }
+
+    fn domains() -> Value {
+        json!([
+            {"id": "d1", "name": "a.com", "status": "verified", "region": "us-east-1", "tags": ["x"]},
+            {"id": "d2", "name": "b.com", "status": "not_started", "region": "eu-west-1", "tags": ["y", "z"]},
+            {"id": "d3", "name": "c.com", "status": "not_started", "region": "us-east-1", "tags": []}
+        ])
+    }
+
+    #[test]
+    fn test_filter_projection() {
+        assert_eq!(
+            query("[?status=='not_started'].name", domains()),
+            json!(["b.com", "c.com"])
+        );
+        assert_eq!(
+            query(
+                "[?status=='not_started' && region!='eu-west-1'].id",
+                domains()
+            ),
+            json!(["d3"])
+        );
+        assert_eq!(
+            query("[?!(status=='verified')] | length(@)", domains()),
+            json!(2)
+        );
+    }
+
+    #[test]
+    fn test_fields_indexes_and_slices() {
+        assert_eq!(query("[0].name", domains()), json!("a.com"));
+        assert_eq!(query("[-1].id", domains()), json!("d3"));
+        assert_eq!(query("[5].id", domains()), Value::Null);
+        assert_eq!(query("[1:].id", domains()), json!(["d2", "d3"]));
+        assert_eq!(query("[::-2].id", domains()), json!(["d3", "d1"]));
+        assert_eq!(
+            query("[1::9223372036854775807].id", domains()),
+            json!(["d2"])
+        );
+        assert_eq!(
+            query("[::-9223372036854775808].id", domains()),
+            json!(["d3"])
+        );
+        assert_eq!(
+            query("\"first name\"", json!({"first name": "Ada"})),
+            json!("Ada")
+        );
+        assert_eq!(query("a.b.c", json!({"a": {"b": {"c": 1}}})), json!(1));
+        assert_eq!(query("a.b.c", json!({"a": 1})), Value::Null);
+    }
+
+    #[test]
+    fn test_wildcards_and_flatten() {
+        assert_eq!(query("[*].id", domains()), json!(["d1", "d2", "d3"]));
+        assert_eq!(query("[].tags[]", domains()), json!(["x", "y", "z"]));
+        assert_eq!(query("*", json!({"a": 1, "b": 2})), json!([1, 2]));
+        assert_eq!(
+            query("*.status", json!({"a": {"status": "ok"}, "b": {}})),
+            json!(["ok"])
+        );
+    }
+
+    #[test]
+    fn test_multi_select_and_pipe() {
+        assert_eq!(
+            query("[*].[id, status] | [0]", domains()),
+            json!(["d1", "verified"])
+        );
+        assert_eq!(
+            query("[?region=='eu-west-1'].{id: id, domain: name}", domains()),
+            json!([{"id": "d2", "domain": "b.com"}])
+        );
+    }
+
+    #[test]
+    fn test_literals_comparisons_and_functions() {
+        let items = json!([{"n": 1}, {"n": 5}, {"n": 10}]);
+        assert_eq!(query("[?n > `2`].n", items.clone()), json!([5, 10]));
+        assert_eq!(query("[?n <= `5`].n", items), json!([1, 5]));
+        assert_eq!(query("[?contains(tags, 'y')].id", domains()), json!(["d2"]));
+        assert_eq!(
+            query("[?starts_with(name, 'c')].id", domains()),
+            json!(["d3"])
+        );
+        assert_eq!(query("join(', ', [*].id)", domains()), json!("d1, d2, d3"));
+        assert_eq!(
+            query("sort([*].region)", domains()),
+            json!(["eu-west-1", "us-east-1", "us-east-1"])
+        );
+        assert_eq!(query("missing || 'fallback'", json!({})), json!("fallback"));
+    }
+
+    #[test]
+    fn test_invalid_queries_are_rejected() {
+        for source in ["[?", "a.", "[0", "'open", "a b", "foo(", "{a}", "`{`"] {
+            assert!(Query::parse(source).is_err(), "{} should not parse", source);
+        }
+        let err = Query::parse("nope(@)")
+            .unwrap()
+            .apply(&json!({}))
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown function nope()"));
+        assert!(err.to_string().contains("only length, contains"));
+
+        let err = Query::parse("[::0]").unwrap_err().to_string();
+        assert!(err.contains("slice step cannot be 0"));
+        assert!(slice(&[json!(1)], [None, None, Some(0)]).is_err());
+        let err = Query::parse("sort_by(@, &name)").unwrap_err().to_string();
+        assert!(err.contains("expression references (&) are not supported"));
+    }
+}
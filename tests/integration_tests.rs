@@ -1,4 +1,68 @@
 use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+/// Returns a `resend` command isolated from the environment running the tests
+///
+/// Every `RESEND_*` variable is cleared, and both `HOME` and the working directory
+/// point at `home`, so that no profile, project file or ID cache outside the test's
+/// temporary directory is read or written. Tests set the variables they need.
+fn resend(home: &Path) -> Command {
+    let mut cmd = Command::new(std::env!("CARGO_BIN_EXE_resend"));
+    for (name, _) in std::env::vars_os() {
+        if name.to_string_lossy().starts_with("RESEND_") {
+            cmd.env_remove(name);
+        }
+    }
+    cmd.env("HOME", home).current_dir(home);
+    cmd
+}
+
+/// Starts a local stand-in server that answers a single request with `body`
+///
+/// Returns the base URL to point the CLI at and a receiver yielding the
+/// request line and headers that the server saw.
+fn serve_once(body: &'static str) -> (String, mpsc::Receiver<Vec<String>>) {
+    serve_once_with_status("200 OK", body)
+}
+
+/// Like `serve_once`, answering with the given HTTP status instead of 200 OK
+fn serve_once_with_status(
+    status: &'static str,
+    body: &'static str,
+) -> (String, mpsc::Receiver<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        tx.send(lines).unwrap();
+    });
+
+    (base_url, rx)
+}
 
 #[test]
 fn test_help_output() -> Result<(), Box<dyn std::error::Error>> {
@@ -62,3 +126,560 @@ fn test_domains_subcommand() -> Result<(), Box<dyn std::error::Error>> {
     assert!(stdout.contains("Manage domains"));
     Ok(())
 }
+
+#[test]
+fn test_base_url_flag_targets_local_server() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let (base_url, requests) = serve_once(
+        r#"{"data":[{"id":"dom_1","name":"local.test","created_at":"2023-01-01","status":"verified","region":"us-east-1"}]}"#,
+    );
+
+    let assert = resend(dir.path())
+        .env("RESEND_API_KEY", "re_test")
+        .args(["--base-url", &base_url, "domains", "list"])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("local.test"));
+
+    let lines = requests.recv()?;
+    assert_eq!(lines[0], "GET /domains HTTP/1.1");
+    assert!(lines
+        .iter()
+        .any(|l| l.eq_ignore_ascii_case("authorization: Bearer re_test")));
+    Ok(())
+}
+
+#[test]
+fn test_base_url_env_var_targets_local_server() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let (base_url, requests) = serve_once(r#"{"data":[]}"#);
+
+    resend(dir.path())
+        .env("RESEND_API_KEY", "re_test")
+        .env("RESEND_BASE_URL", &base_url)
+        .args(["api-keys", "list"])
+        .assert()
+        .success();
+
+    let lines = requests.recv()?;
+    assert_eq!(lines[0], "GET /api-keys HTTP/1.1");
+    Ok(())
+}
+
+#[test]
+fn test_invalid_timeouts_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    for timeout in ["--timeout=-1", "--timeout=inf", "--connect-timeout=NaN"] {
+        let assert = resend(dir.path())
+            .env("RESEND_API_KEY", "re_test")
+            .args([timeout, "domains", "list"])
+            .assert()
+            .failure();
+        let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+        assert!(
+            stderr.contains("non-negative number of seconds"),
+            "{}",
+            stderr
+        );
+        assert!(!stderr.contains("panicked"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_verbose_traces_requests_without_the_api_key() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let (base_url, _requests) = serve_once(r#"{"data":[]}"#);
+
+    let assert = resend(dir.path())
+        .env("RESEND_API_KEY", "re_secret")
+        .args(["--base-url", &base_url, "--debug", "segments", "list"])
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains(&format!("> GET {}/segments", base_url)));
+    assert!(stderr.contains("authorization: Bearer [REDACTED]"));
+    assert!(stderr.contains("< 200 OK"));
+    assert!(!stderr.contains("re_secret"));
+    Ok(())
+}
+
+#[test]
+fn test_cassette_record_then_replay_offline() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let cassette = dir.path().join("cassette.json");
+    let (base_url, _requests) = serve_once(
+        r#"{"data":[{"id":"seg_1","name":"Recorded segment","created_at":"2023-01-01"}]}"#,
+    );
+
+    resend(dir.path())
+        .env("RESEND_API_KEY", "re_secret")
+        .args(["--base-url", &base_url, "segments", "list"])
+        .arg("--cassette")
+        .arg(&cassette)
+        .args(["--cassette-mode", "record"])
+        .assert()
+        .success();
+    assert!(!std::fs::read_to_string(&cassette)?.contains("re_secret"));
+
+    // The server is gone and no API key is set: responses come from the cassette
+    let assert = resend(dir.path())
+        .env("RESEND_CASSETTE", &cassette)
+        .env("RESEND_CASSETTE_MODE", "replay")
+        .args(["segments", "list"])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("Recorded segment"));
+    // Only the recorded IDs are cached for completion, not the replayed ones
+    let caches: Vec<_> = std::fs::read_dir(dir.path().join(".resend-cli/cache"))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    let recorded = base_url.replace("http://", "").replace(':', "_");
+    assert_eq!(caches, vec![std::ffi::OsString::from(recorded)]);
+
+    resend(dir.path())
+        .env("RESEND_CASSETTE", &cassette)
+        .args(["domains", "list"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+/// Kills the mock server process when dropped, even if the test fails
+struct MockServerProcess(std::process::Child);
+
+impl Drop for MockServerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn test_mock_server_end_to_end() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let seed = dir.path().join("seed.json");
+    std::fs::write(
+        &seed,
+        r#"{"domains":[{"id":"dom_1","name":"seeded.test","created_at":"2023-01-01","status":"not_started","region":"us-east-1"}]}"#,
+    )?;
+
+    let mut server = MockServerProcess(
+        std::process::Command::new(std::env!("CARGO_BIN_EXE_resend"))
+            .args(["mock-server", "--port", "0", "--seed"])
+            .arg(&seed)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()?,
+    );
+    let mut line = String::new();
+    BufReader::new(server.0.stdout.take().unwrap()).read_line(&mut line)?;
+    let base_url = line
+        .trim()
+        .rsplit(' ')
+        .next()
+        .ok_or("no URL printed")?
+        .to_string();
+    assert!(base_url.starts_with("http://127.0.0.1:"));
+
+    let mock = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.env("RESEND_API_KEY", "re_test")
+            .args(["--base-url", &base_url])
+            .args(args);
+        cmd
+    };
+
+    mock(&["domains", "verify", "dom_1"]).assert().success();
+    let assert = mock(&["domains", "list"]).assert().success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("seeded.test"));
+    assert!(stdout.contains("verified"));
+
+    mock(&[
+        "emails",
+        "send",
+        "--from",
+        "hi@acme.com",
+        "--to",
+        "user@example.com",
+        "--subject",
+        "Hello",
+        "--text",
+        "Hi",
+    ])
+    .assert()
+    .success();
+    let assert = mock(&["emails", "list"]).assert().success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("Hello"));
+
+    mock(&["domains", "get", "missing"]).assert().failure();
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_prints_requests_without_sending() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let batch = dir.path().join("batch.json");
+    std::fs::write(
+        &batch,
+        r#"[{"from":"hi@acme.com","to":["a@example.com"],"subject":"First"},
+            {"from":"hi@acme.com","to":["b@example.com"],"subject":"Second"}]"#,
+    )?;
+
+    // No API key and an unreachable base URL: nothing may be sent
+    let dry_run = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.args(["--base-url", "http://127.0.0.1:9", "--dry-run"])
+            .args(args);
+        cmd
+    };
+
+    let assert = dry_run(&["emails", "send-batch"])
+        .arg(&batch)
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("[dry-run] POST /emails/batch"));
+    assert!(stderr.contains("\"subject\": \"Second\""));
+
+    let assert = dry_run(&["broadcasts", "send", "b_1"]).assert().success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("[dry-run] POST /broadcasts/b_1/send"));
+
+    // Templates are not fetched, so nothing is made up in their place
+    let assert = dry_run(&["emails", "send", "--to", "a@example.com"])
+        .args(["--template", "welcome", "--var", "FIRST_NAME=Ada"])
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("[dry-run] GET /templates/welcome"));
+    assert!(stderr.contains("required variables are not checked"));
+    assert!(stderr.contains("\"id\": \"welcome\""));
+    assert!(stderr.contains("\"FIRST_NAME\": \"Ada\""));
+    assert!(!stderr.contains("\"from\""));
+    assert!(!stderr.contains("\"subject\""));
+    Ok(())
+}
+
+#[test]
+fn test_output_formats_are_machine_readable() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let (base_url, _requests) =
+        serve_once(r#"{"data":[{"id":"seg_1","name":"Newsletter","created_at":"2023-01-01"}]}"#);
+
+    let assert = resend(dir.path())
+        .env("RESEND_API_KEY", "re_test")
+        .args([
+            "--base-url",
+            &base_url,
+            "--output",
+            "json",
+            "segments",
+            "list",
+        ])
+        .assert()
+        .success();
+    let segments: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout)?;
+    assert_eq!(segments[0]["name"], "Newsletter");
+
+    let assert = resend(dir.path())
+        .env("RESEND_OUTPUT", "csv")
+        .args(["--dry-run", "domains", "delete", "dom_1"])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert_eq!(stdout, "id,deleted\ndom_1,true\n");
+    Ok(())
+}
+
+#[test]
+fn test_query_and_columns_shape_list_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let body = r#"{"data":[
+        {"id":"dom_1","name":"live.test","created_at":"2023-01-01","status":"verified","region":"us-east-1"},
+        {"id":"dom_2","name":"new.test","created_at":"2023-01-02","status":"not_started","region":"eu-west-1"}
+    ]}"#;
+
+    let (base_url, _requests) = serve_once(body);
+    let assert = resend(dir.path())
+        .env("RESEND_API_KEY", "re_test")
+        .args(["--base-url", &base_url, "domains", "list"])
+        .args(["--query", "[?status=='not_started'].name"])
+        .assert()
+        .success();
+    assert_eq!(
+        std::str::from_utf8(&assert.get_output().stdout)?,
+        "new.test\n"
+    );
+
+    let (base_url, _requests) = serve_once(body);
+    let assert = resend(dir.path())
+        .env("RESEND_API_KEY", "re_test")
+        .args(["--base-url", &base_url, "domains", "list"])
+        .args(["--columns", "name,status"])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("| name      | status      |"));
+    assert!(!stdout.contains("dom_1"));
+    Ok(())
+}
+
+#[test]
+fn test_config_profiles_list_and_use() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let cli = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.args(args);
+        cmd
+    };
+
+    // A file written before profiles existed is read as the default profile
+    std::fs::create_dir_all(dir.path().join(".resend-cli"))?;
+    std::fs::write(
+        dir.path().join(".resend-cli").join("config.json"),
+        r#"{"api_key": "re_live_123456789"}"#,
+    )?;
+    cli(&[
+        "config",
+        "--profile",
+        "staging",
+        "--api-key",
+        "re_test_987654321",
+    ])
+    .args(["--from", "Staging <dev@acme.com>"])
+    .assert()
+    .success();
+
+    let assert = cli(&["-o", "json", "config", "list"]).assert().success();
+    let profiles: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout)?;
+    assert_eq!(profiles[0]["name"], "default");
+    assert_eq!(profiles[0]["current"], true);
+    assert_eq!(profiles[0]["api_key"], "re_...6789");
+    assert_eq!(profiles[1]["name"], "staging");
+    assert_eq!(profiles[1]["from"], "Staging <dev@acme.com>");
+
+    cli(&["config", "use", "production"]).assert().failure();
+    cli(&["config", "use", "staging"]).assert().success();
+
+    // The current profile's default sender is used when --from is omitted
+    let assert = cli(&["--dry-run", "emails", "send", "-t", "a@example.com"])
+        .args(["-s", "Hi", "--text", "Hello"])
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("\"from\": \"Staging <dev@acme.com>\""));
+
+    cli(&["--profile", "missing", "--dry-run", "domains", "list"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_api_key_from_secret_command_and_key_file() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let cli = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.args(args);
+        cmd
+    };
+    let authorization = |lines: Vec<String>| {
+        lines
+            .into_iter()
+            .find(|l| l.to_ascii_lowercase().starts_with("authorization:"))
+    };
+
+    cli(&["config", "--api-key-command", "echo re_from_command"])
+        .assert()
+        .success();
+    let config_path = dir.path().join(".resend-cli").join("config.json");
+    let content = std::fs::read_to_string(&config_path)?;
+    assert!(!content.contains("\"api_key\""));
+    let mode = std::fs::metadata(&config_path)?.permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let (base_url, requests) = serve_once(r#"{"data":[]}"#);
+    cli(&["--base-url", &base_url, "domains", "list"])
+        .assert()
+        .success();
+    assert_eq!(
+        authorization(requests.recv()?).as_deref(),
+        Some("authorization: Bearer re_from_command")
+    );
+
+    let key_file = dir.path().join("api-key");
+    std::fs::write(&key_file, "re_from_file\n")?;
+    let (base_url, requests) = serve_once(r#"{"data":[]}"#);
+    cli(&["--base-url", &base_url, "domains", "list"])
+        .env("RESEND_API_KEY_FILE", &key_file)
+        .assert()
+        .success();
+    assert_eq!(
+        authorization(requests.recv()?).as_deref(),
+        Some("authorization: Bearer re_from_file")
+    );
+
+    cli(&["config", "--api-key-command", "exit 1"])
+        .assert()
+        .success();
+    let assert = cli(&["domains", "list"]).assert().failure();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("API key command 'exit 1' failed"));
+    Ok(())
+}
+
+#[test]
+fn test_whoami_reports_key_source_and_access() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let cli = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.env("RESEND_API_KEY", "re_sending_only_1234").args(args);
+        cmd
+    };
+
+    let assert = cli(&["-o", "json", "config", "show"]).assert().success();
+    let shown: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout)?;
+    assert_eq!(shown["source"]["type"], "env_var");
+    assert_eq!(shown["api_key"], "re_...1234");
+
+    let (base_url, requests) = serve_once_with_status(
+        "401 Unauthorized",
+        r#"{"statusCode":401,"name":"restricted_api_key","message":"This API key is restricted to only send emails"}"#,
+    );
+    let assert = cli(&["--base-url", &base_url, "-o", "json", "whoami"])
+        .assert()
+        .success();
+    let identity: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout)?;
+    assert_eq!(identity["access"], "sending_access");
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("can only send emails"));
+    assert_eq!(requests.recv()?[0], "GET /api-keys?limit=1 HTTP/1.1");
+
+    let (base_url, _requests) = serve_once_with_status(
+        "403 Forbidden",
+        r#"{"statusCode":403,"name":"invalid_api_key","message":"API key is invalid"}"#,
+    );
+    let assert = cli(&["--base-url", &base_url, "config", "validate"])
+        .assert()
+        .failure();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("The API key from RESEND_API_KEY environment variable is invalid"));
+    Ok(())
+}
+
+#[test]
+fn test_project_file_supplies_default_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let nested = dir.path().join("app").join("emails");
+    std::fs::create_dir_all(&nested)?;
+    let cli = |cwd: &Path, args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.current_dir(cwd).args(args);
+        cmd
+    };
+
+    cli(
+        dir.path(),
+        &["config", "init", "--from", "Acme <hi@acme.com>"],
+    )
+    .args(["--reply-to", "support@acme.com", "--segment-id", "seg_1"])
+    .assert()
+    .success();
+    cli(dir.path(), &["config", "init"]).assert().failure();
+
+    // Defaults are found from a subdirectory
+    let assert = cli(&nested, &["--dry-run", "broadcasts", "create"])
+        .args(["--name", "Launch", "--subject", "Hi", "--text", "Hello"])
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("\"segment_id\": \"seg_1\""));
+    assert!(stderr.contains("\"from\": \"Acme <hi@acme.com>\""));
+    assert!(stderr.contains("\"support@acme.com\""));
+
+    // Explicit flags take precedence
+    let assert = cli(&nested, &["--dry-run", "emails", "send"])
+        .args(["--from", "ops@acme.com", "-t", "a@example.com"])
+        .args(["--subject", "Hi", "--text", "Hello"])
+        .assert()
+        .success();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("\"from\": \"ops@acme.com\""));
+    assert!(stderr.contains("\"support@acme.com\""));
+
+    // Without a default, the command asks for the argument
+    let assert = cli(&nested, &["--dry-run", "domains", "verify"])
+        .assert()
+        .failure();
+    let stderr = std::str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("No domain"));
+    Ok(())
+}
+
+#[test]
+fn test_completions_and_man_page() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::TempDir::new()?;
+    let cli = |args: &[&str]| {
+        let mut cmd = resend(dir.path());
+        cmd.env("RESEND_API_KEY", "re_test").args(args);
+        cmd
+    };
+
+    for (shell, hook) in [
+        ("bash", "complete -F _resend_with_ids"),
+        ("zsh", "compdef _resend_with_ids resend"),
+        ("fish", "__fish_resend_complete_ids"),
+    ] {
+        let assert = cli(&["completions", shell]).assert().success();
+        let script = std::str::from_utf8(&assert.get_output().stdout)?;
+        assert!(script.contains("broadcasts"));
+        assert!(script.contains(hook));
+    }
+    let assert = cli(&["man"]).assert().success();
+    assert!(std::str::from_utf8(&assert.get_output().stdout)?.starts_with(".ie"));
+
+    // Nothing is cached before domains are listed
+    let assert = cli(&["__complete-ids", "domains", "verify"])
+        .assert()
+        .success();
+    assert!(assert.get_output().stdout.is_empty());
+
+    let (base_url, _requests) = serve_once(
+        r#"{"data":[{"id":"dom_1","name":"acme.com","created_at":"2023-01-01","status":"verified","region":"us-east-1"}]}"#,
+    );
+    cli(&["--base-url", &base_url, "domains", "list"])
+        .assert()
+        .success();
+    let assert = cli(&[
+        "__complete-ids",
+        "--base-url",
+        &base_url,
+        "domains",
+        "verify",
+    ])
+    .assert()
+    .success();
+    assert_eq!(
+        std::str::from_utf8(&assert.get_output().stdout)?,
+        "dom_1\tacme.com\n"
+    );
+    let assert = cli(&["__complete-ids", "domains", "verify", "dom_1"])
+        .assert()
+        .success();
+    assert!(assert.get_output().stdout.is_empty());
+
+    // The IDs listed from another server are not offered
+    let assert = cli(&["__complete-ids", "domains", "verify"])
+        .assert()
+        .success();
+    assert!(assert.get_output().stdout.is_empty());
+    Ok(())
+}